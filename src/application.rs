@@ -14,25 +14,45 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::Duration;
+
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gettextrs::gettext;
+use gtk::glib::variant::{FromVariant, StaticVariantType};
 use gtk::{gio, glib};
 
-use crate::client::MQTTyClient;
+use crate::client::{MQTTyClient, MQTTyClientError, MQTTyMessageStore};
 use crate::config;
 use crate::display_mode::{MQTTyDisplayMode, MQTTyDisplayModeIface};
 use crate::gsettings::MQTTySettingConnection;
 use crate::main_window::MQTTyWindow;
+use crate::objects::{MQTTySubscription, MQTTyTlsSettings, MQTTyWillSettings};
 use crate::pages::{MQTTyAddConnPage, MQTTyAllConnPage, MQTTyBasePage, MQTTyPanelPage};
 use crate::widgets::{
-    MQTTyAddConnCard, MQTTyBaseCard, MQTTyConnCard, MQTTyEditConnListBox, MQTTyKeyValueRow,
-    MQTTyPublishAuthTab, MQTTyPublishBodyTab, MQTTyPublishGeneralTab, MQTTyPublishUserPropsTab,
-    MQTTyPublishView, MQTTySourceView,
+    MQTTyAddConnCard, MQTTyBaseCard, MQTTyBrokerCapabilitiesGroup, MQTTyConnCard,
+    MQTTyEditConnListBox, MQTTyHexView, MQTTyJsonSchemaRow, MQTTyJsonSchemasGroup,
+    MQTTyKeyValueRow, MQTTyMessageDetail, MQTTyMessageRow, MQTTyPayloadFormatsGroup,
+    MQTTyProtobufTypesGroup, MQTTyPublishAuthTab, MQTTyPublishBodyTab, MQTTyPublishGeneralTab,
+    MQTTyPublishPropertiesTab, MQTTyPublishResponseTab, MQTTyPublishUserPropsTab, MQTTyPublishView,
+    MQTTyPublishWillTab, MQTTyReplayDialog, MQTTySourceView, MQTTySubscriptionsView,
+    MQTTyTlsSettingsGroup, MQTTyTopicNodeRow, MQTTyTopicTreeView,
 };
 
+/// Interval in which the connection pool is checked for idle clients
+const CLIENT_POOL_SWEEP_INTERVAL: u32 = 10;
+
+/// Connection attempt of a client that is about to join the pool, shared by everyone that
+/// asks for an equivalent client meanwhile
+type PendingClient = Shared<LocalBoxFuture<'static, Result<MQTTyClient, MQTTyClientError>>>;
+
+/// Maximum time to wait for each client to disconnect when the application shuts down
+const CLIENT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
 mod imp {
 
     use super::*;
@@ -44,18 +64,27 @@ mod imp {
         /// The type of items inside of ListStore is MQTTySettingConnection
         pub settings_conns: OnceCell<gio::ListStore>,
 
-        /// This clients are index mapped 1-to-1 to the settings_conns, they are separated
-        /// because they cannot be tupled and passed to a gio::ListStore, and also because
-        /// we are disconnecting the corresponding client if any MQTTySettingConnection was
-        /// deleted, because the settings_conns::items-changed passes the already mutated list,
-        /// we have to search for the mqtt connection in this Vec, disconnect it and remove it.
+        /// Connection pool, every client in here is connected (or was connected) to a broker
+        /// and can be reused by anyone that needs a client with equivalent connection
+        /// options, see MQTTyClient::is_equivalent()
         ///
         /// IMPORTANT:
         ///
-        /// Listen to settings_conns::items-changed signal for connections removals or
-        /// connections additions, and act accordingly (by disconnecting the MQTT client or
-        /// connecting a new one, respectively)
+        /// Do not push clients directly into this Vec, use MQTTyApplication::pooled_client()
+        /// instead, idle clients that nobody else holds are disconnected and removed
+        /// periodically by the application, see MQTTyApplication::setup_clients_pool()
         pub clients: Rc<RefCell<Vec<MQTTyClient>>>,
+
+        /// Clients of the pool that are still connecting, see
+        /// MQTTyApplication::pooled_client()
+        pub pending_clients: RefCell<Vec<(MQTTyClient, PendingClient)>>,
+
+        /// Clients that are not part of the pool but must be disconnected when the
+        /// application shuts down, see MQTTyApplication::register_client()
+        pub registered_clients: RefCell<Vec<glib::WeakRef<MQTTyClient>>>,
+
+        /// Message store of every connection that sent or received messages, keyed by URL
+        pub message_stores: RefCell<HashMap<String, MQTTyMessageStore>>,
     }
 
     #[glib::object_subclass]
@@ -69,6 +98,9 @@ mod imp {
 
             MQTTyWindow::static_type();
             MQTTySettingConnection::static_type();
+            MQTTyTlsSettings::static_type();
+            MQTTyWillSettings::static_type();
+            MQTTySubscription::static_type();
 
             // Widgets
             MQTTyBaseCard::static_type();
@@ -77,12 +109,28 @@ mod imp {
             MQTTyEditConnListBox::static_type();
             MQTTySourceView::static_type();
             MQTTyKeyValueRow::static_type();
+            MQTTyTlsSettingsGroup::static_type();
+            MQTTyBrokerCapabilitiesGroup::static_type();
+            MQTTyMessageRow::static_type();
+            MQTTyMessageDetail::static_type();
+            MQTTySubscriptionsView::static_type();
+            MQTTyTopicNodeRow::static_type();
+            MQTTyTopicTreeView::static_type();
+            MQTTyReplayDialog::static_type();
+            MQTTyPayloadFormatsGroup::static_type();
+            MQTTyProtobufTypesGroup::static_type();
+            MQTTyHexView::static_type();
+            MQTTyJsonSchemaRow::static_type();
+            MQTTyJsonSchemasGroup::static_type();
 
             MQTTyPublishView::static_type();
             MQTTyPublishGeneralTab::static_type();
             MQTTyPublishBodyTab::static_type();
             MQTTyPublishUserPropsTab::static_type();
             MQTTyPublishAuthTab::static_type();
+            MQTTyPublishWillTab::static_type();
+            MQTTyPublishPropertiesTab::static_type();
+            MQTTyPublishResponseTab::static_type();
 
             // Pages
             MQTTyBasePage::static_type();
@@ -130,6 +178,25 @@ mod imp {
             app.setup_accels();
 
             app.setup_settings();
+            app.setup_clients_pool();
+        }
+
+        fn shutdown(&self) {
+            let app = self.obj();
+
+            // The main loop is not running anymore, so we block until every client is
+            // disconnected, otherwise the brokers will keep the sessions dangling
+            for client in app.clients().take() {
+                client.disconnect_client_blocking(CLIENT_SHUTDOWN_TIMEOUT);
+            }
+
+            for client in self.registered_clients.take() {
+                if let Some(client) = client.upgrade() {
+                    client.disconnect_client_blocking(CLIENT_SHUTDOWN_TIMEOUT);
+                }
+            }
+
+            self.parent_shutdown();
         }
     }
 
@@ -190,77 +257,210 @@ impl MQTTyApplication {
         &self.imp().clients
     }
 
+    /// Returns a connected client from the connection pool that is equivalent to `client`,
+    /// if there is none, `client` gets connected and added to the pool. If an equivalent
+    /// client is already connecting, its connection attempt is awaited instead, so the
+    /// pool never holds two connections with the same client ID.
+    ///
+    /// `client` is expected to be a freshly created client, that is not connected yet.
+    pub async fn pooled_client(
+        &self,
+        client: &MQTTyClient,
+    ) -> Result<MQTTyClient, MQTTyClientError> {
+        let clients = self.clients();
+
+        let pooled = clients
+            .borrow()
+            .iter()
+            .find(|c| c.is_equivalent(client))
+            .cloned();
+
+        if let Some(pooled) = pooled {
+            if pooled.is_connected() {
+                // Keeps the idle sweep from disconnecting it before the caller uses it
+                pooled.touch();
+                return Ok(pooled);
+            }
+
+            // Connection was lost, we discard it and connect the new one. It's
+            // disconnected first, so that it stops trying to reconnect
+            clients.borrow_mut().retain(|c| c != &pooled);
+
+            if let Err(e) = pooled.disconnect_client().await {
+                tracing::warn!("Could not disconnect stale client: {e}");
+            }
+        }
+
+        let pending = self
+            .imp()
+            .pending_clients
+            .borrow()
+            .iter()
+            .find(|(c, _)| c.is_equivalent(client))
+            .map(|(_, pending)| pending.clone());
+
+        if let Some(pending) = pending {
+            return pending.await;
+        }
+
+        // The pool is updated inside of the shared future, so it happens even if the
+        // caller that started the connection attempt drops it
+        let pending = {
+            let app = self.clone();
+            let client = client.clone();
+
+            async move {
+                let result = client.connect_client().await;
+
+                app.imp()
+                    .pending_clients
+                    .borrow_mut()
+                    .retain(|(c, _)| c != &client);
+
+                result?;
+
+                client.touch();
+                app.clients().borrow_mut().push(client.clone());
+
+                Ok(client)
+            }
+            .boxed_local()
+            .shared()
+        };
+
+        self.imp()
+            .pending_clients
+            .borrow_mut()
+            .push((client.clone(), pending.clone()));
+
+        pending.await
+    }
+
+    /// Registers a client that is not part of the connection pool, so it gets disconnected
+    /// gracefully when the application shuts down and the broker doesn't publish its will.
+    /// Only a weak reference is kept, the client is forgotten once it's dropped
+    pub fn register_client(&self, client: &MQTTyClient) {
+        let mut clients = self.imp().registered_clients.borrow_mut();
+
+        clients.retain(|c| c.upgrade().is_some());
+        clients.push(client.downgrade());
+    }
+
+    /// Returns the message store of the connection to `url`, it's shared by everyone that
+    /// sends or receives messages through that connection
+    pub fn message_store(&self, url: &str) -> MQTTyMessageStore {
+        if let Some(store) = self.imp().message_stores.borrow().get(url) {
+            return store.clone();
+        }
+
+        let store = MQTTyMessageStore::new(url);
+
+        let settings = self.settings();
+        settings
+            .bind("message-buffer-size", &store, "capacity")
+            .get()
+            .build();
+        settings
+            .bind("persist-messages", &store, "persistent")
+            .get()
+            .build();
+
+        self.imp()
+            .message_stores
+            .borrow_mut()
+            .insert(url.to_string(), store.clone());
+
+        store
+    }
+
+    /// Periodically disconnects and removes from the pool the clients that have been idle
+    /// for longer than the "client-idle-timeout" setting
+    fn setup_clients_pool(&self) {
+        glib::timeout_add_seconds_local(
+            CLIENT_POOL_SWEEP_INTERVAL,
+            glib::clone!(
+                #[weak(rename_to = app)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    let timeout =
+                        Duration::from_secs(app.settings().uint("client-idle-timeout").into());
+
+                    let idle = app
+                        .clients()
+                        .borrow()
+                        .iter()
+                        // Clients that someone else still holds, e.g. a publish tab,
+                        // are in use even if they didn't send anything lately
+                        .filter(|c| c.ref_count() == 1 && c.is_idle(timeout))
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    if idle.is_empty() {
+                        return glib::ControlFlow::Continue;
+                    }
+
+                    app.clients().borrow_mut().retain(|c| !idle.contains(c));
+
+                    glib::spawn_future_local(async move {
+                        for client in idle {
+                            if let Err(e) = client.disconnect_client().await {
+                                tracing::warn!("Could not disconnect idle client: {e}");
+                            }
+                        }
+                    });
+
+                    glib::ControlFlow::Continue
+                }
+            ),
+        );
+    }
+
     /// We are only requesting the GSettings on startup to prevent infinite recursion,
     /// e.g. app.settings_connections()::items-changed it's emitted, it is saved to
     /// external GSettings, GSettings::changed it's emitted, app.settings_connections() gets
     /// updated with external settings,
     /// app.settings_connections()::items-changed it's emitted again, etc.
     fn setup_settings(&self) {
-        // let settings = self.settings();
-        //
-        // let external_conns = settings.get::<Vec<MQTTySettingConnection>>("connections");
-        //
-        // let app_conns = self.settings_connections();
-        //
-        // app_conns.extend_from_slice(&external_conns);
-        //
-        // let clients_ref = self.clients();
-        //
-        // let mut clients_mut = clients_ref.borrow_mut();
-        // clients_mut.reserve(external_conns.len());
-        //
-        // for conn in app_conns
-        //     .iter::<MQTTySettingConnection>()
-        //     .map(|i| i.unwrap())
-        // {
-        //     let connection = MQTTyClient::new(&conn);
-        //
-        //     clients_mut.push(connection);
-        // }
-        //
-        // // Save settings to external GSettings, and creating MQTT clients for each one
-        // app_conns.connect_items_changed(glib::clone!(
-        //     #[strong]
-        //     settings,
-        //     #[strong]
-        //     clients_ref,
-        //     move |list: &gio::ListStore, pos, rem, add| {
-        //         let mut clients_mut = clients_ref.borrow_mut();
-        //
-        //         settings
-        //             .set(
-        //                 "connections",
-        //                 list.iter::<MQTTySettingConnection>()
-        //                     .map(|i| i.unwrap().downcast::<MQTTySettingConnection>().unwrap())
-        //                     .collect::<Vec<_>>(),
-        //             )
-        //             .unwrap();
-        //
-        //         let pos = pos as usize;
-        //         let rem = rem as usize;
-        //         let add = add as usize;
-        //
-        //         // Removals
-        //         for client in clients_mut.splice(pos..pos + rem, None) {
-        //             client.disconnect_client();
-        //         }
-        //
-        //         // Additions
-        //         clients_mut.reserve(add);
-        //
-        //         for i in pos..pos + add {
-        //             let new_client = MQTTyClient::new(
-        //                 &list
-        //                     .item(i as u32)
-        //                     .unwrap()
-        //                     .downcast::<MQTTySettingConnection>()
-        //                     .unwrap(),
-        //             );
-        //
-        //             clients_mut.insert(i, new_client);
-        //         }
-        //     }
-        // ));
+        let settings = self.settings();
+
+        let app_conns = self.settings_connections();
+
+        app_conns.extend_from_slice(
+            &settings
+                .value("saved-connections")
+                .iter()
+                .filter_map(|v| MQTTySettingConnection::from_variant(&v))
+                .collect::<Vec<_>>(),
+        );
+
+        // Migrate connections saved with the legacy "(ss)" format
+        let legacy_conns = settings
+            .value("connections")
+            .iter()
+            .filter_map(|v| MQTTySettingConnection::from_variant(&v))
+            .collect::<Vec<_>>();
+
+        if !legacy_conns.is_empty() {
+            tracing::info!(
+                "Migrating {} connections from the legacy settings format",
+                legacy_conns.len()
+            );
+
+            app_conns.extend_from_slice(&legacy_conns);
+
+            save_connections(settings, app_conns);
+            settings.reset("connections");
+        }
+
+        app_conns.connect_items_changed(glib::clone!(
+            #[strong]
+            settings,
+            move |list, _, _, _| {
+                save_connections(&settings, list);
+            }
+        ));
     }
 
     fn setup_gactions(&self) {
@@ -300,6 +500,9 @@ impl MQTTyApplication {
     // Sets up keyboard shortcuts
     fn setup_accels(&self) {
         self.set_accels_for_action("app.quit", &["<Control>q"]);
+        self.set_accels_for_action("win.open-workspace", &["<Control>o"]);
+        self.set_accels_for_action("win.save-workspace", &["<Control>s"]);
+        self.set_accels_for_action("win.save-workspace-as", &["<Control><Shift>s"]);
     }
 
     fn setup_css(&self) {
@@ -316,3 +519,20 @@ impl MQTTyApplication {
         // }
     }
 }
+
+fn save_connections(settings: &gio::Settings, conns: &gio::ListStore) {
+    let conns = conns
+        .iter::<MQTTySettingConnection>()
+        .map(|conn| glib::Variant::from(conn.unwrap()))
+        .collect::<Vec<_>>();
+
+    if let Err(e) = settings.set_value(
+        "saved-connections",
+        &glib::Variant::array_from_iter_with_type(
+            &MQTTySettingConnection::static_variant_type(),
+            conns,
+        ),
+    ) {
+        tracing::error!("Could not save connections: {}", e);
+    }
+}
@@ -13,17 +13,43 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+mod broker_capabilities;
+mod error;
 mod message;
+mod message_export;
+mod message_store;
+mod reason_code;
+mod session_player;
+mod session_recorder;
+mod subscribe_options;
+mod topic_node;
+mod topic_tree;
 
-pub use message::MQTTyClientMessage;
+pub use broker_capabilities::MQTTyBrokerCapabilities;
+pub use error::MQTTyClientError;
+pub use message::{MQTTyClientMessage, MQTTyMessageDirection};
+pub use message_export::{export_messages, MQTTyExportFormat};
+pub use message_store::{MQTTyMessageQuery, MQTTyMessageStore};
+pub use reason_code::MQTTyReasonCode;
+pub use session_player::MQTTySessionPlayer;
+pub use session_recorder::MQTTySessionRecorder;
+pub use subscribe_options::{MQTTyRetainHandling, MQTTySubscribeOptions};
+pub use topic_node::MQTTyTopicNode;
+pub use topic_tree::MQTTyTopicTree;
 
 use std::cell::{Cell, OnceCell, RefCell};
 use std::sync::LazyLock;
+use std::time::{Duration, Instant};
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
+use gettextrs::gettext;
 use gtk::glib;
 use gtk::glib::subclass::Signal;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::hex;
+use crate::objects::{MQTTyTlsSettings, MQTTyTlsVersion, MQTTyWillSettings};
 
 #[derive(Default, Clone, Copy, glib::Enum, PartialEq)]
 #[enum_type(name = "MQTTyClientVersion")]
@@ -33,7 +59,157 @@ pub enum MQTTyClientVersion {
     V5,
 }
 
-#[derive(Default, Clone, Copy, glib::Enum)]
+/// Transport used to reach the broker, it's inferred from the URL scheme
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MQTTyUrlScheme {
+    /// tcp:// or mqtt://
+    Tcp,
+    /// ssl:// or mqtts://
+    Tls,
+    /// ws://
+    WebSocket,
+    /// wss://
+    WebSocketTls,
+}
+
+impl MQTTyUrlScheme {
+    /// Returns None if the URL doesn't have a host or its scheme is not supported
+    pub fn parse(url: &str) -> Option<Self> {
+        let (scheme, rest) = url.trim().split_once("://")?;
+
+        if rest.is_empty() || rest.starts_with('/') {
+            return None;
+        }
+
+        match scheme.to_ascii_lowercase().as_str() {
+            "tcp" | "mqtt" => Some(Self::Tcp),
+            "ssl" | "mqtts" => Some(Self::Tls),
+            "ws" => Some(Self::WebSocket),
+            "wss" => Some(Self::WebSocketTls),
+            _ => None,
+        }
+    }
+
+    pub fn is_websocket(&self) -> bool {
+        matches!(self, Self::WebSocket | Self::WebSocketTls)
+    }
+
+    pub fn is_tls(&self) -> bool {
+        matches!(self, Self::Tls | Self::WebSocketTls)
+    }
+}
+
+/// Replaces the path of a WebSocket URL, if `path` is empty, the URL is returned as it is
+/// and the broker default path is used ("/mqtt")
+pub fn websocket_url(url: &str, path: &str) -> String {
+    let path = path.trim();
+
+    if path.is_empty() {
+        return url.to_string();
+    }
+
+    let (scheme, rest) = url.trim().split_once("://").unwrap_or(("ws", url));
+
+    let authority = rest.split('/').next().unwrap_or_default();
+
+    format!("{scheme}://{authority}/{}", path.trim_start_matches('/'))
+}
+
+/// Returns true if `topic` matches the MQTT topic filter, wildcards don't match topics
+/// starting with '$' at the first level
+pub fn topic_matches_filter(topic_filter: &str, topic: &str) -> bool {
+    if topic.starts_with('$') && topic_filter.starts_with(['+', '#']) {
+        return false;
+    }
+
+    let mut filter_levels = topic_filter.split('/');
+    let mut topic_levels = topic.split('/');
+
+    loop {
+        match (filter_levels.next(), topic_levels.next()) {
+            (Some("#"), _) => return true,
+            (Some("+"), Some(_)) => {}
+            (Some(f), Some(t)) if f == t => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Reply to a message sent with [`MQTTyClient::request()`]
+pub struct MQTTyClientResponse {
+    /// Message that was published, with the response topic and correlation data that
+    /// were used
+    pub request: MQTTyClientMessage,
+
+    pub message: MQTTyClientMessage,
+
+    /// Time elapsed between publishing the request and receiving the response
+    pub latency: Duration,
+}
+
+/// Returns `len` random bytes, not suitable for cryptographic purposes
+fn random_bytes(len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|_| glib::random_int_range(0, 256) as u8)
+        .collect()
+}
+
+/// Expands the placeholders of a client ID template:
+/// - `{random}`: 8 random hexadecimal characters
+/// - `{hostname}`: host name of this machine
+/// - `{user}`: name of the current user
+/// - `{timestamp}`: seconds since the Unix epoch
+///
+/// Text without placeholders is returned as it is, so fixed client IDs are also
+/// valid templates
+pub fn client_id_from_template(template: &str) -> String {
+    let mut client_id = template.trim().to_string();
+
+    if client_id.contains("{random}") {
+        client_id = client_id.replace("{random}", &format!("{:08x}", glib::random_int()));
+    }
+
+    if client_id.contains("{hostname}") {
+        client_id = client_id.replace("{hostname}", &glib::host_name());
+    }
+
+    if client_id.contains("{user}") {
+        client_id = client_id.replace("{user}", &glib::user_name().to_string_lossy());
+    }
+
+    if client_id.contains("{timestamp}") {
+        let timestamp = glib::real_time() / 1_000_000;
+        client_id = client_id.replace("{timestamp}", &timestamp.to_string());
+    }
+
+    client_id
+}
+
+#[derive(Default, Clone, Copy, glib::Enum, PartialEq, Debug)]
+#[enum_type(name = "MQTTyClientState")]
+pub enum MQTTyClientState {
+    #[default]
+    Disconnected,
+    Connecting,
+    Connected,
+    Reconnecting,
+}
+
+/// Delay before the first reconnection attempt, it gets doubled on every failed attempt
+const RECONNECT_MIN_DELAY: Duration = Duration::from_secs(1);
+
+/// Upper bound for the exponential backoff between reconnection attempts
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Returns the delay before the nth (starting from 1) reconnection attempt
+fn reconnect_delay(attempt: u32) -> Duration {
+    RECONNECT_MIN_DELAY
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(RECONNECT_MAX_DELAY)
+}
+
+#[derive(Default, Clone, Copy, glib::Enum, PartialEq, Debug)]
 #[enum_type(name = "MQTTyClientQos")]
 pub enum MQTTyClientQos {
     #[default]
@@ -42,6 +218,54 @@ pub enum MQTTyClientQos {
     Qos2,
 }
 
+impl MQTTyClientQos {
+    pub fn listed() -> &'static [MQTTyClientQos] {
+        &[
+            MQTTyClientQos::Qos0,
+            MQTTyClientQos::Qos1,
+            MQTTyClientQos::Qos2,
+        ]
+    }
+}
+
+/// Serialized as 0, 1 or 2
+impl Serialize for MQTTyClientQos {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for MQTTyClientQos {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match u8::deserialize(deserializer)? {
+            0 => Ok(Self::Qos0),
+            1 => Ok(Self::Qos1),
+            2 => Ok(Self::Qos2),
+            qos => Err(de::Error::custom(format!("invalid QoS {qos}"))),
+        }
+    }
+}
+
+/// Serialized as 3 (MQTT v3.x) or 5
+impl Serialize for MQTTyClientVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(match self {
+            Self::V3X => 3,
+            Self::V5 => 5,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for MQTTyClientVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match u8::deserialize(deserializer)? {
+            3 => Ok(Self::V3X),
+            5 => Ok(Self::V5),
+            version => Err(de::Error::custom(format!("invalid MQTT version {version}"))),
+        }
+    }
+}
+
 mod imp {
 
     use super::*;
@@ -61,7 +285,74 @@ mod imp {
         #[property(get, construct_only)]
         password: RefCell<String>,
 
-        client: OnceCell<paho::AsyncClient>,
+        /// Client identifier sent to the broker, if empty the broker will assign one
+        #[property(get, construct_only)]
+        client_id: RefCell<String>,
+
+        /// TLS options, only used when connecting to secure URLs
+        #[property(get, construct_only)]
+        tls: RefCell<MQTTyTlsSettings>,
+
+        /// If disabled, the broker resumes the previous session of this client ID, it's
+        /// called "clean session" on MQTT v3.x. Only read when connecting
+        #[property(get, set, construct, default = true)]
+        clean_start: Cell<bool>,
+
+        /// Maximum seconds between control packets sent to the broker, 0 disables the
+        /// keep alive mechanism. Only read when connecting
+        #[property(get, set, construct, default = 60)]
+        keep_alive_interval: Cell<u32>,
+
+        /// Seconds the broker keeps the session after the client disconnects, only
+        /// MQTT v5, on MQTT v3.x this is controlled by :clean-start. Only read when
+        /// connecting
+        #[property(get, set)]
+        session_expiry_interval: Cell<u32>,
+
+        /// Session present flag of the last CONNACK, true if the broker resumed a
+        /// previous session
+        #[property(get)]
+        session_present: Cell<bool>,
+
+        /// Capabilities announced by the broker in the last CONNACK, None while the
+        /// client is not connected
+        #[property(get, nullable)]
+        capabilities: RefCell<Option<MQTTyBrokerCapabilities>>,
+
+        /// Last Will and Testament, only sent to the broker if it's enabled
+        #[property(get, construct_only)]
+        will: RefCell<MQTTyWillSettings>,
+
+        /// Extra HTTP headers sent in the WebSocket handshake, only used when connecting
+        /// to ws:// and wss:// URLs
+        pub http_headers: RefCell<Vec<(String, String)>>,
+
+        /// Extra WebSocket subprotocol requested in the handshake, only used when
+        /// connecting to ws:// and wss:// URLs. Only read when connecting.
+        ///
+        /// NOTE: The underlying MQTT library always requests "mqtt", so this one is sent
+        /// as an additional Sec-WebSocket-Protocol header and the broker picks one of them
+        #[property(get, set)]
+        ws_subprotocol: RefCell<String>,
+
+        #[property(get, builder(MQTTyClientState::Disconnected))]
+        state: Cell<MQTTyClientState>,
+
+        /// If enabled, the client tries to reconnect when the connection is lost
+        #[property(get, set)]
+        auto_reconnect: Cell<bool>,
+
+        /// Maximum number of reconnection attempts, 0 means no limit
+        #[property(get, set, construct, default = 5)]
+        reconnect_max_retries: Cell<u32>,
+
+        /// Holds the error if the client could not be created, e.g. the URL is invalid,
+        /// in that case every method returns that error
+        client: OnceCell<Result<paho::AsyncClient, MQTTyClientError>>,
+
+        /// Last time this client published, subscribed or received a message, used by
+        /// the application connection pool for closing idle connections
+        last_activity: Cell<Option<Instant>>,
     }
 
     #[glib::object_subclass]
@@ -80,11 +371,29 @@ mod imp {
 
             let obj = self.obj();
 
+            let url = obj.url();
+
+            if MQTTyUrlScheme::parse(&url).is_none() {
+                self.client
+                    .set(Err(MQTTyClientError::InvalidUrl(url.trim().to_string())))
+                    .ok()
+                    .unwrap();
+                return;
+            }
+
             let client = match paho::CreateOptionsBuilder::new()
-                .server_uri(obj.url())
+                .server_uri(url.trim())
+                .client_id(obj.client_id())
                 .create_client()
             {
-                Err(e) => panic!("CLIENT CREATION ERROR {:?}", e),
+                Err(e) => {
+                    tracing::error!("Could not create client for {url}: {e}");
+                    self.client
+                        .set(Err(MQTTyClientError::InvalidUrl(url.trim().to_string())))
+                        .ok()
+                        .unwrap();
+                    return;
+                }
                 Ok(c) => c,
             };
 
@@ -96,98 +405,535 @@ mod imp {
                 let _ = message_tx.send_blocking(msg);
             });
 
-            glib::spawn_future_local(glib::clone!(
-                #[weak]
-                obj,
-                async move {
-                    loop {
-                        let Ok(msg) = message_rx.recv().await else {
-                            return;
-                        };
+            // The client is upgraded for every message, the loop must not keep it alive
+            let weak_obj = obj.downgrade();
+
+            glib::spawn_future_local(async move {
+                loop {
+                    let Ok(msg) = message_rx.recv().await else {
+                        return;
+                    };
+
+                    let Some(obj) = weak_obj.upgrade() else {
+                        return;
+                    };
+
+                    println!("{:?}", msg);
+
+                    obj.imp().touch();
+
+                    let out_msg = MQTTyClientMessage::new();
+
+                    let props = msg.properties();
+
+                    out_msg.set_topic(msg.topic());
+                    out_msg.set_qos(MQTTyClientQos::from(msg.qos()));
+                    out_msg.set_body(msg.payload());
+                    out_msg.set_mqtt_version(obj.mqtt_version());
+                    out_msg.set_content_type(props.get_string(paho::PropertyCode::ContentType));
+                    out_msg.set_retained(msg.retained());
+                    out_msg.set_timestamp(glib::DateTime::now_local().ok());
+                    out_msg.set_user_properties(props.user_iter().collect::<Vec<_>>().as_slice());
+                    out_msg.set_payload_utf8(
+                        props.get_int(paho::PropertyCode::PayloadFormatIndicator) == Some(1),
+                    );
+                    out_msg.set_message_expiry_interval(
+                        props
+                            .get_int(paho::PropertyCode::MessageExpiryInterval)
+                            .unwrap_or_default() as u32,
+                    );
+                    out_msg.set_response_topic(props.get_string(paho::PropertyCode::ResponseTopic));
+                    out_msg.set_correlation_data(
+                        props
+                            .get_binary(paho::PropertyCode::CorrelationData)
+                            .as_deref(),
+                    );
+                    out_msg.set_topic_alias(
+                        props
+                            .get_int(paho::PropertyCode::TopicAlias)
+                            .unwrap_or_default() as u32,
+                    );
+                    out_msg.set_subscription_identifiers(
+                        (0..)
+                            .map_while(|i| {
+                                props.get_int_at(paho::PropertyCode::SubscriptionIdentifier, i)
+                            })
+                            .map(|id| id as u32)
+                            .collect::<Vec<_>>()
+                            .as_slice(),
+                    );
+
+                    obj.emit_by_name::<()>("message", &[&out_msg]);
+                }
+            });
+
+            // Redirecting connection losses to the main loop, the reason is None when
+            // the broker didn't tell us why
+            let (lost_tx, lost_rx) = async_channel::unbounded::<Option<String>>();
+
+            let disconnected_tx = lost_tx.clone();
 
-                        println!("{:?}", msg);
+            client.set_connection_lost_callback(move |_| {
+                let _ = lost_tx.send_blocking(None);
+            });
+
+            // Only MQTT v5 brokers send DISCONNECT packets with a reason
+            client.set_disconnected_callback(move |_, _, reason| {
+                let _ = disconnected_tx.send_blocking(Some(reason.to_string()));
+            });
 
-                        let out_msg = MQTTyClientMessage::new();
+            let weak_obj = obj.downgrade();
 
-                        let props = msg.properties();
+            glib::spawn_future_local(async move {
+                loop {
+                    let Ok(reason) = lost_rx.recv().await else {
+                        return;
+                    };
 
-                        out_msg.set_topic(msg.topic());
-                        out_msg.set_qos(MQTTyClientQos::from(msg.qos()));
-                        out_msg.set_body(msg.payload());
-                        out_msg.set_mqtt_version(obj.mqtt_version());
-                        out_msg.set_content_type(props.get_string(paho::PropertyCode::ContentType));
-                        out_msg.set_retained(msg.retained());
-                        out_msg
-                            .set_user_properties(props.user_iter().collect::<Vec<_>>().as_slice());
+                    let Some(obj) = weak_obj.upgrade() else {
+                        return;
+                    };
 
-                        obj.emit_by_name::<()>("message", &[&out_msg]);
+                    // Both callbacks can be called for the same connection loss
+                    if obj.state() != MQTTyClientState::Connected {
+                        continue;
+                    }
+
+                    let reason = reason.unwrap_or_else(|| gettext("Connection lost"));
+
+                    tracing::warn!("Connection to {} lost: {reason}", obj.url());
+
+                    obj.emit_by_name::<()>("connection-lost", &[&reason]);
+
+                    if obj.auto_reconnect() {
+                        obj.imp().reconnect().await;
+                    } else {
+                        obj.imp().set_state(MQTTyClientState::Disconnected);
+                        obj.emit_by_name::<()>("disconnected", &[]);
                     }
                 }
-            ));
+            });
 
-            self.client.set(client).ok().unwrap();
+            self.client.set(Ok(client)).ok().unwrap();
         }
 
         fn signals() -> &'static [Signal] {
             static SIGNALS: LazyLock<Vec<Signal>> = LazyLock::new(|| {
-                vec![Signal::builder("message")
-                    .param_types([MQTTyClientMessage::static_type()])
-                    .build()]
+                vec![
+                    Signal::builder("message")
+                        .param_types([MQTTyClientMessage::static_type()])
+                        .build(),
+                    Signal::builder("connected").build(),
+                    Signal::builder("connection-lost")
+                        .param_types([String::static_type()])
+                        .build(),
+                    Signal::builder("reconnecting")
+                        .param_types([u32::static_type()])
+                        .build(),
+                    Signal::builder("disconnected").build(),
+                ]
             });
             &*SIGNALS
         }
     }
 
     impl MQTTyClient {
-        fn client(&self) -> &paho::AsyncClient {
-            self.client.get().unwrap()
+        fn client(&self) -> Result<&paho::AsyncClient, MQTTyClientError> {
+            self.client.get().unwrap().as_ref().map_err(Clone::clone)
+        }
+
+        fn set_state(&self, state: MQTTyClientState) {
+            if self.state.replace(state) != state {
+                self.obj().notify_state();
+            }
+        }
+
+        fn set_session_present(&self, session_present: bool) {
+            if self.session_present.replace(session_present) != session_present {
+                self.obj().notify_session_present();
+            }
         }
 
-        pub async fn connect_client(&self) -> Result<(), String> {
-            let client = self.client();
+        fn set_capabilities(&self, capabilities: Option<MQTTyBrokerCapabilities>) {
+            self.capabilities.replace(capabilities);
+            self.obj().notify_capabilities();
+        }
+
+        /// Stores the information sent by the broker in the CONNACK packet
+        fn handle_connack(&self, res: &paho::ServerResponse) {
+            self.set_session_present(res.connect_response().is_some_and(|r| r.session_present));
+            self.set_capabilities(Some(MQTTyBrokerCapabilities::from(res.properties())));
+        }
+
+        /// Converts errors of the underlying client, TCP/TLS connect failures are only
+        /// reported as TLS errors when the URL scheme uses TLS
+        fn client_error(&self, error: paho::Error) -> MQTTyClientError {
+            MQTTyClientError::from_paho(error, MQTTyUrlScheme::parse(&self.obj().url()))
+        }
+
+        pub fn touch(&self) {
+            self.last_activity.set(Some(Instant::now()));
+        }
+
+        pub fn is_connected(&self) -> bool {
+            self.state.get() == MQTTyClientState::Connected
+                && self.client().is_ok_and(|c| c.is_connected())
+        }
 
+        pub fn is_idle(&self, timeout: Duration) -> bool {
+            self.last_activity
+                .get()
+                .is_none_or(|last| last.elapsed() >= timeout)
+        }
+
+        /// Tries to reconnect with exponential backoff, until it succeeds, the maximum
+        /// number of retries is reached, or the client gets disconnected by the user
+        async fn reconnect(&self) {
             let obj = self.obj();
 
-            client
-                .connect(Some(
-                    paho::ConnectOptionsBuilder::with_mqtt_version(obj.mqtt_version())
-                        .user_name(obj.username())
-                        .password(obj.password())
-                        .ssl_options(Default::default())
-                        .finalize(),
-                ))
+            let max_retries = obj.reconnect_max_retries();
+
+            let mut attempt = 0;
+
+            self.set_state(MQTTyClientState::Reconnecting);
+
+            while max_retries == 0 || attempt < max_retries {
+                attempt += 1;
+
+                obj.emit_by_name::<()>("reconnecting", &[&attempt]);
+
+                glib::timeout_future(reconnect_delay(attempt)).await;
+
+                // User disconnected the client while we were waiting
+                if obj.state() != MQTTyClientState::Reconnecting {
+                    return;
+                }
+
+                let Ok(client) = self.client() else {
+                    break;
+                };
+
+                match client.reconnect().await {
+                    Ok(res) => {
+                        self.handle_connack(&res);
+                        self.touch();
+                        self.set_state(MQTTyClientState::Connected);
+                        obj.emit_by_name::<()>("connected", &[]);
+                        return;
+                    }
+                    Err(e) => tracing::warn!("Reconnection attempt {attempt} failed: {e}"),
+                }
+            }
+
+            self.set_state(MQTTyClientState::Disconnected);
+            obj.emit_by_name::<()>("disconnected", &[]);
+        }
+
+        fn connect_options(&self) -> Result<paho::ConnectOptions, MQTTyClientError> {
+            let obj = self.obj();
+
+            let mut builder = paho::ConnectOptionsBuilder::with_mqtt_version(obj.mqtt_version());
+
+            builder
+                .user_name(obj.username())
+                .password(obj.password())
+                .keep_alive_interval(Duration::from_secs(obj.keep_alive_interval() as u64))
+                .ssl_options(paho::SslOptions::try_from(&obj.tls())?);
+
+            match obj.mqtt_version() {
+                MQTTyClientVersion::V3X => {
+                    builder.clean_session(obj.clean_start());
+                }
+                MQTTyClientVersion::V5 => {
+                    builder.clean_start(obj.clean_start());
+
+                    let session_expiry_interval = obj.session_expiry_interval();
+                    if session_expiry_interval > 0 {
+                        let mut props = paho::Properties::new();
+                        props
+                            .push_int(
+                                paho::PropertyCode::SessionExpiryInterval,
+                                session_expiry_interval as i32,
+                            )
+                            .unwrap();
+                        builder.properties(props);
+                    }
+                }
+            }
+
+            let will = obj.will();
+            if will.enabled() {
+                builder.will_message(will_message(&will, obj.mqtt_version())?);
+            }
+
+            let mut http_headers = self.http_headers.borrow().clone();
+
+            let ws_subprotocol = obj.ws_subprotocol();
+            if !ws_subprotocol.is_empty() {
+                http_headers.push(("Sec-WebSocket-Protocol".to_string(), ws_subprotocol));
+            }
+
+            if !http_headers.is_empty() {
+                builder.http_headers(http_headers.as_slice());
+            }
+
+            Ok(builder.finalize())
+        }
+
+        pub async fn connect_client(&self) -> Result<(), MQTTyClientError> {
+            let client = self.client()?;
+
+            let obj = self.obj();
+
+            let connect_options = self.connect_options()?;
+
+            self.set_state(MQTTyClientState::Connecting);
+
+            let ret = client
+                .connect(Some(connect_options))
                 .await
-                .map(|res| println!("CONNECTION SERVER RESPONSE: {res:?}"))
-                .map_err(|e| e.to_string())
+                .map(|res| self.handle_connack(&res))
+                .map_err(|e| self.client_error(e));
+
+            if ret.is_ok() {
+                self.touch();
+                self.set_state(MQTTyClientState::Connected);
+                obj.emit_by_name::<()>("connected", &[]);
+            } else {
+                self.set_state(MQTTyClientState::Disconnected);
+            }
+
+            ret
         }
 
-        pub async fn disconnect_client(&self) -> Result<(), String> {
-            let client = self.client();
+        pub async fn disconnect_client(&self) -> Result<(), MQTTyClientError> {
+            let client = self.client()?;
 
-            client
+            let was_disconnected = self.state.get() == MQTTyClientState::Disconnected;
+
+            // Setting the state first, so that any pending reconnection gets cancelled
+            self.set_state(MQTTyClientState::Disconnected);
+            self.set_session_present(false);
+            self.set_capabilities(None);
+
+            if !client.is_connected() {
+                if !was_disconnected {
+                    self.obj().emit_by_name::<()>("disconnected", &[]);
+                }
+                return Ok(());
+            }
+
+            let ret = client
                 .disconnect(None)
                 .await
                 .map(|res| println!("DISCONNECTION SERVER RESPONSE: {res:?}"))
-                .map_err(|e| e.to_string())
+                .map_err(|e| self.client_error(e));
+
+            self.obj().emit_by_name::<()>("disconnected", &[]);
+
+            ret
         }
 
-        pub async fn publish(&self, message: &MQTTyClientMessage) -> Result<(), String> {
-            let client = self.client();
+        /// Blocks the current thread until the client is disconnected or the timeout
+        /// expires, only meant to be used when the application is shutting down and the
+        /// main loop is no longer available
+        pub fn disconnect_client_blocking(&self, timeout: Duration) {
+            let Ok(client) = self.client() else {
+                return;
+            };
+
+            self.set_state(MQTTyClientState::Disconnected);
+
+            if client.is_connected() {
+                let _ = client.disconnect(None).wait_for(timeout);
+            }
+        }
+
+        pub async fn publish(&self, message: &MQTTyClientMessage) -> Result<(), MQTTyClientError> {
+            let client = self.client()?;
+
+            self.touch();
 
             client
                 .publish(paho::Message::from(message))
                 .await
-                .map_err(|e| e.to_string())
+                .map_err(|e| self.client_error(e))?;
+
+            message.set_direction(MQTTyMessageDirection::Sent);
+            message.set_timestamp(glib::DateTime::now_local().ok());
+
+            Ok(())
+        }
+
+        pub async fn subscribe(
+            &self,
+            topic: &str,
+            options: &MQTTySubscribeOptions,
+        ) -> Result<MQTTyReasonCode, MQTTyClientError> {
+            let client = self.client()?;
+
+            self.touch();
+
+            let qos = paho::QoS::from(options.qos());
+
+            let token = match self.obj().mqtt_version() {
+                MQTTyClientVersion::V3X => client.subscribe(topic, qos),
+                MQTTyClientVersion::V5 => {
+                    let mut props = paho::Properties::new();
+
+                    let subscription_identifier = options.subscription_identifier();
+                    if subscription_identifier > 0 {
+                        props
+                            .push_int(
+                                paho::PropertyCode::SubscriptionIdentifier,
+                                subscription_identifier as i32,
+                            )
+                            .unwrap();
+                    }
+
+                    client.subscribe_with_options(
+                        topic,
+                        qos,
+                        paho::SubscribeOptions::from(options),
+                        props,
+                    )
+                }
+            };
+
+            token
+                .await
+                .map(|res| {
+                    // Granted QoS on MQTT v3.x, reason code on MQTT v5, both share the
+                    // same values
+                    res.subscribe_response()
+                        .map_or(MQTTyReasonCode::from(res.reason_code()), |code| {
+                            MQTTyReasonCode::new(code as u8)
+                        })
+                })
+                .map_err(|e| self.client_error(e))
         }
 
-        pub async fn subscribe(&self, topic: &str, qos: MQTTyClientQos) -> Result<(), String> {
-            let client = self.client();
+        pub async fn unsubscribe(&self, topic: &str) -> Result<MQTTyReasonCode, MQTTyClientError> {
+            let client = self.client()?;
+
+            self.touch();
 
             client
-                .subscribe(topic, qos)
+                .unsubscribe(topic)
                 .await
-                .map(|res| println!("SUBSCRIPTION SERVER RESPONSE: {res:?}"))
-                .map_err(|e| e.to_string())
+                .map(|res| MQTTyReasonCode::from(res.reason_code()))
+                .map_err(|e| self.client_error(e))
+        }
+
+        pub async fn unsubscribe_many(
+            &self,
+            topics: &[String],
+        ) -> Result<Vec<MQTTyReasonCode>, MQTTyClientError> {
+            let client = self.client()?;
+
+            self.touch();
+
+            client
+                .unsubscribe_many(topics)
+                .await
+                .map(|res| match res.unsubscribe_many_response() {
+                    Some(codes) => codes
+                        .into_iter()
+                        .map(|code| MQTTyReasonCode::new(code as u8))
+                        .collect(),
+                    // MQTT v3.x brokers don't send per-topic codes
+                    None => vec![MQTTyReasonCode::from(res.reason_code()); topics.len()],
+                })
+                .map_err(|e| self.client_error(e))
+        }
+
+        pub async fn request(
+            &self,
+            message: &MQTTyClientMessage,
+            timeout: Duration,
+        ) -> Result<MQTTyClientResponse, MQTTyClientError> {
+            let obj = self.obj();
+
+            if obj.mqtt_version() != MQTTyClientVersion::V5 {
+                return Err(MQTTyClientError::Other(gettext(
+                    "Requests are only supported on MQTT v5",
+                )));
+            }
+
+            let request = message.duplicate();
+
+            // Temporary response topics are unsubscribed once the request finishes
+            let (response_topic, temporary) = match request.response_topic() {
+                Some(topic) if !topic.is_empty() => (topic, false),
+                _ => (
+                    format!("MQTTy/response/{}", hex::encode(&random_bytes(8))),
+                    true,
+                ),
+            };
+
+            if response_topic.contains(['+', '#']) {
+                return Err(MQTTyClientError::Other(gettext(
+                    "The response topic cannot contain wildcards",
+                )));
+            }
+
+            let correlation_data = random_bytes(16);
+
+            request.set_response_topic(Some(response_topic.clone()));
+            request.set_correlation_data(Some(&correlation_data));
+
+            let (response_tx, response_rx) = async_channel::bounded(1);
+
+            let handler = obj.connect_message(glib::clone!(
+                #[strong]
+                response_topic,
+                move |_, msg| {
+                    if msg.topic() == response_topic
+                        && msg.correlation_data().as_deref() == Some(&correlation_data)
+                    {
+                        let _ = response_tx.try_send(msg.clone());
+                    }
+                }
+            ));
+
+            let ret = async {
+                let reason_code = self
+                    .subscribe(
+                        &response_topic,
+                        &MQTTySubscribeOptions::new(MQTTyClientQos::Qos1),
+                    )
+                    .await?;
+
+                if !reason_code.is_success() {
+                    return Err(reason_code.into());
+                }
+
+                let start = Instant::now();
+
+                self.publish(&request).await?;
+
+                let message = glib::future_with_timeout(timeout, response_rx.recv())
+                    .await
+                    .map_err(|_| MQTTyClientError::Timeout)?
+                    .map_err(|_| MQTTyClientError::NotConnected)?;
+
+                Ok(MQTTyClientResponse {
+                    request: request.clone(),
+                    message,
+                    latency: start.elapsed(),
+                })
+            }
+            .await;
+
+            obj.disconnect(handler);
+
+            if temporary && self.is_connected() {
+                if let Err(e) = self.unsubscribe(&response_topic).await {
+                    tracing::warn!("Could not unsubscribe from {response_topic}: {e}");
+                }
+            }
+
+            ret
         }
     }
 }
@@ -195,7 +941,16 @@ mod imp {
 glib::wrapper! {
     /// This Object works as an inteface, in case the underlying MQTT library changes,
     /// also, we are using it so that we can emit signals like "connected",
-    /// "connection-error" and "message"
+    /// "connection-lost", "reconnecting", "disconnected" and "message"
+    ///
+    /// Emits "connected" every time a connection (or reconnection) succeeds.
+    ///
+    /// Emits "connection-lost" with a human readable reason when an established connection
+    /// is lost, if :auto-reconnect is enabled, "reconnecting" gets emitted with the attempt
+    /// number before every reconnection attempt.
+    ///
+    /// Emits "disconnected" when the client is disconnected by the user, or when the
+    /// connection was lost and it could not be recovered.
     pub struct MQTTyClient(ObjectSubclass<imp::MQTTyClient>);
 }
 
@@ -205,29 +960,116 @@ impl MQTTyClient {
         mqtt_version: MQTTyClientVersion,
         username: &str,
         password: &str,
+        client_id: &str,
+        tls: &MQTTyTlsSettings,
+        will: &MQTTyWillSettings,
     ) -> Self {
         glib::Object::builder()
             .property("url", url)
             .property("mqtt_version", mqtt_version)
             .property("username", username)
             .property("password", password)
+            .property("client_id", client_id)
+            .property("tls", tls)
+            .property("will", will)
             .build()
     }
 
-    pub async fn connect_client(&self) -> Result<(), String> {
+    /// Returns true if both clients were created with the same connection options, meaning
+    /// that one of them can be used in place of the other one
+    pub fn is_equivalent(&self, other: &MQTTyClient) -> bool {
+        self.url() == other.url()
+            && self.mqtt_version() == other.mqtt_version()
+            && self.username() == other.username()
+            && self.password() == other.password()
+            && self.client_id() == other.client_id()
+            && self.tls().is_equivalent(&other.tls())
+            && self.will().is_equivalent(&other.will())
+            && self.clean_start() == other.clean_start()
+            && self.keep_alive_interval() == other.keep_alive_interval()
+            && self.session_expiry_interval() == other.session_expiry_interval()
+            && self.http_headers() == other.http_headers()
+            && self.ws_subprotocol() == other.ws_subprotocol()
+    }
+
+    pub fn http_headers(&self) -> Vec<(String, String)> {
+        self.imp().http_headers.borrow().clone()
+    }
+
+    /// Must be called before connecting the client
+    pub fn set_http_headers(&self, http_headers: &[(String, String)]) {
+        let mut v = self.imp().http_headers.borrow_mut();
+        v.clear();
+        v.extend_from_slice(http_headers);
+    }
+
+    /// Returns true if the client has an open connection with the broker, this also checks
+    /// the underlying connection, not only the "state" property
+    pub fn is_connected(&self) -> bool {
+        self.imp().is_connected()
+    }
+
+    /// Returns true if the client has not been used for at least `timeout`
+    pub fn is_idle(&self, timeout: Duration) -> bool {
+        self.imp().is_idle(timeout)
+    }
+
+    /// Marks the client as used right now, so it's not considered idle
+    pub fn touch(&self) {
+        self.imp().touch()
+    }
+
+    pub async fn connect_client(&self) -> Result<(), MQTTyClientError> {
         self.imp().connect_client().await
     }
 
-    pub async fn disconnect_client(&self) -> Result<(), String> {
+    pub async fn disconnect_client(&self) -> Result<(), MQTTyClientError> {
         self.imp().disconnect_client().await
     }
 
-    pub async fn publish(&self, message: &MQTTyClientMessage) -> Result<(), String> {
+    pub fn disconnect_client_blocking(&self, timeout: Duration) {
+        self.imp().disconnect_client_blocking(timeout);
+    }
+
+    pub async fn publish(&self, message: &MQTTyClientMessage) -> Result<(), MQTTyClientError> {
         self.imp().publish(message).await
     }
 
-    pub async fn subscribe(&self, topic: &str, qos: MQTTyClientQos) -> Result<(), String> {
-        self.imp().subscribe(topic, qos).await
+    /// Returns the reason code of the SUBACK, note that a failure reason code is
+    /// not an Err
+    pub async fn subscribe(
+        &self,
+        topic: &str,
+        options: &MQTTySubscribeOptions,
+    ) -> Result<MQTTyReasonCode, MQTTyClientError> {
+        self.imp().subscribe(topic, options).await
+    }
+
+    /// Returns the reason code of the UNSUBACK, note that a failure reason code is
+    /// not an Err
+    pub async fn unsubscribe(&self, topic: &str) -> Result<MQTTyReasonCode, MQTTyClientError> {
+        self.imp().unsubscribe(topic).await
+    }
+
+    /// Returns the reason codes of the UNSUBACK, in the same order as `topics`
+    pub async fn unsubscribe_many(
+        &self,
+        topics: &[String],
+    ) -> Result<Vec<MQTTyReasonCode>, MQTTyClientError> {
+        self.imp().unsubscribe_many(topics).await
+    }
+
+    /// Publishes `message` with a Response Topic and random Correlation Data, then waits
+    /// up to `timeout` for the matching response, only MQTT v5.
+    ///
+    /// If the message doesn't have a response topic, a temporary one is generated. The
+    /// response topic cannot contain wildcards.
+    pub async fn request(
+        &self,
+        message: &MQTTyClientMessage,
+        timeout: Duration,
+    ) -> Result<MQTTyClientResponse, MQTTyClientError> {
+        self.imp().request(message, timeout).await
     }
 
     pub fn connect_message(
@@ -240,6 +1082,41 @@ impl MQTTyClient {
             glib::closure_local!(move |o: &Self, msg: &MQTTyClientMessage| cb(o, msg)),
         )
     }
+
+    pub fn connect_connected(&self, cb: impl Fn(&Self) + 'static) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "connected",
+            false,
+            glib::closure_local!(move |o: &Self| cb(o)),
+        )
+    }
+
+    pub fn connect_connection_lost(
+        &self,
+        cb: impl Fn(&Self, &str) + 'static,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "connection-lost",
+            false,
+            glib::closure_local!(move |o: &Self, reason: String| cb(o, &reason)),
+        )
+    }
+
+    pub fn connect_reconnecting(&self, cb: impl Fn(&Self, u32) + 'static) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "reconnecting",
+            false,
+            glib::closure_local!(move |o: &Self, attempt: u32| cb(o, attempt)),
+        )
+    }
+
+    pub fn connect_disconnected(&self, cb: impl Fn(&Self) + 'static) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "disconnected",
+            false,
+            glib::closure_local!(move |o: &Self| cb(o)),
+        )
+    }
 }
 
 /*
@@ -262,6 +1139,44 @@ impl From<&MQTTyClientMessage> for paho::Message {
                 .unwrap();
         }
 
+        if value.payload_utf8() {
+            props
+                .push_int(paho::PropertyCode::PayloadFormatIndicator, 1)
+                .unwrap();
+        }
+
+        let message_expiry_interval = value.message_expiry_interval();
+        if message_expiry_interval > 0 {
+            props
+                .push_int(
+                    paho::PropertyCode::MessageExpiryInterval,
+                    message_expiry_interval as i32,
+                )
+                .unwrap();
+        }
+
+        if let Some(response_topic) = value.response_topic() {
+            props
+                .push_string(paho::PropertyCode::ResponseTopic, &response_topic)
+                .unwrap();
+        }
+
+        if let Some(correlation_data) = value.correlation_data() {
+            props
+                .push_binary(paho::PropertyCode::CorrelationData, correlation_data)
+                .unwrap();
+        }
+
+        let topic_alias = value.topic_alias();
+        if topic_alias > 0 {
+            props
+                .push_int(paho::PropertyCode::TopicAlias, topic_alias as i32)
+                .unwrap();
+        }
+
+        // NOTE: Subscription identifiers are not sent, the broker sets them when
+        // forwarding the message to subscribers
+
         let msg = paho::MessageBuilder::new()
             .topic(value.topic())
             .qos(paho::QoS::from(value.qos()))
@@ -305,6 +1220,231 @@ impl From<&MQTTyClientMessage> for paho::Message {
 //     }
 // }
 
+/// Will message of the CONNECT packet, the will properties are only set on MQTT v5
+fn will_message(
+    will: &MQTTyWillSettings,
+    mqtt_version: MQTTyClientVersion,
+) -> Result<paho::Message, MQTTyClientError> {
+    let mut props = paho::Properties::new();
+
+    // paho sends the properties of the will message as the will properties of
+    // the CONNECT packet
+    if mqtt_version == MQTTyClientVersion::V5 {
+        if let Some(content_type) = will.content_type().mime_type() {
+            props
+                .push_string(paho::PropertyCode::ContentType, content_type)
+                .unwrap();
+        }
+
+        let delay_interval = will.delay_interval();
+        if delay_interval > 0 {
+            props
+                .push_int(paho::PropertyCode::WillDelayInterval, delay_interval as i32)
+                .unwrap();
+        }
+
+        let message_expiry_interval = will.message_expiry_interval();
+        if message_expiry_interval > 0 {
+            props
+                .push_int(
+                    paho::PropertyCode::MessageExpiryInterval,
+                    message_expiry_interval as i32,
+                )
+                .unwrap();
+        }
+
+        for (key, value) in will.user_properties().into_iter() {
+            props
+                .push_string_pair(paho::PropertyCode::UserProperty, &key, &value)
+                .unwrap();
+        }
+    }
+
+    let body = will
+        .content_type()
+        .encode(&will.body(), "")
+        .map_err(MQTTyClientError::InvalidPayload)?;
+
+    Ok(paho::MessageBuilder::new()
+        .topic(will.topic())
+        .qos(paho::QoS::from(will.qos()))
+        .retained(will.retain())
+        .payload(body)
+        .properties(props)
+        .finalize())
+}
+
+impl TryFrom<&MQTTyTlsSettings> for paho::SslOptions {
+    type Error = MQTTyClientError;
+
+    fn try_from(value: &MQTTyTlsSettings) -> Result<Self, Self::Error> {
+        let mut builder = paho::SslOptionsBuilder::new();
+
+        let ca_file = value.ca_file();
+        if !ca_file.is_empty() {
+            builder
+                .trust_store(ca_file)
+                .map_err(|e| MQTTyClientError::Tls(e.to_string()))?;
+        }
+
+        let client_cert_file = value.client_cert_file();
+        if !client_cert_file.is_empty() {
+            builder
+                .key_store(client_cert_file)
+                .map_err(|e| MQTTyClientError::Tls(e.to_string()))?;
+        }
+
+        let client_key_file = value.client_key_file();
+        if !client_key_file.is_empty() {
+            builder
+                .private_key(client_key_file)
+                .map_err(|e| MQTTyClientError::Tls(e.to_string()))?;
+        }
+
+        let client_key_passphrase = value.client_key_passphrase();
+        if !client_key_passphrase.is_empty() {
+            builder.private_key_password(client_key_passphrase);
+        }
+
+        let cipher_list = value.cipher_list();
+        if !cipher_list.is_empty() {
+            builder.enabled_cipher_suites(cipher_list);
+        }
+
+        let alpn_protocols = value.alpn_protocols_list();
+        if !alpn_protocols.is_empty() {
+            builder.alpn_protos(
+                &alpn_protocols
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        builder
+            .enable_server_cert_auth(value.verify_server_cert())
+            .verify(value.verify_hostname())
+            .ssl_version(value.min_tls_version().into());
+
+        Ok(builder.finalize())
+    }
+}
+
+impl MQTTyClientError {
+    /// `scheme` is the one of the URL of the client, paho reports TCP and TLS connect
+    /// failures with the same error
+    fn from_paho(value: paho::Error, scheme: Option<MQTTyUrlScheme>) -> Self {
+        let detail = value.to_string();
+
+        match value {
+            paho::Error::Timeout | paho::Error::TcpConnectTimeout => Self::Timeout,
+            paho::Error::Disconnected => Self::NotConnected,
+            paho::Error::ReasonCode(code) => MQTTyReasonCode::from(code).into(),
+            paho::Error::ConnectReturn(
+                paho::ConnectReturnCode::BadUserNameOrPassword
+                | paho::ConnectReturnCode::NotAuthorized,
+            ) => Self::AuthenticationRefused,
+            paho::Error::TcpTlsConnectFailure if scheme.is_some_and(|s| s.is_tls()) => {
+                Self::Tls(detail)
+            }
+            paho::Error::SslNotSupported | paho::Error::MissingSslOptions => Self::Tls(detail),
+            paho::Error::Failure
+            | paho::Error::TcpConnectCompletionFailure
+            | paho::Error::TcpTlsConnectFailure
+            | paho::Error::SocketError(_)
+            | paho::Error::ReceivedDisconnect(_)
+            | paho::Error::ConnectReturn(_)
+            | paho::Error::Io(_) => Self::Connection(detail),
+            _ => Self::Other(detail),
+        }
+    }
+}
+
+impl From<&paho::Properties> for MQTTyBrokerCapabilities {
+    fn from(value: &paho::Properties) -> Self {
+        let capabilities = MQTTyBrokerCapabilities::default();
+
+        // Properties that are not sent keep their default value
+        let get_int = |code| value.get_int(code).map(|v| v as u32);
+
+        if let Some(maximum_qos) = get_int(paho::PropertyCode::MaximumQos) {
+            capabilities.set_maximum_qos(if maximum_qos == 0 {
+                MQTTyClientQos::Qos0
+            } else {
+                MQTTyClientQos::Qos1
+            });
+        }
+        if let Some(v) = get_int(paho::PropertyCode::RetainAvailable) {
+            capabilities.set_retain_available(v != 0);
+        }
+        if let Some(v) = get_int(paho::PropertyCode::WildcardSubscriptionAvailable) {
+            capabilities.set_wildcard_subscription_available(v != 0);
+        }
+        if let Some(v) = get_int(paho::PropertyCode::SharedSubscriptionAvailable) {
+            capabilities.set_shared_subscription_available(v != 0);
+        }
+        if let Some(v) = get_int(paho::PropertyCode::SubscriptionIdentifiersAvailable) {
+            capabilities.set_subscription_identifiers_available(v != 0);
+        }
+        if let Some(v) = get_int(paho::PropertyCode::TopicAliasMaximum) {
+            capabilities.set_topic_alias_maximum(v);
+        }
+        if let Some(v) = get_int(paho::PropertyCode::ReceiveMaximum) {
+            capabilities.set_receive_maximum(v);
+        }
+        if let Some(v) = get_int(paho::PropertyCode::MaximumPacketSize) {
+            capabilities.set_maximum_packet_size(v);
+        }
+        if let Some(v) = get_int(paho::PropertyCode::ServerKeepAlive) {
+            capabilities.set_server_keep_alive(v);
+        }
+
+        // Paho spells it this way
+        capabilities.set_assigned_client_identifier(
+            value.get_string(paho::PropertyCode::AssignedClientIdentifer),
+        );
+
+        capabilities
+    }
+}
+
+impl From<&MQTTySubscribeOptions> for paho::SubscribeOptions {
+    fn from(value: &MQTTySubscribeOptions) -> Self {
+        paho::SubscribeOptionsBuilder::new()
+            .no_local(value.no_local())
+            .retain_as_published(value.retain_as_published())
+            .retain_handling(value.retain_handling().into())
+            .finalize()
+    }
+}
+
+impl From<MQTTyRetainHandling> for paho::RetainHandling {
+    fn from(value: MQTTyRetainHandling) -> Self {
+        match value {
+            MQTTyRetainHandling::SendOnSubscribe => paho::RetainHandling::SendRetainedOnSubscribe,
+            MQTTyRetainHandling::SendOnNewSubscribe => paho::RetainHandling::SendRetainedOnNew,
+            MQTTyRetainHandling::DontSend => paho::RetainHandling::DontSendRetained,
+        }
+    }
+}
+
+impl From<paho::ReasonCode> for MQTTyReasonCode {
+    fn from(value: paho::ReasonCode) -> Self {
+        MQTTyReasonCode::new(value as u8)
+    }
+}
+
+impl From<MQTTyTlsVersion> for paho::SslVersion {
+    fn from(value: MQTTyTlsVersion) -> Self {
+        match value {
+            MQTTyTlsVersion::Default => paho::SslVersion::Default,
+            MQTTyTlsVersion::Tls10 => paho::SslVersion::Tls_1_0,
+            MQTTyTlsVersion::Tls11 => paho::SslVersion::Tls_1_1,
+            MQTTyTlsVersion::Tls12 => paho::SslVersion::Tls_1_2,
+        }
+    }
+}
+
 impl From<MQTTyClientVersion> for paho::MqttVersion {
     fn from(value: MQTTyClientVersion) -> Self {
         match value {
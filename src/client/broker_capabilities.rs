@@ -0,0 +1,183 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
+use gtk::glib;
+
+use crate::client::{MQTTyClientMessage, MQTTyClientQos};
+
+mod imp {
+
+    use super::*;
+
+    #[derive(glib::Properties)]
+    #[properties(wrapper_type = super::MQTTyBrokerCapabilities)]
+    pub struct MQTTyBrokerCapabilities {
+        #[property(get, set, builder(MQTTyClientQos::Qos2))]
+        maximum_qos: Cell<MQTTyClientQos>,
+
+        #[property(get, set)]
+        retain_available: Cell<bool>,
+
+        #[property(get, set)]
+        wildcard_subscription_available: Cell<bool>,
+
+        #[property(get, set)]
+        shared_subscription_available: Cell<bool>,
+
+        #[property(get, set)]
+        subscription_identifiers_available: Cell<bool>,
+
+        /// 0 means that topic aliases are not accepted
+        #[property(get, set)]
+        topic_alias_maximum: Cell<u32>,
+
+        /// Maximum number of QoS 1 and QoS 2 publications being processed at the same time
+        #[property(get, set)]
+        receive_maximum: Cell<u32>,
+
+        /// 0 means no limit
+        #[property(get, set)]
+        maximum_packet_size: Cell<u32>,
+
+        /// Keep alive in seconds imposed by the broker, 0 means the broker didn't send one
+        #[property(get, set)]
+        server_keep_alive: Cell<u32>,
+
+        /// Client identifier assigned by the broker, only when the client didn't send one
+        #[property(get, set, nullable)]
+        assigned_client_identifier: RefCell<Option<String>>,
+    }
+
+    // Default values are the ones assumed by the MQTT v5 specification when the broker
+    // doesn't send the property
+    impl Default for MQTTyBrokerCapabilities {
+        fn default() -> Self {
+            Self {
+                maximum_qos: Cell::new(MQTTyClientQos::Qos2),
+                retain_available: Cell::new(true),
+                wildcard_subscription_available: Cell::new(true),
+                shared_subscription_available: Cell::new(true),
+                subscription_identifiers_available: Cell::new(true),
+                topic_alias_maximum: Cell::new(0),
+                receive_maximum: Cell::new(65535),
+                maximum_packet_size: Cell::new(0),
+                server_keep_alive: Cell::new(0),
+                assigned_client_identifier: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyBrokerCapabilities {
+        const NAME: &'static str = "MQTTyBrokerCapabilities";
+
+        type Type = super::MQTTyBrokerCapabilities;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyBrokerCapabilities {}
+}
+
+glib::wrapper! {
+    /// Capabilities and limits announced by the broker in the CONNACK packet, MQTT v3.x
+    /// brokers don't announce anything, so every capability is assumed to be available
+    pub struct MQTTyBrokerCapabilities(ObjectSubclass<imp::MQTTyBrokerCapabilities>);
+}
+
+impl Default for MQTTyBrokerCapabilities {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl MQTTyBrokerCapabilities {
+    /// Returns human readable explanations of the limits that `message` exceeds, an
+    /// empty Vec means that the broker should accept the message
+    pub fn violations(&self, message: &MQTTyClientMessage) -> Vec<String> {
+        let mut violations = vec![];
+
+        let maximum_qos = self.maximum_qos();
+        if message.qos() as i32 > maximum_qos as i32 {
+            violations.push(
+                formatx!(
+                    gettext("The broker only supports up to QoS {}"),
+                    maximum_qos as i32
+                )
+                .unwrap_or_default(),
+            );
+        }
+
+        if message.retained() && !self.retain_available() {
+            violations.push(gettext("The broker doesn't support retained messages"));
+        }
+
+        let topic_alias = message.topic_alias();
+        if topic_alias > self.topic_alias_maximum() {
+            violations.push(
+                formatx!(
+                    gettext(
+                        "Topic alias {} is greater than the maximum allowed by the broker ({})"
+                    ),
+                    topic_alias,
+                    self.topic_alias_maximum()
+                )
+                .unwrap_or_default(),
+            );
+        }
+
+        let maximum_packet_size = self.maximum_packet_size();
+        let packet_size = estimated_packet_size(message);
+        if maximum_packet_size > 0 && packet_size > maximum_packet_size as usize {
+            violations.push(
+                formatx!(
+                    gettext(
+                        "The message takes about {} bytes, but the broker accepts up to {} bytes"
+                    ),
+                    packet_size,
+                    maximum_packet_size
+                )
+                .unwrap_or_default(),
+            );
+        }
+
+        violations
+    }
+}
+
+/// Approximate size of the PUBLISH packet of `message`, it's a lower bound, the exact
+/// encoding of properties is up to the MQTT library
+fn estimated_packet_size(message: &MQTTyClientMessage) -> usize {
+    // Fixed header, topic length prefix and packet identifier
+    let headers = 5 + 2 + 2;
+
+    let properties = message.content_type().map_or(0, |c| c.len() + 3)
+        + message.response_topic().map_or(0, |t| t.len() + 3)
+        + message.correlation_data().map_or(0, |c| c.len() + 3)
+        + message
+            .user_properties()
+            .iter()
+            .map(|(k, v)| k.len() + v.len() + 5)
+            .sum::<usize>();
+
+    headers + message.topic().len() + properties + message.body().len()
+}
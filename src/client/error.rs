@@ -0,0 +1,126 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use formatx::formatx;
+use gettextrs::gettext;
+
+use crate::client::MQTTyReasonCode;
+
+/// Errors returned by MQTTyClient methods, the Display implementation is translated
+/// and meant to be shown to the user
+#[derive(Clone, Debug)]
+pub enum MQTTyClientError {
+    /// The URL could not be parsed or has an unsupported scheme
+    InvalidUrl(String),
+
+    /// The broker could not be reached, e.g. DNS resolution or TCP connection failure
+    Connection(String),
+
+    /// TLS handshake failure, or invalid certificates/keys
+    Tls(String),
+
+    Timeout,
+
+    /// The broker rejected the credentials or the client is not authorized
+    AuthenticationRefused,
+
+    /// The operation requires an established connection
+    NotConnected,
+
+    /// The broker answered with a failure reason code
+    ReasonCode(MQTTyReasonCode),
+
+    /// The body could not be encoded to its content type, e.g. invalid JSON for CBOR
+    InvalidPayload(String),
+
+    /// The user cancelled the operation, usually there is no need to tell them
+    Cancelled,
+
+    /// Any other error, the String is already a human readable explanation
+    Other(String),
+}
+
+impl MQTTyClientError {
+    /// Returns true if trying again later could succeed without changing any option,
+    /// e.g. a network failure, as opposed to a bad URL or bad credentials
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Connection(_) | Self::Timeout | Self::NotConnected => true,
+            Self::ReasonCode(code) => matches!(
+                code.value(),
+                // Server unavailable, server busy, server shutting down, keep alive
+                // timeout, quota exceeded, message rate too high and connection rate
+                // exceeded
+                0x88 | 0x89 | 0x8B | 0x8D | 0x97 | 0x96 | 0x9F
+            ),
+            Self::InvalidUrl(_)
+            | Self::Tls(_)
+            | Self::AuthenticationRefused
+            | Self::InvalidPayload(_)
+            | Self::Cancelled
+            | Self::Other(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for MQTTyClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::InvalidUrl(url) if url.is_empty() => gettext("The URL is empty"),
+            Self::InvalidUrl(url) => formatx!(
+                gettext(
+                    "Invalid URL \"{}\", supported schemes are tcp, mqtt, ssl, mqtts, ws and wss"
+                ),
+                url
+            )
+            .unwrap_or_default(),
+            Self::Connection(detail) => {
+                formatx!(gettext("Could not reach the broker: {}"), detail).unwrap_or_default()
+            }
+            Self::Tls(detail) => {
+                formatx!(gettext("Secure connection failed: {}"), detail).unwrap_or_default()
+            }
+            Self::Timeout => gettext("The broker took too long to respond"),
+            Self::AuthenticationRefused => {
+                gettext("The broker refused the username, password or certificate")
+            }
+            Self::NotConnected => gettext("The client is not connected"),
+            Self::ReasonCode(code) => {
+                formatx!(gettext("The broker answered {}"), code).unwrap_or_default()
+            }
+            Self::InvalidPayload(detail) => {
+                formatx!(gettext("Could not encode the body: {}"), detail).unwrap_or_default()
+            }
+            Self::Cancelled => gettext("Cancelled"),
+            Self::Other(msg) => msg.clone(),
+        };
+
+        f.write_str(&msg)
+    }
+}
+
+impl std::error::Error for MQTTyClientError {}
+
+impl From<MQTTyReasonCode> for MQTTyClientError {
+    fn from(code: MQTTyReasonCode) -> Self {
+        match code.value() {
+            // Bad user name or password, not authorized and bad authentication method
+            0x86 | 0x87 | 0x8C => Self::AuthenticationRefused,
+            _ => Self::ReasonCode(code),
+        }
+    }
+}
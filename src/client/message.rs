@@ -14,12 +14,43 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
+use base64::prelude::*;
+use gettextrs::pgettext;
 use gtk::glib;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::client::{MQTTyClientQos, MQTTyClientVersion};
+use crate::payload_decoder::MQTTyDecodedPayload;
+
+#[derive(Default, Clone, Copy, glib::Enum, PartialEq, Debug, Serialize, Deserialize)]
+#[enum_type(name = "MQTTyMessageDirection")]
+#[serde(rename_all = "lowercase")]
+pub enum MQTTyMessageDirection {
+    #[default]
+    Received,
+    Sent,
+}
+
+impl MQTTyMessageDirection {
+    pub fn listed() -> &'static [MQTTyMessageDirection] {
+        &[MQTTyMessageDirection::Received, MQTTyMessageDirection::Sent]
+    }
+
+    pub fn translated(&self) -> String {
+        match self {
+            MQTTyMessageDirection::Received => pgettext("message direction", "Received"),
+            MQTTyMessageDirection::Sent => pgettext("message direction", "Sent"),
+        }
+    }
+}
+
+/// Schema violations and the generations of the schemas and the decoder settings they
+/// were found with
+type CachedViolations = ((u64, u64), Rc<Vec<String>>);
 
 mod imp {
 
@@ -28,6 +59,14 @@ mod imp {
     #[derive(Default, glib::Properties)]
     #[properties(wrapper_type = super::MQTTyClientMessage)]
     pub struct MQTTyClientMessage {
+        /// Stable identifier of the message, a random UUID unless it's set explicitly,
+        /// e.g. when the message is loaded from a message store
+        #[property(get, set)]
+        id: RefCell<String>,
+
+        #[property(get, set, builder(Default::default()))]
+        direction: Cell<MQTTyMessageDirection>,
+
         #[property(get, set)]
         topic: RefCell<String>,
 
@@ -46,6 +85,40 @@ mod imp {
         pub user_properties: RefCell<Vec<(String, String)>>,
 
         pub body: RefCell<Vec<u8>>,
+
+        /// Payload Format Indicator, true if the body is UTF-8 encoded text, only MQTT v5
+        #[property(get, set)]
+        payload_utf8: Cell<bool>,
+
+        /// Lifetime of the message in seconds, 0 means no expiry, only MQTT v5
+        #[property(get, set)]
+        message_expiry_interval: Cell<u32>,
+
+        /// Topic the receiver should publish its response to, only MQTT v5
+        #[property(get, set, nullable)]
+        response_topic: RefCell<Option<String>>,
+
+        /// Only MQTT v5, 0 means no topic alias, the maximum value is 65535
+        #[property(get, set, maximum = u16::MAX as u32)]
+        topic_alias: Cell<u32>,
+
+        /// Only MQTT v5
+        pub correlation_data: RefCell<Option<Vec<u8>>>,
+
+        /// Identifiers of the subscriptions that matched this message, only set on
+        /// received messages, MQTT v5
+        pub subscription_identifiers: RefCell<Vec<u32>>,
+
+        /// Time the message was received or sent, None for messages that were neither
+        #[property(get, set, nullable)]
+        timestamp: RefCell<Option<glib::DateTime>>,
+
+        /// Decoded body and the generation of the decoder settings it was decoded with,
+        /// cleared when the body changes
+        pub decoded_body: RefCell<Option<(u64, Rc<MQTTyDecodedPayload>)>>,
+
+        /// Schema violations of the body, cleared when the body changes
+        pub schema_violations: RefCell<Option<CachedViolations>>,
     }
 
     #[glib::object_subclass]
@@ -58,16 +131,49 @@ mod imp {
     }
 
     #[glib::derived_properties]
-    impl ObjectImpl for MQTTyClientMessage {}
+    impl ObjectImpl for MQTTyClientMessage {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.id.replace(glib::uuid_string_random().to_string());
+        }
+    }
 }
 
 glib::wrapper! {
     /// This type works as a model, that carries all of the data related to a
     /// publish/subscribed MQTT message
     ///
-    /// Implements serialization and deserialization of MQTT messages
+    /// Implements serialization and deserialization of MQTT messages, the JSON
+    /// representation looks like this:
+    ///
+    /// ```json
+    /// {
+    ///   "id": "5f0c4d8e-0b8e-4a43-9c1b-0f6a3f2b9c4d",
+    ///   "direction": "received",
+    ///   "timestamp": "2025-03-01T10:00:00.123456+01:00",
+    ///   "topic": "sensors/kitchen",
+    ///   "qos": 1,
+    ///   "retained": false,
+    ///   "mqtt_version": 5,
+    ///   "content_type": "application/json",
+    ///   "user_properties": [{ "key": "unit", "value": "celsius" }],
+    ///   "payload_utf8": true,
+    ///   "message_expiry_interval": 0,
+    ///   "response_topic": null,
+    ///   "topic_alias": 0,
+    ///   "correlation_data": { "base64": "AAEC" },
+    ///   "subscription_identifiers": [],
+    ///   "body": { "text": "{\"temperature\": 21.5}" }
+    /// }
+    /// ```
+    ///
+    /// `qos` is 0, 1 or 2, and `mqtt_version` is 3 (MQTT v3.x) or 5. Binary fields,
+    /// `body` and `correlation_data`, are `{ "text": ... }` when they are valid UTF-8,
+    /// and `{ "base64": ... }` otherwise. `timestamp` is an ISO 8601 date or null.
     ///
-    /// TODO: add serde functions
+    /// Only `topic` and `body` are required when deserializing, the rest of the fields
+    /// take their default value, and `id` a new random one
     pub struct MQTTyClientMessage(ObjectSubclass<imp::MQTTyClientMessage>);
 }
 
@@ -76,14 +182,82 @@ impl MQTTyClientMessage {
         glib::Object::builder().build()
     }
 
+    /// Returns a new message with the same values
+    pub fn duplicate(&self) -> Self {
+        let message = Self::new();
+
+        for pspec in self.list_properties() {
+            message.set_property_from_value(pspec.name(), &self.property_value(pspec.name()));
+        }
+
+        message.set_body(&self.body());
+        message.set_user_properties(&self.user_properties());
+        message.set_correlation_data(self.correlation_data().as_deref());
+        message.set_subscription_identifiers(&self.subscription_identifiers());
+
+        // Duplicates are new messages, so they don't share the ID
+        message.set_id(glib::uuid_string_random().as_str());
+
+        message
+    }
+
     pub fn body(&self) -> Vec<u8> {
         self.imp().body.borrow().clone()
     }
 
+    /// Body decoded by [`crate::payload_decoder::MQTTyPayloadDecoder::decode_message()`]
+    /// with the settings of `generation`, None if it was not decoded yet or the body
+    /// changed since then
+    pub fn cached_decoded_body(&self, generation: u64) -> Option<Rc<MQTTyDecodedPayload>> {
+        self.imp()
+            .decoded_body
+            .borrow()
+            .as_ref()
+            .filter(|(g, _)| *g == generation)
+            .map(|(_, decoded)| decoded.clone())
+    }
+
+    pub fn cache_decoded_body(&self, generation: u64, decoded: Rc<MQTTyDecodedPayload>) {
+        self.imp().decoded_body.replace(Some((generation, decoded)));
+    }
+
+    /// Violations found by [`crate::json_schema::MQTTyJsonSchemas::message_violations()`]
+    /// with the schemas and decoder settings of `generation`, None if it was not
+    /// validated yet or the body changed since then
+    pub fn cached_schema_violations(&self, generation: (u64, u64)) -> Option<Rc<Vec<String>>> {
+        self.imp()
+            .schema_violations
+            .borrow()
+            .as_ref()
+            .filter(|(g, _)| *g == generation)
+            .map(|(_, violations)| violations.clone())
+    }
+
+    pub fn cache_schema_violations(&self, generation: (u64, u64), violations: Rc<Vec<String>>) {
+        self.imp()
+            .schema_violations
+            .replace(Some((generation, violations)));
+    }
+
+    fn clear_body_caches(&self) {
+        self.imp().decoded_body.take();
+        self.imp().schema_violations.take();
+    }
+
     pub fn set_body(&self, body: &[u8]) {
-        let mut v = self.imp().body.borrow_mut();
-        v.clear();
-        v.extend_from_slice(body);
+        {
+            let mut v = self.imp().body.borrow_mut();
+            v.clear();
+            v.extend_from_slice(body);
+        }
+
+        self.clear_body_caches();
+    }
+
+    /// Same as [`MQTTyClientMessage::set_body()`], but `body` is moved instead of copied
+    pub fn set_body_vec(&self, body: Vec<u8>) {
+        self.imp().body.replace(body);
+        self.clear_body_caches();
     }
 
     pub fn user_properties(&self) -> Vec<(String, String)> {
@@ -95,4 +269,171 @@ impl MQTTyClientMessage {
         v.clear();
         v.extend_from_slice(user_properties);
     }
+
+    pub fn correlation_data(&self) -> Option<Vec<u8>> {
+        self.imp().correlation_data.borrow().clone()
+    }
+
+    pub fn set_correlation_data(&self, correlation_data: Option<&[u8]>) {
+        self.imp()
+            .correlation_data
+            .replace(correlation_data.map(<[u8]>::to_vec));
+    }
+
+    pub fn subscription_identifiers(&self) -> Vec<u32> {
+        self.imp().subscription_identifiers.borrow().clone()
+    }
+
+    pub fn set_subscription_identifiers(&self, subscription_identifiers: &[u32]) {
+        let mut v = self.imp().subscription_identifiers.borrow_mut();
+        v.clear();
+        v.extend_from_slice(subscription_identifiers);
+    }
+}
+
+/// Binary data as it's represented in JSON, text when it's valid UTF-8
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Bytes {
+    Text(String),
+    Base64(String),
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(value: &[u8]) -> Self {
+        match std::str::from_utf8(value) {
+            Ok(text) => Self::Text(text.to_string()),
+            Err(_) => Self::Base64(BASE64_STANDARD.encode(value)),
+        }
+    }
+}
+
+impl Bytes {
+    fn into_vec<E: de::Error>(self) -> Result<Vec<u8>, E> {
+        match self {
+            Self::Text(text) => Ok(text.into_bytes()),
+            Self::Base64(data) => BASE64_STANDARD.decode(data).map_err(E::custom),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct UserProperty {
+    key: String,
+    value: String,
+}
+
+/// JSON representation of [`MQTTyClientMessage`]
+#[derive(Serialize, Deserialize)]
+struct MessageRepr {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    direction: MQTTyMessageDirection,
+    #[serde(default)]
+    timestamp: Option<String>,
+    topic: String,
+    #[serde(default)]
+    qos: MQTTyClientQos,
+    #[serde(default)]
+    retained: bool,
+    #[serde(default)]
+    mqtt_version: MQTTyClientVersion,
+    #[serde(default)]
+    content_type: Option<String>,
+    #[serde(default)]
+    user_properties: Vec<UserProperty>,
+    #[serde(default)]
+    payload_utf8: bool,
+    #[serde(default)]
+    message_expiry_interval: u32,
+    #[serde(default)]
+    response_topic: Option<String>,
+    #[serde(default)]
+    topic_alias: u16,
+    #[serde(default)]
+    correlation_data: Option<Bytes>,
+    #[serde(default)]
+    subscription_identifiers: Vec<u32>,
+    body: Bytes,
+}
+
+impl Serialize for MQTTyClientMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let timestamp = self
+            .timestamp()
+            .map(|timestamp| timestamp.format_iso8601())
+            .transpose()
+            .map_err(serde::ser::Error::custom)?;
+
+        MessageRepr {
+            id: Some(self.id()),
+            direction: self.direction(),
+            timestamp: timestamp.map(Into::into),
+            topic: self.topic(),
+            qos: self.qos(),
+            retained: self.retained(),
+            mqtt_version: self.mqtt_version(),
+            content_type: self.content_type(),
+            user_properties: self
+                .user_properties()
+                .into_iter()
+                .map(|(key, value)| UserProperty { key, value })
+                .collect(),
+            payload_utf8: self.payload_utf8(),
+            message_expiry_interval: self.message_expiry_interval(),
+            response_topic: self.response_topic(),
+            topic_alias: self.topic_alias() as u16,
+            correlation_data: self.correlation_data().as_deref().map(Bytes::from),
+            subscription_identifiers: self.subscription_identifiers(),
+            body: Bytes::from(self.body().as_slice()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MQTTyClientMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = MessageRepr::deserialize(deserializer)?;
+
+        let timestamp = repr
+            .timestamp
+            .map(|timestamp| glib::DateTime::from_iso8601(&timestamp, None))
+            .transpose()
+            .map_err(de::Error::custom)?;
+
+        let message = Self::new();
+
+        if let Some(id) = repr.id {
+            message.set_id(id);
+        }
+        message.set_direction(repr.direction);
+        message.set_timestamp(timestamp);
+        message.set_topic(repr.topic);
+        message.set_qos(repr.qos);
+        message.set_retained(repr.retained);
+        message.set_mqtt_version(repr.mqtt_version);
+        message.set_content_type(repr.content_type);
+        message.set_user_properties(
+            &repr
+                .user_properties
+                .into_iter()
+                .map(|property| (property.key, property.value))
+                .collect::<Vec<_>>(),
+        );
+        message.set_payload_utf8(repr.payload_utf8);
+        message.set_message_expiry_interval(repr.message_expiry_interval);
+        message.set_response_topic(repr.response_topic);
+        message.set_topic_alias(repr.topic_alias as u32);
+        message.set_correlation_data(
+            repr.correlation_data
+                .map(Bytes::into_vec)
+                .transpose()?
+                .as_deref(),
+        );
+        message.set_subscription_identifiers(&repr.subscription_identifiers);
+        message.set_body(&repr.body.into_vec()?);
+
+        Ok(message)
+    }
 }
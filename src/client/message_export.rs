@@ -0,0 +1,208 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+use std::io;
+
+use adw::prelude::*;
+use futures::{Stream, StreamExt};
+use gettextrs::gettext;
+use gtk::{gio, glib};
+
+use super::MQTTyClientMessage;
+
+/// Number of messages written at once, the main loop runs between chunks so that
+/// exporting a lot of messages doesn't freeze the UI
+const CHUNK_SIZE: usize = 1000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MQTTyExportFormat {
+    /// One JSON message per line, see [`MQTTyClientMessage`] for the representation
+    JsonLines,
+    /// Columns timestamp, topic, qos, retained and payload, the payload is written as
+    /// text when it's valid UTF-8, and as hexadecimal otherwise
+    Csv,
+    /// `topic payload` lines, as printed by `mosquitto_sub -v`
+    MosquittoSub,
+}
+
+impl MQTTyExportFormat {
+    pub fn from_nick(nick: &str) -> Option<Self> {
+        match nick {
+            "jsonl" => Some(Self::JsonLines),
+            "csv" => Some(Self::Csv),
+            "text" => Some(Self::MosquittoSub),
+            _ => None,
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::JsonLines => "messages.jsonl",
+            Self::Csv => "messages.csv",
+            Self::MosquittoSub => "messages.txt",
+        }
+    }
+
+    pub fn file_filter(&self) -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+
+        match self {
+            Self::JsonLines => {
+                filter.set_name(Some(&gettext("JSON Lines")));
+                filter.add_suffix("jsonl");
+            }
+            Self::Csv => {
+                filter.set_name(Some(&gettext("CSV")));
+                filter.add_mime_type("text/csv");
+            }
+            Self::MosquittoSub => {
+                filter.set_name(Some(&gettext("Plain Text")));
+                filter.add_mime_type("text/plain");
+            }
+        }
+
+        filter
+    }
+
+    fn write_header(&self, buf: &mut Vec<u8>) {
+        if *self == Self::Csv {
+            buf.extend_from_slice(b"timestamp,topic,qos,retained,payload\r\n");
+        }
+    }
+
+    fn write_message(
+        &self,
+        msg: &MQTTyClientMessage,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), glib::Error> {
+        match self {
+            Self::JsonLines => {
+                serde_json::to_writer(&mut *buf, msg)
+                    .map_err(|e| glib::Error::new(gio::IOErrorEnum::InvalidData, &e.to_string()))?;
+                buf.push(b'\n');
+            }
+            Self::Csv => {
+                let timestamp = msg
+                    .timestamp()
+                    .and_then(|timestamp| timestamp.format_iso8601().ok())
+                    .unwrap_or_default();
+
+                let body = msg.body();
+                let payload = match std::str::from_utf8(&body) {
+                    Ok(text) => text.to_string(),
+                    Err(_) => body.iter().fold(String::new(), |mut hex, byte| {
+                        let _ = write!(hex, "{byte:02x}");
+                        hex
+                    }),
+                };
+
+                let record = [
+                    csv_field(&timestamp),
+                    csv_field(&msg.topic()),
+                    (msg.qos() as u8).to_string(),
+                    msg.retained().to_string(),
+                    csv_field(&payload),
+                ]
+                .join(",");
+
+                buf.extend_from_slice(record.as_bytes());
+                buf.extend_from_slice(b"\r\n");
+            }
+            Self::MosquittoSub => {
+                buf.extend_from_slice(msg.topic().as_bytes());
+                buf.push(b' ');
+                buf.extend_from_slice(&msg.body());
+                buf.push(b'\n');
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Quotes a CSV field if it contains separators, quotes or line breaks
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes the messages of every page to `file` in the given format, replacing its
+/// contents, see [`super::MQTTyMessageStore::query_pages()`]. Returns the number of
+/// messages written
+pub async fn export_messages(
+    mut pages: impl Stream<Item = io::Result<Vec<MQTTyClientMessage>>> + Unpin,
+    format: MQTTyExportFormat,
+    file: &gio::File,
+) -> Result<usize, glib::Error> {
+    let stream = file
+        .replace_future(
+            None,
+            false,
+            gio::FileCreateFlags::REPLACE_DESTINATION,
+            glib::Priority::DEFAULT,
+        )
+        .await?;
+
+    let mut buf = Vec::new();
+
+    format.write_header(&mut buf);
+
+    let mut written = 0;
+
+    while let Some(page) = pages.next().await {
+        let page = page.map_err(|e| glib::Error::new(gio::IOErrorEnum::Failed, &e.to_string()))?;
+
+        for chunk in page.chunks(CHUNK_SIZE) {
+            for msg in chunk {
+                format.write_message(msg, &mut buf)?;
+            }
+
+            written += chunk.len();
+
+            buf = write_buffer(&stream, buf).await?;
+        }
+    }
+
+    if !buf.is_empty() {
+        write_buffer(&stream, buf).await?;
+    }
+
+    stream.close_future(glib::Priority::DEFAULT).await?;
+
+    Ok(written)
+}
+
+/// Returns the buffer cleared, so that it can be reused
+async fn write_buffer(
+    stream: &gio::FileOutputStream,
+    buf: Vec<u8>,
+) -> Result<Vec<u8>, glib::Error> {
+    let (mut buf, _, error) = stream
+        .write_all_future(buf, glib::Priority::DEFAULT_IDLE)
+        .await
+        .map_err(|(_, e)| e)?;
+
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    buf.clear();
+
+    Ok(buf)
+}
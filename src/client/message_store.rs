@@ -0,0 +1,835 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::thread;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use futures::channel::oneshot;
+use futures::stream::{self, LocalBoxStream, StreamExt};
+use gtk::{gio, glib};
+
+use super::{MQTTyClientMessage, MQTTyClientQos, MQTTyClientVersion, MQTTyMessageDirection};
+
+/// Number of messages kept in memory by default
+const DEFAULT_CAPACITY: u32 = 1000;
+
+/// Maximum number of messages of each page of [`MQTTyMessageStore::query_pages()`]
+const PAGE_SIZE: usize = 1000;
+
+/// Written at the start of every log file, the last byte is the version of the format
+const LOG_MAGIC: &[u8; 8] = b"MQTTyLg\x01";
+
+/// Serialized form of a message inside of a log file, see [`encode_record()`]
+type Record = (
+    String,                // id
+    u8,                    // direction
+    i64,                   // timestamp, microseconds since the Unix epoch
+    String,                // topic
+    u8,                    // qos
+    bool,                  // retained
+    u8,                    // mqtt version
+    String,                // content type, empty if there is none
+    bool,                  // payload format indicator
+    u32,                   // message expiry interval
+    u32,                   // topic alias, 0 if there is none
+    String,                // response topic, empty if there is none
+    Vec<u8>,               // correlation data, empty if there is none
+    Vec<(String, String)>, // user properties
+    Vec<u32>,              // subscription identifiers
+    Vec<u8>,               // body
+);
+
+/// Filters of [`MQTTyMessageStore::query()`], fields set to None match every message
+#[derive(Default, Clone, Debug)]
+pub struct MQTTyMessageQuery {
+    /// Case insensitive text contained in the topic
+    pub topic: Option<String>,
+
+    pub direction: Option<MQTTyMessageDirection>,
+
+    /// Microseconds since the Unix epoch, inclusive
+    pub since: Option<i64>,
+
+    /// Microseconds since the Unix epoch, inclusive
+    pub until: Option<i64>,
+
+    /// Maximum number of messages returned, the newest ones are preferred, 0 means no
+    /// limit
+    pub limit: usize,
+}
+
+impl MQTTyMessageQuery {
+    fn matches(&self, topic: &str, direction: MQTTyMessageDirection, timestamp: i64) -> bool {
+        self.direction.is_none_or(|d| d == direction)
+            && self.since.is_none_or(|since| timestamp >= since)
+            && self.until.is_none_or(|until| timestamp <= until)
+            && self
+                .topic
+                .as_ref()
+                .is_none_or(|t| topic.to_lowercase().contains(&t.to_lowercase()))
+    }
+}
+
+/// Location of a message inside of a log file
+struct IndexEntry {
+    offset: u64,
+    timestamp: i64,
+    direction: MQTTyMessageDirection,
+    topic: Box<str>,
+}
+
+/// Requests handled by the log thread of a store, in the same order they're sent
+enum LogCommand {
+    /// Builds the index of the log file, replies with its latest `load` records
+    Open {
+        load: usize,
+        reply: oneshot::Sender<io::Result<Vec<Record>>>,
+    },
+    Append(Record),
+    Query {
+        query: MQTTyMessageQuery,
+        reply: oneshot::Sender<io::Result<Vec<Record>>>,
+    },
+    /// Sends the matching records oldest first, in pages, the channel is bounded so
+    /// that the log thread doesn't read faster than the pages are consumed
+    QueryPages {
+        query: MQTTyMessageQuery,
+        pages: async_channel::Sender<io::Result<Vec<Record>>>,
+    },
+}
+
+/// Log file of a store, owned by its log thread so that the disk is never touched from
+/// the main thread, see [`MessageLog::run()`]
+struct MessageLog {
+    path: PathBuf,
+
+    /// None until the file is opened, it's only created when the first message is
+    /// appended, so that connections that never had messages don't leave empty files
+    /// behind
+    file: Option<File>,
+
+    /// Every message in the log file, in the same order, which is also the order of
+    /// their timestamps unless the system clock went backwards
+    index: Vec<IndexEntry>,
+}
+
+impl MessageLog {
+    /// Spawns the log thread of `path`, it exits once the returned sender is dropped and
+    /// the pending commands are handled
+    fn spawn(path: PathBuf) -> io::Result<mpsc::Sender<LogCommand>> {
+        let (sender, receiver) = mpsc::channel();
+
+        let log = Self {
+            path,
+            file: None,
+            index: vec![],
+        };
+
+        thread::Builder::new()
+            .name("message-log".into())
+            .spawn(move || log.run(receiver))?;
+
+        Ok(sender)
+    }
+
+    fn run(mut self, receiver: mpsc::Receiver<LogCommand>) {
+        for command in receiver {
+            match command {
+                LogCommand::Open { load, reply } => {
+                    let result = self.open(false).and_then(|_| {
+                        let offsets = self.index[self.index.len().saturating_sub(load)..]
+                            .iter()
+                            .map(|e| e.offset)
+                            .collect::<Vec<_>>();
+
+                        self.read_records(&offsets)
+                    });
+
+                    let _ = reply.send(result);
+                }
+                LogCommand::Append(record) => {
+                    if let Err(e) = self.append(record) {
+                        tracing::warn!("Could not save message to the message store: {e}");
+                    }
+                }
+                LogCommand::Query { query, reply } => {
+                    let mut offsets = self.matching_offsets(&query);
+                    offsets.reverse();
+
+                    let _ = reply.send(self.read_records(&offsets));
+                }
+                LogCommand::QueryPages { query, pages } => {
+                    for offsets in self.matching_offsets(&query).chunks(PAGE_SIZE) {
+                        let page = self.read_records(offsets);
+                        let failed = page.is_err();
+
+                        // The receiver is dropped when the pages are not needed anymore
+                        if pages.send_blocking(page).is_err() || failed {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Builds the index of the log file, the file is only created if `create` is true
+    fn open(&mut self, create: bool) -> io::Result<()> {
+        let path = &self.path;
+
+        if !create && !path.exists() {
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut magic = [0; LOG_MAGIC.len()];
+
+        match file.read_exact(&mut magic) {
+            Ok(_) if &magic == LOG_MAGIC => {}
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} is not a message log", path.display()),
+                ))
+            }
+            // Empty or new file
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                file.set_len(0)?;
+                file.write_all(LOG_MAGIC)?;
+            }
+            Err(e) => return Err(e),
+        }
+
+        let mut index = vec![];
+        let mut skipped = 0;
+
+        let mut reader = BufReader::new(&file);
+        let mut offset = reader.seek(SeekFrom::Start(LOG_MAGIC.len() as u64))?;
+
+        while let Some((record, len)) = read_record(&mut reader)? {
+            match record {
+                Some(record) => index.push(IndexEntry {
+                    offset,
+                    timestamp: record.2,
+                    direction: direction_from_u8(record.1),
+                    topic: record.3.into(),
+                }),
+                None => skipped += 1,
+            }
+            offset += len;
+        }
+
+        if skipped != 0 {
+            tracing::warn!(
+                "Skipped {skipped} corrupt records of the message log {}",
+                path.display()
+            );
+        }
+
+        // A record may have been written partially, e.g. the application crashed, we
+        // discard it so that the next records don't get misaligned
+        if file.metadata()?.len() > offset {
+            tracing::warn!(
+                "Discarded an incomplete record at the end of the message log {}",
+                path.display()
+            );
+            file.set_len(offset)?;
+        }
+
+        self.index = index;
+        self.file = Some(file);
+
+        Ok(())
+    }
+
+    fn append(&mut self, record: Record) -> io::Result<()> {
+        if self.file.is_none() {
+            self.open(true)?;
+        }
+
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+
+        let offset = file.seek(SeekFrom::End(0))?;
+
+        let entry = IndexEntry {
+            offset,
+            timestamp: record.2,
+            direction: direction_from_u8(record.1),
+            topic: record.3.as_str().into(),
+        };
+
+        if let Err(e) = write_record(file, record) {
+            // Keeps the next records aligned
+            let _ = file.set_len(offset);
+            return Err(e);
+        }
+
+        self.index.push(entry);
+
+        Ok(())
+    }
+
+    /// Offsets of the records that match `query`, oldest first
+    fn matching_offsets(&self, query: &MQTTyMessageQuery) -> Vec<u64> {
+        let index = &self.index;
+
+        // The index is sorted by timestamp, so the time range is a slice of it
+        let start = query
+            .since
+            .map_or(0, |since| index.partition_point(|e| e.timestamp < since));
+        let end = query.until.map_or(index.len(), |until| {
+            index.partition_point(|e| e.timestamp <= until)
+        });
+
+        let mut offsets = index[start..end.max(start)]
+            .iter()
+            .rev()
+            .filter(|e| query.matches(&e.topic, e.direction, e.timestamp))
+            .take(if query.limit == 0 {
+                usize::MAX
+            } else {
+                query.limit
+            })
+            .map(|e| e.offset)
+            .collect::<Vec<_>>();
+
+        offsets.reverse();
+
+        offsets
+    }
+
+    /// Reads the records at the given offsets of the log file, in the same order,
+    /// corrupt records are skipped
+    fn read_records(&self, offsets: &[u64]) -> io::Result<Vec<Record>> {
+        let Some(file) = self.file.as_ref() else {
+            return Ok(vec![]);
+        };
+
+        let mut reader = BufReader::new(file);
+
+        let mut records = Vec::with_capacity(offsets.len());
+
+        for offset in offsets {
+            reader.seek(SeekFrom::Start(*offset))?;
+
+            if let Some((Some(record), _)) = read_record(&mut reader)? {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+}
+
+mod imp {
+
+    use super::*;
+
+    #[derive(glib::Properties)]
+    #[properties(wrapper_type = super::MQTTyMessageStore)]
+    pub struct MQTTyMessageStore {
+        /// Connection the messages belong to, usually the URL of the broker
+        #[property(get, construct_only)]
+        connection: RefCell<String>,
+
+        /// Number of messages kept in memory, the oldest ones are dropped first
+        #[property(get, set = Self::set_capacity, construct, minimum = 1, default = DEFAULT_CAPACITY)]
+        capacity: Cell<u32>,
+
+        /// Whether the messages are also appended to a log file under the user data
+        /// directory, enabling it loads the messages of the previous sessions
+        #[property(get, set = Self::set_persistent)]
+        persistent: Cell<bool>,
+
+        /// Ring buffer, oldest message first
+        ring: RefCell<VecDeque<MQTTyClientMessage>>,
+
+        /// Channel of the log thread, only while the store is persistent
+        log: RefCell<Option<Rc<mpsc::Sender<LogCommand>>>>,
+    }
+
+    impl Default for MQTTyMessageStore {
+        fn default() -> Self {
+            Self {
+                connection: Default::default(),
+                capacity: Cell::new(DEFAULT_CAPACITY),
+                persistent: Default::default(),
+                ring: Default::default(),
+                log: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyMessageStore {
+        const NAME: &'static str = "MQTTyMessageStore";
+
+        type Type = super::MQTTyMessageStore;
+
+        type ParentType = glib::Object;
+
+        type Interfaces = (gio::ListModel,);
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyMessageStore {}
+
+    /// Items are exposed newest first
+    impl ListModelImpl for MQTTyMessageStore {
+        fn item_type(&self) -> glib::Type {
+            MQTTyClientMessage::static_type()
+        }
+
+        fn n_items(&self) -> u32 {
+            self.ring.borrow().len() as u32
+        }
+
+        fn item(&self, position: u32) -> Option<glib::Object> {
+            let ring = self.ring.borrow();
+            let idx = ring.len().checked_sub(position as usize + 1)?;
+            ring.get(idx).map(|msg| msg.clone().upcast())
+        }
+    }
+
+    impl MQTTyMessageStore {
+        pub fn push(&self, msg: &MQTTyClientMessage) {
+            self.ring.borrow_mut().push_back(msg.clone());
+            self.obj().items_changed(0, 0, 1);
+
+            self.trim();
+
+            if let Some(log) = self.log.borrow().as_ref() {
+                if log.send(LogCommand::Append(encode_record(msg))).is_err() {
+                    tracing::warn!("Could not save message to the message store: log closed");
+                }
+            }
+        }
+
+        pub fn clear(&self) {
+            let removed = self.ring.take().len() as u32;
+            if removed != 0 {
+                self.obj().items_changed(0, removed, 0);
+            }
+        }
+
+        /// Messages in memory that match `query`, newest first
+        fn query_ring(&self, query: &MQTTyMessageQuery) -> Vec<MQTTyClientMessage> {
+            self.ring
+                .borrow()
+                .iter()
+                .rev()
+                .filter(|msg| {
+                    query.matches(
+                        &msg.topic(),
+                        msg.direction(),
+                        msg.timestamp().as_ref().map_or(0, timestamp_usec),
+                    )
+                })
+                .take(if query.limit == 0 {
+                    usize::MAX
+                } else {
+                    query.limit
+                })
+                .cloned()
+                .collect()
+        }
+
+        pub async fn query(&self, query: &MQTTyMessageQuery) -> Vec<MQTTyClientMessage> {
+            let log = self.log.borrow().clone();
+
+            let Some(log) = log else {
+                return self.query_ring(query);
+            };
+
+            let (reply, records) = oneshot::channel();
+
+            let command = LogCommand::Query {
+                query: query.clone(),
+                reply,
+            };
+
+            let records = match log.send(command) {
+                Ok(_) => records.await.unwrap_or_else(|_| Ok(vec![])),
+                Err(_) => Ok(vec![]),
+            };
+
+            match records {
+                Ok(records) => records.into_iter().map(decode_record).collect(),
+                Err(e) => {
+                    tracing::warn!("Could not read the message store: {e}");
+                    vec![]
+                }
+            }
+        }
+
+        pub fn query_pages(
+            &self,
+            query: &MQTTyMessageQuery,
+        ) -> LocalBoxStream<'static, io::Result<Vec<MQTTyClientMessage>>> {
+            let log = self.log.borrow().clone();
+
+            let Some(log) = log else {
+                let mut messages = self.query_ring(query);
+                messages.reverse();
+
+                let pages = messages
+                    .chunks(PAGE_SIZE)
+                    .map(|page| Ok(page.to_vec()))
+                    .collect::<Vec<_>>();
+
+                return stream::iter(pages).boxed_local();
+            };
+
+            let (sender, pages) = async_channel::bounded(1);
+
+            let command = LogCommand::QueryPages {
+                query: query.clone(),
+                pages: sender,
+            };
+
+            if log.send(command).is_err() {
+                return stream::empty().boxed_local();
+            }
+
+            pages
+                .map(|page| page.map(|records| records.into_iter().map(decode_record).collect()))
+                .boxed_local()
+        }
+
+        fn set_capacity(&self, capacity: u32) {
+            self.capacity.set(capacity);
+            self.trim();
+        }
+
+        fn set_persistent(&self, persistent: bool) {
+            if self.persistent.get() == persistent {
+                return;
+            }
+
+            if persistent {
+                if let Err(e) = self.open_log() {
+                    tracing::warn!("Could not open the message store: {e}");
+                    return;
+                }
+            } else {
+                // The log thread exits once its pending messages are written
+                self.log.replace(None);
+            }
+
+            self.persistent.set(persistent);
+            self.obj().notify_persistent();
+        }
+
+        fn trim(&self) {
+            let mut ring = self.ring.borrow_mut();
+            let capacity = self.capacity.get() as usize;
+
+            if ring.len() <= capacity {
+                return;
+            }
+
+            let removed = ring.len() - capacity;
+            ring.drain(..removed);
+
+            drop(ring);
+
+            self.obj().items_changed(capacity as u32, removed as u32, 0);
+        }
+
+        fn log_path(&self) -> PathBuf {
+            let name = glib::compute_checksum_for_string(
+                glib::ChecksumType::Sha256,
+                self.connection.borrow().as_str(),
+            )
+            .unwrap_or_default();
+
+            glib::user_data_dir()
+                .join("MQTTy")
+                .join("messages")
+                .join(format!("{name}.log"))
+        }
+
+        /// Starts the log thread, which builds the index of the log file and loads its
+        /// latest messages in the background, they're placed before the messages pushed
+        /// meanwhile
+        fn open_log(&self) -> io::Result<()> {
+            let log = Rc::new(MessageLog::spawn(self.log_path())?);
+
+            let (reply, records) = oneshot::channel();
+
+            let _ = log.send(LogCommand::Open {
+                load: self.capacity.get() as usize,
+                reply,
+            });
+
+            self.log.replace(Some(log.clone()));
+
+            let obj = self.obj();
+
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    let Ok(records) = records.await else {
+                        return;
+                    };
+
+                    let this = obj.imp();
+
+                    // The store may have been disabled, or enabled again, meanwhile
+                    if !this
+                        .log
+                        .borrow()
+                        .as_ref()
+                        .is_some_and(|l| Rc::ptr_eq(l, &log))
+                    {
+                        return;
+                    }
+
+                    let records = match records {
+                        Ok(records) => records,
+                        Err(e) => {
+                            tracing::warn!("Could not open the message store: {e}");
+                            obj.set_persistent(false);
+                            return;
+                        }
+                    };
+
+                    if records.is_empty() {
+                        return;
+                    }
+
+                    let added = records.len() as u32;
+
+                    {
+                        let mut ring = this.ring.borrow_mut();
+                        for record in records.into_iter().rev() {
+                            ring.push_front(decode_record(record));
+                        }
+                    }
+
+                    // Newest first, so the older messages go at the end
+                    let position = this.ring.borrow().len() as u32 - added;
+                    obj.items_changed(position, 0, added);
+
+                    this.trim();
+                }
+            ));
+
+            Ok(())
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Keeps the latest messages of a connection in a bounded ring buffer, and optionally
+    /// in an append-only log file, so that they survive restarts and can be searched
+    ///
+    /// It's a list model of [`MQTTyClientMessage`] with the newest message first
+    pub struct MQTTyMessageStore(ObjectSubclass<imp::MQTTyMessageStore>)
+        @implements gio::ListModel;
+}
+
+impl MQTTyMessageStore {
+    pub fn new(connection: &str) -> Self {
+        glib::Object::builder()
+            .property("connection", connection)
+            .build()
+    }
+
+    pub fn push(&self, msg: &MQTTyClientMessage) {
+        self.imp().push(msg);
+    }
+
+    /// Removes the messages kept in memory, the log file is left untouched
+    pub fn clear(&self) {
+        self.imp().clear();
+    }
+
+    /// Looks for messages in the log file if the store is persistent, or in memory
+    /// otherwise, newest first. The log file is read in the background
+    pub async fn query(&self, query: &MQTTyMessageQuery) -> Vec<MQTTyClientMessage> {
+        self.imp().query(query).await
+    }
+
+    /// Same as [`Self::query()`], but the messages are returned oldest first in pages,
+    /// so that every message can be processed without keeping all of them in memory
+    pub fn query_pages(
+        &self,
+        query: &MQTTyMessageQuery,
+    ) -> LocalBoxStream<'static, io::Result<Vec<MQTTyClientMessage>>> {
+        self.imp().query_pages(query)
+    }
+}
+
+/// Reads a length-prefixed record, returns None at the end of the file or when the
+/// record is incomplete, and a None record if it's corrupt so that it can be skipped.
+/// The length includes the prefix
+fn read_record(reader: &mut impl Read) -> io::Result<Option<(Option<Record>, u64)>> {
+    let mut len = [0; 4];
+
+    match reader.read_exact(&mut len) {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let len = u32::from_le_bytes(len);
+
+    // A corrupt length must not allocate more than what is left in the file
+    let mut data = vec![];
+    reader.by_ref().take(len as u64).read_to_end(&mut data)?;
+
+    if data.len() < len as usize {
+        return Ok(None);
+    }
+
+    let record = glib::Variant::from_data::<Record, _>(data)
+        .normal_form()
+        .get::<Record>();
+
+    Ok(Some((record, 4 + len as u64)))
+}
+
+fn write_record(writer: &mut impl Write, record: Record) -> io::Result<()> {
+    let variant = record.to_variant();
+    let data = variant.data();
+
+    let mut buf = Vec::with_capacity(4 + data.len());
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+
+    writer.write_all(&buf)
+}
+
+fn encode_record(msg: &MQTTyClientMessage) -> Record {
+    (
+        msg.id(),
+        match msg.direction() {
+            MQTTyMessageDirection::Received => 0,
+            MQTTyMessageDirection::Sent => 1,
+        },
+        msg.timestamp().as_ref().map_or(0, timestamp_usec),
+        msg.topic(),
+        msg.qos() as u8,
+        msg.retained(),
+        match msg.mqtt_version() {
+            MQTTyClientVersion::V3X => 3,
+            MQTTyClientVersion::V5 => 5,
+        },
+        msg.content_type().unwrap_or_default(),
+        msg.payload_utf8(),
+        msg.message_expiry_interval(),
+        msg.topic_alias(),
+        msg.response_topic().unwrap_or_default(),
+        msg.correlation_data().unwrap_or_default(),
+        msg.user_properties(),
+        msg.subscription_identifiers(),
+        msg.body(),
+    )
+}
+
+fn decode_record(record: Record) -> MQTTyClientMessage {
+    let (
+        id,
+        direction,
+        timestamp,
+        topic,
+        qos,
+        retained,
+        mqtt_version,
+        content_type,
+        payload_utf8,
+        message_expiry_interval,
+        topic_alias,
+        response_topic,
+        correlation_data,
+        user_properties,
+        subscription_identifiers,
+        body,
+    ) = record;
+
+    let msg = MQTTyClientMessage::new();
+
+    msg.set_id(id);
+    msg.set_direction(direction_from_u8(direction));
+    msg.set_timestamp(datetime_from_usec(timestamp));
+    msg.set_topic(topic);
+    msg.set_qos(
+        MQTTyClientQos::listed()
+            .get(qos as usize)
+            .copied()
+            .unwrap_or_default(),
+    );
+    msg.set_retained(retained);
+    msg.set_mqtt_version(if mqtt_version == 5 {
+        MQTTyClientVersion::V5
+    } else {
+        MQTTyClientVersion::V3X
+    });
+    msg.set_content_type((!content_type.is_empty()).then_some(content_type));
+    msg.set_payload_utf8(payload_utf8);
+    msg.set_message_expiry_interval(message_expiry_interval);
+    msg.set_topic_alias(topic_alias);
+    msg.set_response_topic((!response_topic.is_empty()).then_some(response_topic));
+    msg.set_correlation_data((!correlation_data.is_empty()).then_some(&correlation_data[..]));
+    msg.set_user_properties(&user_properties);
+    msg.set_subscription_identifiers(&subscription_identifiers);
+    msg.set_body(&body);
+
+    msg
+}
+
+fn direction_from_u8(direction: u8) -> MQTTyMessageDirection {
+    if direction == 1 {
+        MQTTyMessageDirection::Sent
+    } else {
+        MQTTyMessageDirection::Received
+    }
+}
+
+/// Microseconds since the Unix epoch
+fn timestamp_usec(datetime: &glib::DateTime) -> i64 {
+    datetime.to_unix() * 1_000_000 + datetime.microsecond() as i64
+}
+
+fn datetime_from_usec(usec: i64) -> Option<glib::DateTime> {
+    glib::DateTime::from_unix_local(usec.div_euclid(1_000_000))
+        .and_then(|dt| {
+            dt.add(glib::TimeSpan::from_microseconds(
+                usec.rem_euclid(1_000_000),
+            ))
+        })
+        .ok()
+}
@@ -0,0 +1,65 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use gettextrs::gettext;
+
+/// Reason code sent by the broker in acknowledgement packets (SUBACK, UNSUBACK, ...).
+///
+/// On MQTT v3.x, SUBACK return codes are mapped to the equivalent v5 reason codes, and
+/// UNSUBACK is always a success.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MQTTyReasonCode(u8);
+
+impl MQTTyReasonCode {
+    pub const SUCCESS: Self = Self(0x00);
+
+    pub fn new(value: u8) -> Self {
+        Self(value)
+    }
+
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+
+    /// Codes lower than 0x80 are successful ones
+    pub fn is_success(&self) -> bool {
+        self.0 < 0x80
+    }
+}
+
+impl fmt::Display for MQTTyReasonCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self.0 {
+            0x00 => gettext("Success"),
+            0x01 => gettext("Granted QoS 1"),
+            0x02 => gettext("Granted QoS 2"),
+            0x11 => gettext("No subscription existed"),
+            0x80 => gettext("Unspecified error"),
+            0x83 => gettext("Implementation specific error"),
+            0x87 => gettext("Not authorized"),
+            0x8F => gettext("Topic filter invalid"),
+            0x91 => gettext("Packet identifier in use"),
+            0x97 => gettext("Quota exceeded"),
+            0x9E => gettext("Shared subscriptions not supported"),
+            0xA1 => gettext("Subscription identifiers not supported"),
+            0xA2 => gettext("Wildcard subscriptions not supported"),
+            code => return write!(f, "0x{code:02X}"),
+        };
+
+        write!(f, "{description} (0x{:02X})", self.0)
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::{MQTTyClient, MQTTyClientError, MQTTyClientMessage};
+
+/// Number of lines parsed at once when loading a session, the main loop runs between
+/// chunks so that loading big sessions doesn't freeze the UI
+const CHUNK_SIZE: usize = 1000;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(glib::Properties)]
+    #[properties(wrapper_type = super::MQTTySessionPlayer)]
+    pub struct MQTTySessionPlayer {
+        /// Multiplier of the original timing, 0 means as fast as possible
+        #[property(get, set, minimum = 0.0)]
+        speed: Cell<f64>,
+
+        /// Whether the session starts again once the last message is published
+        #[property(get, set)]
+        looping: Cell<bool>,
+
+        /// Topic prefix that gets replaced by [`Self::new_topic_prefix`], topics are
+        /// not rewritten if it's empty
+        #[property(get, set)]
+        topic_prefix: RefCell<String>,
+
+        #[property(get, set)]
+        new_topic_prefix: RefCell<String>,
+
+        #[property(get)]
+        playing: Cell<bool>,
+
+        /// Number of messages published in the current round
+        #[property(get)]
+        position: Cell<u32>,
+
+        #[property(get)]
+        n_messages: Cell<u32>,
+
+        pub messages: RefCell<Vec<MQTTyClientMessage>>,
+
+        /// Incremented every time the playback starts or stops, so that a previous
+        /// playback notices that it must stop
+        generation: Cell<u64>,
+    }
+
+    impl Default for MQTTySessionPlayer {
+        fn default() -> Self {
+            Self {
+                speed: Cell::new(1.0),
+                looping: Default::default(),
+                topic_prefix: Default::default(),
+                new_topic_prefix: Default::default(),
+                playing: Default::default(),
+                position: Default::default(),
+                n_messages: Default::default(),
+                messages: Default::default(),
+                generation: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTySessionPlayer {
+        const NAME: &'static str = "MQTTySessionPlayer";
+
+        type Type = super::MQTTySessionPlayer;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTySessionPlayer {}
+
+    impl MQTTySessionPlayer {
+        pub async fn load(&self, file: &gio::File) -> Result<(), glib::Error> {
+            self.stop();
+
+            let (contents, _) = file.load_contents_future().await?;
+
+            let mut messages = vec![];
+
+            let lines = contents
+                .split(|b| *b == b'\n')
+                .filter(|line| !line.trim_ascii().is_empty())
+                .collect::<Vec<_>>();
+
+            for chunk in lines.chunks(CHUNK_SIZE) {
+                for (i, line) in chunk.iter().enumerate() {
+                    let msg = serde_json::from_slice::<MQTTyClientMessage>(line).map_err(|e| {
+                        glib::Error::new(
+                            gio::IOErrorEnum::InvalidData,
+                            &format!("Message {}: {}", messages.len() + i + 1, e),
+                        )
+                    })?;
+                    messages.push(msg);
+                }
+
+                glib::timeout_future(Duration::ZERO).await;
+            }
+
+            let obj = self.obj();
+
+            self.n_messages.set(messages.len() as u32);
+            self.messages.replace(messages);
+            self.position.set(0);
+
+            obj.notify_n_messages();
+            obj.notify_position();
+
+            Ok(())
+        }
+
+        pub async fn play(&self, client: &MQTTyClient) -> Result<(), MQTTyClientError> {
+            self.stop();
+
+            let generation = self.generation.get();
+            let is_current = || self.generation.get() == generation;
+
+            self.set_playing(true);
+
+            let messages = self.messages.borrow().clone();
+
+            let result = 'playback: loop {
+                self.set_position(0);
+
+                let mut previous: Option<glib::DateTime> = None;
+
+                for msg in &messages {
+                    if let (Some(previous), Some(timestamp)) = (&previous, msg.timestamp()) {
+                        let delay = self.delay(previous, &timestamp);
+                        if !delay.is_zero() {
+                            glib::timeout_future(delay).await;
+                        }
+                    }
+
+                    if !is_current() {
+                        return Ok(());
+                    }
+
+                    previous = msg.timestamp();
+
+                    if let Err(e) = client.publish(&self.replayed_message(msg)).await {
+                        break 'playback Err(e);
+                    }
+
+                    if !is_current() {
+                        return Ok(());
+                    }
+
+                    self.set_position(self.position.get() + 1);
+                }
+
+                if !self.looping.get() || messages.is_empty() {
+                    break 'playback Ok(());
+                }
+            };
+
+            if is_current() {
+                self.generation.set(generation + 1);
+                self.set_playing(false);
+            }
+
+            result
+        }
+
+        pub fn stop(&self) {
+            self.generation.set(self.generation.get() + 1);
+            self.set_playing(false);
+        }
+
+        /// Time to wait between two messages, as they were recorded, scaled by the speed
+        fn delay(&self, previous: &glib::DateTime, timestamp: &glib::DateTime) -> Duration {
+            let speed = self.speed.get();
+
+            if speed <= 0.0 {
+                return Duration::ZERO;
+            }
+
+            let micros = timestamp.difference(previous).as_microseconds().max(0);
+
+            Duration::from_micros(micros as u64).div_f64(speed)
+        }
+
+        /// Copy of a recorded message that can be published, with its topic rewritten
+        fn replayed_message(&self, msg: &MQTTyClientMessage) -> MQTTyClientMessage {
+            let msg = msg.duplicate();
+
+            let topic_prefix = self.topic_prefix.borrow();
+
+            if !topic_prefix.is_empty() {
+                if let Some(rest) = msg.topic().strip_prefix(topic_prefix.as_str()) {
+                    msg.set_topic(format!("{}{}", self.new_topic_prefix.borrow(), rest));
+                }
+            }
+
+            // Aliases and subscription identifiers belong to the recorded connection
+            msg.set_topic_alias(0);
+            msg.set_subscription_identifiers(&[]);
+
+            msg
+        }
+
+        fn set_playing(&self, playing: bool) {
+            if self.playing.replace(playing) != playing {
+                self.obj().notify_playing();
+            }
+        }
+
+        fn set_position(&self, position: u32) {
+            if self.position.replace(position) != position {
+                self.obj().notify_position();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Replays a session recorded by [`super::MQTTySessionRecorder`] through any client,
+    /// keeping the time between messages, scaled by :speed
+    pub struct MQTTySessionPlayer(ObjectSubclass<imp::MQTTySessionPlayer>);
+}
+
+impl Default for MQTTySessionPlayer {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl MQTTySessionPlayer {
+    /// Loads the messages of a session file, replacing the previous ones
+    pub async fn load(&self, file: &gio::File) -> Result<(), glib::Error> {
+        self.imp().load(file).await
+    }
+
+    /// Publishes the loaded messages through `client`, it returns once every message
+    /// is published, when the playback is stopped, or when publishing fails
+    pub async fn play(&self, client: &MQTTyClient) -> Result<(), MQTTyClientError> {
+        self.imp().play(client).await
+    }
+
+    pub fn stop(&self) {
+        self.imp().stop();
+    }
+
+    /// Version of the recorded messages, it's the version the session is replayed with
+    pub fn mqtt_version(&self) -> Option<super::MQTTyClientVersion> {
+        self.imp()
+            .messages
+            .borrow()
+            .first()
+            .map(MQTTyClientMessage::mqtt_version)
+    }
+}
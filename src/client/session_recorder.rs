@@ -0,0 +1,174 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::MQTTyClientMessage;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::MQTTySessionRecorder)]
+    pub struct MQTTySessionRecorder {
+        /// File the session is being recorded to
+        #[property(get, nullable)]
+        file: RefCell<Option<gio::File>>,
+
+        #[property(get)]
+        recording: Cell<bool>,
+
+        /// Messages recorded since the recording started
+        #[property(get)]
+        message_count: Cell<u64>,
+
+        /// Lines waiting to be written, the writer task closes the file once this is
+        /// dropped
+        sender: RefCell<Option<async_channel::Sender<Vec<u8>>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTySessionRecorder {
+        const NAME: &'static str = "MQTTySessionRecorder";
+
+        type Type = super::MQTTySessionRecorder;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTySessionRecorder {
+        fn dispose(&self) {
+            self.stop();
+        }
+    }
+
+    impl MQTTySessionRecorder {
+        pub async fn start(&self, file: &gio::File) -> Result<(), glib::Error> {
+            self.stop();
+
+            let stream = file
+                .replace_future(
+                    None,
+                    false,
+                    gio::FileCreateFlags::REPLACE_DESTINATION,
+                    glib::Priority::DEFAULT,
+                )
+                .await?;
+
+            let (sender, receiver) = async_channel::unbounded::<Vec<u8>>();
+
+            glib::spawn_future_local(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                async move {
+                    while let Ok(line) = receiver.recv().await {
+                        if let Err((_, e)) = stream
+                            .write_all_future(line, glib::Priority::DEFAULT_IDLE)
+                            .await
+                        {
+                            tracing::error!("Could not write to the session file: {}", e);
+                            this.stop();
+                            break;
+                        }
+                    }
+
+                    if let Err(e) = stream.close_future(glib::Priority::DEFAULT).await {
+                        tracing::error!("Could not close the session file: {}", e);
+                    }
+                }
+            ));
+
+            let obj = self.obj();
+
+            self.sender.replace(Some(sender));
+            self.file.replace(Some(file.clone()));
+            self.message_count.set(0);
+            self.recording.set(true);
+
+            obj.notify_file();
+            obj.notify_message_count();
+            obj.notify_recording();
+
+            Ok(())
+        }
+
+        pub fn push(&self, msg: &MQTTyClientMessage) {
+            let Some(sender) = self.sender.borrow().clone() else {
+                return;
+            };
+
+            let mut line = match serde_json::to_vec(msg) {
+                Ok(line) => line,
+                Err(e) => {
+                    tracing::error!("Could not serialize a recorded message: {}", e);
+                    return;
+                }
+            };
+            line.push(b'\n');
+
+            if sender.try_send(line).is_ok() {
+                self.message_count.set(self.message_count.get() + 1);
+                self.obj().notify_message_count();
+            }
+        }
+
+        pub fn stop(&self) {
+            // Dropping the sender lets the writer task write the pending lines and
+            // close the file
+            if self.sender.take().is_none() {
+                return;
+            }
+
+            self.recording.set(false);
+            self.obj().notify_recording();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Records messages into a session file, one serialized [`MQTTyClientMessage`] per
+    /// line, so that it can be replayed later with its original timing, see
+    /// [`super::MQTTySessionPlayer`]
+    pub struct MQTTySessionRecorder(ObjectSubclass<imp::MQTTySessionRecorder>);
+}
+
+impl Default for MQTTySessionRecorder {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl MQTTySessionRecorder {
+    /// Starts recording into `file`, replacing its contents, a previous recording is
+    /// stopped first
+    pub async fn start(&self, file: &gio::File) -> Result<(), glib::Error> {
+        self.imp().start(file).await
+    }
+
+    /// Records `msg` if there is a recording in progress
+    pub fn push(&self, msg: &MQTTyClientMessage) {
+        self.imp().push(msg);
+    }
+
+    pub fn stop(&self) {
+        self.imp().stop();
+    }
+}
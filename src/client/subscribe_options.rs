@@ -0,0 +1,116 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::Cell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::pgettext;
+use gtk::glib;
+use serde::{Deserialize, Serialize};
+
+use crate::client::MQTTyClientQos;
+
+#[derive(Default, Clone, Copy, glib::Enum, PartialEq, Debug, Serialize, Deserialize)]
+#[enum_type(name = "MQTTyRetainHandling")]
+#[serde(rename_all = "kebab-case")]
+pub enum MQTTyRetainHandling {
+    /// Send retained messages every time a subscription is made
+    #[default]
+    SendOnSubscribe,
+    /// Send retained messages only if the subscription didn't exist
+    SendOnNewSubscribe,
+    DontSend,
+}
+
+impl MQTTyRetainHandling {
+    pub fn listed() -> &'static [MQTTyRetainHandling] {
+        &[
+            MQTTyRetainHandling::SendOnSubscribe,
+            MQTTyRetainHandling::SendOnNewSubscribe,
+            MQTTyRetainHandling::DontSend,
+        ]
+    }
+
+    pub fn translated(&self) -> String {
+        match self {
+            MQTTyRetainHandling::SendOnSubscribe => {
+                pgettext("retain handling", "Send on subscribe")
+            }
+            MQTTyRetainHandling::SendOnNewSubscribe => {
+                pgettext("retain handling", "Send on new subscription")
+            }
+            MQTTyRetainHandling::DontSend => pgettext("retain handling", "Don't send"),
+        }
+    }
+}
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::MQTTySubscribeOptions)]
+    pub struct MQTTySubscribeOptions {
+        #[property(get, set, builder(Default::default()))]
+        qos: Cell<MQTTyClientQos>,
+
+        /// Don't receive the messages published by this same client, only MQTT v5
+        #[property(get, set)]
+        no_local: Cell<bool>,
+
+        /// Keep the retain flag of forwarded messages, only MQTT v5
+        #[property(get, set)]
+        retain_as_published: Cell<bool>,
+
+        /// Only MQTT v5
+        #[property(get, set, builder(Default::default()))]
+        retain_handling: Cell<MQTTyRetainHandling>,
+
+        /// Identifier attached by the broker to the messages matching this subscription,
+        /// 0 means no identifier, only MQTT v5
+        #[property(get, set, maximum = 268_435_455)]
+        subscription_identifier: Cell<u32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTySubscribeOptions {
+        const NAME: &'static str = "MQTTySubscribeOptions";
+
+        type Type = super::MQTTySubscribeOptions;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTySubscribeOptions {}
+}
+
+glib::wrapper! {
+    /// Options of a subscription, everything but the QoS is ignored on MQTT v3.x
+    pub struct MQTTySubscribeOptions(ObjectSubclass<imp::MQTTySubscribeOptions>);
+}
+
+impl Default for MQTTySubscribeOptions {
+    fn default() -> Self {
+        Self::new(MQTTyClientQos::default())
+    }
+}
+
+impl MQTTySubscribeOptions {
+    pub fn new(qos: MQTTyClientQos) -> Self {
+        glib::Object::builder().property("qos", qos).build()
+    }
+}
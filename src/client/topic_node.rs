@@ -0,0 +1,240 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::{HashMap, VecDeque};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::MQTTyClientMessage;
+
+/// Number of messages kept in the history of every topic
+const HISTORY_LIMIT: u32 = 50;
+
+/// Window used to compute the message rate of the nodes, in microseconds
+const RATE_WINDOW: i64 = 10_000_000;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::MQTTyTopicNode)]
+    pub struct MQTTyTopicNode {
+        /// Topic level of this node, can be empty, e.g. the first level of "/a"
+        #[property(get, construct_only)]
+        name: RefCell<String>,
+
+        /// Full topic of this node
+        #[property(get, construct_only)]
+        topic: RefCell<String>,
+
+        #[property(get, nullable)]
+        last_message: RefCell<Option<MQTTyClientMessage>>,
+
+        /// Messages received in this exact topic
+        #[property(get)]
+        message_count: Cell<u64>,
+
+        /// Messages received in this topic and all of its subtopics
+        #[property(get)]
+        subtree_message_count: Cell<u64>,
+
+        /// Messages per second received in this topic and all of its subtopics
+        #[property(get)]
+        message_rate: Cell<f64>,
+
+        /// Whether the last message of this topic was retained
+        #[property(get)]
+        retained: Cell<bool>,
+
+        #[property(get)]
+        n_children: Cell<u32>,
+
+        /// List of [`super::MQTTyTopicNode`] sorted by name
+        pub children: OnceCell<gio::ListStore>,
+
+        children_index: RefCell<HashMap<String, super::MQTTyTopicNode>>,
+
+        /// List of [`MQTTyClientMessage`] received in this exact topic, newest first
+        pub history: OnceCell<gio::ListStore>,
+
+        /// Monotonic time of the messages received inside of the rate window
+        recent: RefCell<VecDeque<i64>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyTopicNode {
+        const NAME: &'static str = "MQTTyTopicNode";
+
+        type Type = super::MQTTyTopicNode;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyTopicNode {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.children
+                .get_or_init(gio::ListStore::new::<super::MQTTyTopicNode>);
+            self.history
+                .get_or_init(gio::ListStore::new::<MQTTyClientMessage>);
+        }
+    }
+
+    impl MQTTyTopicNode {
+        pub fn child_or_insert(&self, name: &str, topic: &str) -> super::MQTTyTopicNode {
+            if let Some(child) = self.children_index.borrow().get(name) {
+                return child.clone();
+            }
+
+            let child = super::MQTTyTopicNode::new(name, topic);
+
+            self.children_index
+                .borrow_mut()
+                .insert(name.to_string(), child.clone());
+
+            self.children().insert_sorted(&child, |a, b| {
+                let a = a.downcast_ref::<super::MQTTyTopicNode>().unwrap();
+                let b = b.downcast_ref::<super::MQTTyTopicNode>().unwrap();
+                a.name().cmp(&b.name())
+            });
+
+            self.n_children.set(self.n_children.get() + 1);
+            self.obj().notify_n_children();
+
+            child
+        }
+
+        /// Counts a message received in this topic or in one of its subtopics
+        pub fn record(&self, now: i64) {
+            self.subtree_message_count
+                .set(self.subtree_message_count.get() + 1);
+            self.obj().notify_subtree_message_count();
+
+            self.recent.borrow_mut().push_back(now);
+            self.update_rate(now);
+        }
+
+        pub fn push_message(&self, msg: &MQTTyClientMessage) {
+            let obj = self.obj();
+
+            self.message_count.set(self.message_count.get() + 1);
+            obj.notify_message_count();
+
+            if self.retained.replace(msg.retained()) != msg.retained() {
+                obj.notify_retained();
+            }
+
+            self.last_message.replace(Some(msg.clone()));
+            obj.notify_last_message();
+
+            let history = self.history();
+            history.insert(0, msg);
+            if history.n_items() > HISTORY_LIMIT {
+                history.remove(HISTORY_LIMIT);
+            }
+        }
+
+        /// Updates the rate of this node and its descendants, a node without recent
+        /// messages can't have descendants with recent messages, so those are skipped
+        pub fn refresh_rate(&self, now: i64) {
+            if self.recent.borrow().is_empty() && self.message_rate.get() == 0.0 {
+                return;
+            }
+
+            self.update_rate(now);
+
+            for child in self.children_index.borrow().values() {
+                child.imp().refresh_rate(now);
+            }
+        }
+
+        fn update_rate(&self, now: i64) {
+            let mut recent = self.recent.borrow_mut();
+
+            while recent.front().is_some_and(|t| now - t > RATE_WINDOW) {
+                recent.pop_front();
+            }
+
+            let rate = recent.len() as f64 / (RATE_WINDOW as f64 / 1_000_000.0);
+
+            drop(recent);
+
+            if self.message_rate.replace(rate) != rate {
+                self.obj().notify_message_rate();
+            }
+        }
+
+        pub fn clear(&self) {
+            let obj = self.obj();
+
+            self.children().remove_all();
+            self.children_index.borrow_mut().clear();
+            self.history().remove_all();
+            self.recent.borrow_mut().clear();
+
+            self.last_message.replace(None);
+            self.message_count.set(0);
+            self.subtree_message_count.set(0);
+            self.message_rate.set(0.0);
+            self.retained.set(false);
+            self.n_children.set(0);
+
+            obj.notify_last_message();
+            obj.notify_message_count();
+            obj.notify_subtree_message_count();
+            obj.notify_message_rate();
+            obj.notify_retained();
+            obj.notify_n_children();
+        }
+
+        pub fn children(&self) -> &gio::ListStore {
+            self.children.get().unwrap()
+        }
+
+        pub fn history(&self) -> &gio::ListStore {
+            self.history.get().unwrap()
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Topic level of a [`MQTTyTopicTree`], along with the statistics of its messages
+    pub struct MQTTyTopicNode(ObjectSubclass<imp::MQTTyTopicNode>);
+}
+
+impl MQTTyTopicNode {
+    pub(super) fn new(name: &str, topic: &str) -> Self {
+        glib::Object::builder()
+            .property("name", name)
+            .property("topic", topic)
+            .build()
+    }
+
+    /// List of [`MQTTyTopicNode`] sorted by name
+    pub fn children(&self) -> gio::ListStore {
+        self.imp().children().clone()
+    }
+
+    /// Latest messages received in this exact topic, newest first
+    pub fn history(&self) -> gio::ListStore {
+        self.imp().history().clone()
+    }
+}
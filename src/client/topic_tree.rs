@@ -0,0 +1,117 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{OnceCell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::{MQTTyClientMessage, MQTTyTopicNode};
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::MQTTyTopicTree)]
+    pub struct MQTTyTopicTree {
+        /// Node without name that holds the first topic levels
+        #[property(get)]
+        root: OnceCell<MQTTyTopicNode>,
+
+        rate_source: RefCell<Option<glib::SourceId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyTopicTree {
+        const NAME: &'static str = "MQTTyTopicTree";
+
+        type Type = super::MQTTyTopicTree;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyTopicTree {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.root.get_or_init(|| MQTTyTopicNode::new("", ""));
+
+            // Rates need to go down when no more messages are received
+            let source = glib::timeout_add_seconds_local(
+                1,
+                glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    #[upgrade_or]
+                    glib::ControlFlow::Break,
+                    move || {
+                        this.obj().root().imp().refresh_rate(glib::monotonic_time());
+                        glib::ControlFlow::Continue
+                    }
+                ),
+            );
+
+            self.rate_source.replace(Some(source));
+        }
+
+        fn dispose(&self) {
+            if let Some(source) = self.rate_source.take() {
+                source.remove();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Trie of topic levels built from received messages, e.g. "a/b" and "a/c" are
+    /// stored as the node "a" with the children "b" and "c"
+    pub struct MQTTyTopicTree(ObjectSubclass<imp::MQTTyTopicTree>);
+}
+
+impl Default for MQTTyTopicTree {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl MQTTyTopicTree {
+    pub fn insert(&self, msg: &MQTTyClientMessage) {
+        let now = glib::monotonic_time();
+
+        let mut node = self.root();
+        node.imp().record(now);
+
+        let mut topic = String::new();
+
+        for (i, level) in msg.topic().split('/').enumerate() {
+            if i != 0 {
+                topic.push('/');
+            }
+            topic.push_str(level);
+
+            node = node.imp().child_or_insert(level, &topic);
+            node.imp().record(now);
+        }
+
+        node.imp().push_message(msg);
+    }
+
+    pub fn clear(&self) {
+        self.root().imp().clear();
+    }
+}
@@ -15,14 +15,27 @@
 
 use gettextrs::pgettext;
 use gtk::glib;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default, Clone, Copy, glib::Enum, PartialEq)]
+use crate::protobuf_registry::MQTTyProtobufRegistry;
+
+#[derive(Default, Clone, Copy, glib::Enum, PartialEq, Serialize, Deserialize)]
 #[enum_type(name = "MQTTyContentType")]
+#[serde(rename_all = "lowercase")]
 pub enum MQTTyContentType {
     #[default]
     None,
     Json,
     Xml,
+    Yaml,
+    Text,
+    /// Edited as JSON, encoded to CBOR when sending
+    Cbor,
+    /// Edited as JSON, encoded to MessagePack when sending
+    MessagePack,
+    /// Edited as JSON, encoded to the wire format of a message type of
+    /// [`MQTTyProtobufRegistry`] when sending
+    Protobuf,
     Raw,
 }
 
@@ -32,6 +45,11 @@ impl MQTTyContentType {
             MQTTyContentType::None,
             MQTTyContentType::Json,
             MQTTyContentType::Xml,
+            MQTTyContentType::Yaml,
+            MQTTyContentType::Text,
+            MQTTyContentType::Cbor,
+            MQTTyContentType::MessagePack,
+            MQTTyContentType::Protobuf,
             MQTTyContentType::Raw,
         ]
     }
@@ -41,6 +59,11 @@ impl MQTTyContentType {
             MQTTyContentType::None => pgettext("body content type", "(none)"),
             MQTTyContentType::Json => pgettext("body content type", "JSON"),
             MQTTyContentType::Xml => pgettext("body content type", "XML"),
+            MQTTyContentType::Yaml => pgettext("body content type", "YAML"),
+            MQTTyContentType::Text => pgettext("body content type", "Plain Text"),
+            MQTTyContentType::Cbor => pgettext("body content type", "CBOR"),
+            MQTTyContentType::MessagePack => pgettext("body content type", "MessagePack"),
+            MQTTyContentType::Protobuf => pgettext("body content type", "Protobuf"),
             MQTTyContentType::Raw => pgettext("body content type", "Raw"),
         }
     }
@@ -50,7 +73,83 @@ impl MQTTyContentType {
             MQTTyContentType::None => None,
             MQTTyContentType::Json => Some("application/json"),
             MQTTyContentType::Xml => Some("text/xml"),
+            MQTTyContentType::Yaml => Some("application/yaml"),
+            MQTTyContentType::Text => Some("text/plain"),
+            MQTTyContentType::Cbor => Some("application/cbor"),
+            MQTTyContentType::MessagePack => Some("application/msgpack"),
+            MQTTyContentType::Protobuf => Some("application/x-protobuf"),
             MQTTyContentType::Raw => Some("application/octet-stream"),
         }
     }
+
+    /// Reverse operation of [`MQTTyContentType::mime_type()`], parameters such as
+    /// "; charset=utf-8" and the usual aliases of each MIME type are accepted
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        let essence = mime_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        match essence.as_str() {
+            "application/json" | "text/json" => Some(MQTTyContentType::Json),
+            "text/xml" | "application/xml" => Some(MQTTyContentType::Xml),
+            "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => {
+                Some(MQTTyContentType::Yaml)
+            }
+            "text/plain" => Some(MQTTyContentType::Text),
+            "application/cbor" => Some(MQTTyContentType::Cbor),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(MQTTyContentType::MessagePack)
+            }
+            "application/x-protobuf"
+            | "application/protobuf"
+            | "application/vnd.google.protobuf" => Some(MQTTyContentType::Protobuf),
+            "application/octet-stream" => Some(MQTTyContentType::Raw),
+            _ => None,
+        }
+    }
+
+    /// Sourceview language used for highlighting the text of this content type, binary
+    /// formats are edited as JSON
+    pub fn language_id(&self) -> Option<&'static str> {
+        match self {
+            MQTTyContentType::None | MQTTyContentType::Text | MQTTyContentType::Raw => None,
+            MQTTyContentType::Json
+            | MQTTyContentType::Cbor
+            | MQTTyContentType::MessagePack
+            | MQTTyContentType::Protobuf => Some("json"),
+            MQTTyContentType::Xml => Some("xml"),
+            MQTTyContentType::Yaml => Some("yaml"),
+        }
+    }
+
+    /// Converts the text edited by the user to the payload that is sent, the String
+    /// error is a human readable explanation. `message_type` is only used by
+    /// [`MQTTyContentType::Protobuf`]
+    pub fn encode(&self, text: &str, message_type: &str) -> Result<Vec<u8>, String> {
+        match self {
+            MQTTyContentType::None => Ok(vec![]),
+            MQTTyContentType::Cbor => {
+                let value =
+                    serde_json::from_str::<serde_json::Value>(text).map_err(|e| e.to_string())?;
+
+                let mut payload = vec![];
+                ciborium::into_writer(&value, &mut payload).map_err(|e| e.to_string())?;
+
+                Ok(payload)
+            }
+            MQTTyContentType::MessagePack => {
+                let value =
+                    serde_json::from_str::<serde_json::Value>(text).map_err(|e| e.to_string())?;
+
+                rmp_serde::to_vec_named(&value).map_err(|e| e.to_string())
+            }
+            MQTTyContentType::Protobuf => {
+                MQTTyProtobufRegistry::get_singleton().encode(message_type, text)
+            }
+            _ => Ok(text.as_bytes().to_vec()),
+        }
+    }
 }
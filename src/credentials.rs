@@ -0,0 +1,238 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Broker passwords are never written to GSettings or workspace files, they are stored
+//! in the system keyring through the Secret Service API and referenced by a
+//! credentials ID.
+//!
+//! When there is no keyring available (e.g. headless sessions without a Secret Service
+//! daemon), an encrypted keyring file under the user data directory is used instead,
+//! protected by a password that the user enters once per session, see
+//! [`file_keyring()`]. Sandboxed builds get their keyring file secret from the Secret
+//! portal, through [`oo7::Keyring::new()`].
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use futures::lock::Mutex;
+use gettextrs::gettext;
+use gtk::glib;
+
+use crate::application::MQTTyApplication;
+use crate::config;
+
+/// Environment variable with the secret used for encrypting the fallback keyring file,
+/// if it's not set, the user is asked for the password of the keyring file
+pub const KEYRING_SECRET_ENV: &str = "MQTTY_KEYRING_SECRET";
+
+const CREDENTIALS_ID_ATTRIBUTE: &str = "credentials-id";
+
+thread_local! {
+    static KEYRING: RefCell<Option<Rc<oo7::Keyring>>> = const { RefCell::new(None) };
+
+    /// Held while the keyring is opened, so that the user is asked for the password of
+    /// the keyring file only once
+    static KEYRING_LOCK: Rc<Mutex<()>> = Rc::new(Mutex::new(()));
+}
+
+/// Returns a new random credentials ID, used by connection profiles
+pub fn new_credentials_id() -> String {
+    glib::uuid_string_random().to_string()
+}
+
+/// Credentials ID shared by every publish tab and subscriptions view that connects to
+/// the same broker with the same username, used by workspaces
+pub fn broker_credentials_id(url: &str, username: &str) -> String {
+    glib::compute_checksum_for_string(glib::ChecksumType::Sha256, format!("{url}\n{username}"))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Credentials ID under which the TLS client key passphrase of `credentials_id` is
+/// stored, it's kept as a separate keyring item next to the password
+pub fn client_key_passphrase_id(credentials_id: &str) -> String {
+    format!("{credentials_id}/client-key-passphrase")
+}
+
+fn attributes(credentials_id: &str) -> HashMap<&str, &str> {
+    HashMap::from([
+        (oo7::XDG_SCHEMA_ATTRIBUTE, config::APP_ID),
+        (CREDENTIALS_ID_ATTRIBUTE, credentials_id),
+    ])
+}
+
+async fn keyring() -> Result<Rc<oo7::Keyring>, oo7::Error> {
+    let lock = KEYRING_LOCK.with(Clone::clone);
+    let _guard = lock.lock().await;
+
+    if let Some(keyring) = KEYRING.with_borrow(Clone::clone) {
+        return Ok(keyring);
+    }
+
+    let keyring = match oo7::Keyring::new().await {
+        Ok(keyring) => keyring,
+        Err(e) => {
+            tracing::warn!(
+                "System keyring not available, using the keyring file: {}",
+                e
+            );
+            file_keyring().await?
+        }
+    };
+
+    keyring.unlock().await?;
+
+    let keyring = Rc::new(keyring);
+    KEYRING.set(Some(keyring.clone()));
+
+    Ok(keyring)
+}
+
+fn file_keyring_dir() -> PathBuf {
+    glib::user_data_dir().join("MQTTy").join("keyrings")
+}
+
+/// Keyring file encrypted with the secret of [`KEYRING_SECRET_ENV`], or with a password
+/// entered by the user, the encryption key is derived from it by oo7. Nothing that
+/// decrypts the keyring file is stored on disk
+async fn file_keyring() -> Result<oo7::Keyring, oo7::Error> {
+    let dir = file_keyring_dir();
+
+    std::fs::create_dir_all(&dir).map_err(oo7::file::Error::from)?;
+
+    let path = dir.join("MQTTy.keyring");
+
+    if let Ok(secret) = std::env::var(KEYRING_SECRET_ENV) {
+        if !secret.is_empty() {
+            let keyring = oo7::file::Keyring::load(&path, oo7::Secret::text(secret)).await?;
+            return Ok(oo7::Keyring::File(keyring.into()));
+        }
+    }
+
+    let new = !path.exists();
+    let mut wrong_password = false;
+
+    loop {
+        let password = ask_keyring_password(new, wrong_password).await?;
+
+        match oo7::file::Keyring::load(&path, oo7::Secret::text(password)).await {
+            Ok(keyring) => return Ok(oo7::Keyring::File(keyring.into())),
+            Err(oo7::file::Error::IncorrectSecret) => wrong_password = true,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Asks the user for the password of the keyring file, `new` if the password is being
+/// chosen. Fails if the user doesn't enter it
+async fn ask_keyring_password(new: bool, wrong_password: bool) -> Result<String, oo7::Error> {
+    const RESPONSE_UNLOCK: &str = "unlock";
+
+    let cancelled = || {
+        oo7::Error::from(oo7::file::Error::from(std::io::Error::other(
+            "the password of the keyring file was not entered",
+        )))
+    };
+
+    let Some(window) = MQTTyApplication::get_singleton().active_window() else {
+        return Err(cancelled());
+    };
+
+    let entry = gtk::PasswordEntry::builder()
+        .show_peek_icon(true)
+        .activates_default(true)
+        .build();
+
+    let body = if wrong_password {
+        gettext("The password is not correct, try again")
+    } else if new {
+        gettext("There is no system keyring, so saved passwords are kept in an encrypted file. Choose the password that protects it, it's asked once every time MQTTy starts")
+    } else {
+        gettext("There is no system keyring, enter the password of the file where saved passwords are kept")
+    };
+
+    let dialog = adw::AlertDialog::builder()
+        .heading(if new {
+            gettext("Protect Saved Passwords")
+        } else {
+            gettext("Unlock Saved Passwords")
+        })
+        .body(body)
+        .extra_child(&entry)
+        .close_response("cancel")
+        .default_response(RESPONSE_UNLOCK)
+        .build();
+
+    dialog.add_response("cancel", &gettext("Cancel"));
+    dialog.add_response(
+        RESPONSE_UNLOCK,
+        &if new {
+            gettext("Save")
+        } else {
+            gettext("Unlock")
+        },
+    );
+    dialog.set_response_appearance(RESPONSE_UNLOCK, adw::ResponseAppearance::Suggested);
+    dialog.set_response_enabled(RESPONSE_UNLOCK, false);
+
+    entry.connect_changed(glib::clone!(
+        #[weak]
+        dialog,
+        move |entry| dialog.set_response_enabled(RESPONSE_UNLOCK, !entry.text().is_empty())
+    ));
+
+    if dialog.choose_future(&window).await != RESPONSE_UNLOCK {
+        return Err(cancelled());
+    }
+
+    Ok(entry.text().to_string())
+}
+
+/// Saves `password` in the keyring, replacing the previous one of `credentials_id`
+pub async fn store_password(
+    credentials_id: &str,
+    label: &str,
+    password: &str,
+) -> Result<(), oo7::Error> {
+    keyring()
+        .await?
+        .create_item(label, &attributes(credentials_id), password, true)
+        .await
+}
+
+/// Returns the password of `credentials_id`, or None if it's not in the keyring
+pub async fn lookup_password(credentials_id: &str) -> Result<Option<String>, oo7::Error> {
+    let items = keyring()
+        .await?
+        .search_items(&attributes(credentials_id))
+        .await?;
+
+    let Some(item) = items.first() else {
+        return Ok(None);
+    };
+
+    let secret = item.secret().await?;
+
+    Ok(Some(
+        String::from_utf8_lossy(secret.as_bytes()).into_owned(),
+    ))
+}
+
+pub async fn delete_password(credentials_id: &str) -> Result<(), oo7::Error> {
+    keyring().await?.delete(&attributes(credentials_id)).await
+}
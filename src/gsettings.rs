@@ -13,25 +13,88 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
 use gtk::glib;
 use gtk::glib::variant::{FromVariant, StaticVariantType};
 use gtk::prelude::*;
 
+use crate::client::MQTTyClientVersion;
+use crate::credentials;
+use crate::objects::{MQTTyTlsSettings, MQTTyWillSettings};
+
 mod imp {
 
     use super::*;
 
-    #[derive(Default, glib::Properties)]
+    #[derive(glib::Properties)]
     #[properties(wrapper_type = super::MQTTySettingConnection)]
     pub struct MQTTySettingConnection {
+        /// Human readable name, if empty, the topic or the url is shown instead
+        #[property(get, set)]
+        name: RefCell<String>,
+
         #[property(get, set)]
         url: RefCell<String>,
 
         #[property(get, set)]
         topic: RefCell<String>,
+
+        #[property(get, set, builder(MQTTyClientVersion::V3X))]
+        mqtt_version: Cell<MQTTyClientVersion>,
+
+        /// Client identifier sent to the broker, if empty the broker will assign one
+        #[property(get, set)]
+        client_id: RefCell<String>,
+
+        #[property(get, set)]
+        username: RefCell<String>,
+
+        /// Not saved to GSettings, it's stored in the keyring under :credentials-id if
+        /// :remember-password is enabled
+        #[property(get, set)]
+        password: RefCell<String>,
+
+        /// ID of the password in the keyring, generated the first time it's stored
+        #[property(get, set)]
+        credentials_id: RefCell<String>,
+
+        /// If disabled, the password is asked every time the connection is opened
+        #[property(get, set)]
+        remember_password: Cell<bool>,
+
+        /// Maximum seconds between control packets sent to the broker, 0 disables the
+        /// keep alive mechanism
+        #[property(get, set)]
+        keep_alive_interval: Cell<u32>,
+
+        #[property(get, set)]
+        tls: RefCell<MQTTyTlsSettings>,
+
+        #[property(get, set)]
+        will: RefCell<MQTTyWillSettings>,
+    }
+
+    impl Default for MQTTySettingConnection {
+        fn default() -> Self {
+            Self {
+                name: Default::default(),
+                url: Default::default(),
+                topic: Default::default(),
+                mqtt_version: Default::default(),
+                client_id: Default::default(),
+                username: Default::default(),
+                password: Default::default(),
+                credentials_id: Default::default(),
+                remember_password: Cell::new(true),
+                keep_alive_interval: Cell::new(60),
+                tls: Default::default(),
+                will: Default::default(),
+            }
+        }
     }
 
     #[glib::object_subclass]
@@ -58,6 +121,83 @@ impl MQTTySettingConnection {
             .property("topic", topic)
             .build()
     }
+
+    /// Name shown to the user, connections migrated from the legacy format don't have
+    /// a name, so the topic or the url is used instead
+    pub fn display_name(&self) -> String {
+        [self.name(), self.topic(), self.url()]
+            .into_iter()
+            .find(|s| !s.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Saves the password and the TLS client key passphrase in the keyring if they must
+    /// be remembered, otherwise they're removed from it. Must be called before saving
+    /// the connection to GSettings, the credentials ID may be generated here
+    pub async fn store_credentials(&self) -> Result<(), oo7::Error> {
+        if self.credentials_id().is_empty() {
+            self.set_credentials_id(credentials::new_credentials_id());
+        }
+
+        let credentials_id = self.credentials_id();
+        let passphrase_id = credentials::client_key_passphrase_id(&credentials_id);
+        let password = self.password();
+        let passphrase = self.tls().client_key_passphrase();
+
+        if self.remember_password() && !password.is_empty() {
+            let label =
+                formatx!(gettext("MQTTy password for {}"), self.display_name()).unwrap_or_default();
+
+            credentials::store_password(&credentials_id, &label, &password).await?;
+        } else {
+            credentials::delete_password(&credentials_id).await?;
+        }
+
+        if self.remember_password() && !passphrase.is_empty() {
+            let label = formatx!(
+                gettext("MQTTy client key passphrase for {}"),
+                self.display_name()
+            )
+            .unwrap_or_default();
+
+            credentials::store_password(&passphrase_id, &label, &passphrase).await
+        } else {
+            credentials::delete_password(&passphrase_id).await
+        }
+    }
+
+    pub async fn delete_credentials(&self) -> Result<(), oo7::Error> {
+        let credentials_id = self.credentials_id();
+
+        if credentials_id.is_empty() {
+            return Ok(());
+        }
+
+        credentials::delete_password(&credentials_id).await?;
+        credentials::delete_password(&credentials::client_key_passphrase_id(&credentials_id)).await
+    }
+
+    /// Retrieves the password and the TLS client key passphrase from the keyring if
+    /// they were remembered
+    pub async fn load_credentials(&self) -> Result<(), oo7::Error> {
+        let credentials_id = self.credentials_id();
+
+        if !self.remember_password() || credentials_id.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(password) = credentials::lookup_password(&credentials_id).await? {
+            self.set_password(password);
+        }
+
+        let passphrase_id = credentials::client_key_passphrase_id(&credentials_id);
+
+        if let Some(passphrase) = credentials::lookup_password(&passphrase_id).await? {
+            self.tls().set_client_key_passphrase(passphrase);
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for MQTTySettingConnection {
@@ -66,7 +206,22 @@ impl Default for MQTTySettingConnection {
     }
 }
 
-const VARIANT_TYPE: &str = "(ss)";
+/// Version of the dictionary format, it's bumped when a key changes its meaning, adding
+/// new keys doesn't need a bump, unknown keys are ignored and missing keys get their
+/// default value
+pub const CONNECTION_VERSION: u32 = 1;
+
+const VARIANT_TYPE: &str = "a{sv}";
+
+/// Format of the legacy "connections" key
+const LEGACY_VARIANT_TYPE: &str = "(ss)";
+
+/// Properties not written to the settings, secrets are kept out of GSettings
+const CONNECTION_SKIPPED_PROPERTIES: &[&str] = &["password"];
+
+/// The client key passphrase is stored in the keyring, see
+/// [`MQTTySettingConnection::store_credentials()`]
+const TLS_SKIPPED_PROPERTIES: &[&str] = &["client-key-passphrase"];
 
 impl StaticVariantType for MQTTySettingConnection {
     fn static_variant_type() -> std::borrow::Cow<'static, gtk::glib::VariantTy> {
@@ -79,18 +234,70 @@ impl StaticVariantType for MQTTySettingConnection {
 /// - 1 <-> topic: MQTT topic
 type MQTTySettingConnectionTuple = (String, String);
 
+/// Dictionary keys:
+/// - "version": u32, see [`CONNECTION_VERSION`]
+/// - One key for each property of [`MQTTySettingConnection`], enums are stored by their
+///   nick
+/// - "tls" and "will": nested a{sv} dictionaries with the properties of
+///   [`MQTTyTlsSettings`] and [`MQTTyWillSettings`]
+/// - "user-properties": a(ss) inside the "will" dictionary
+///
+/// Legacy "(ss)" variants are also accepted, see [`MQTTySettingConnectionTuple`]
 impl FromVariant for MQTTySettingConnection {
     fn from_variant(variant: &gtk::glib::Variant) -> Option<Self> {
-        let tuple = variant.get::<MQTTySettingConnectionTuple>();
-        if tuple.is_none() {
+        if variant.type_().as_str() == LEGACY_VARIANT_TYPE {
+            return variant
+                .get::<MQTTySettingConnectionTuple>()
+                .map(|tuple| tuple.into());
+        }
+
+        if !variant.is::<glib::VariantDict>() {
             tracing::error!(
                 "Could not convert from variant with format '{}', expected '{}'",
                 variant.type_(),
                 VARIANT_TYPE
             );
+            return None;
         }
 
-        tuple.map(|tuple| tuple.into())
+        let dict = glib::VariantDict::new(Some(variant));
+
+        let version = dict
+            .lookup::<u32>("version")
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        if version > CONNECTION_VERSION {
+            tracing::warn!(
+                "Connection saved with format version {}, only known keys of version {} are read",
+                version,
+                CONNECTION_VERSION
+            );
+        }
+
+        let conn = Self::default();
+
+        properties_from_dict(&conn, &dict, CONNECTION_SKIPPED_PROPERTIES);
+
+        let tls = MQTTyTlsSettings::default();
+        if let Some(tls_dict) = lookup_dict(&dict, "tls") {
+            properties_from_dict(&tls, &tls_dict, TLS_SKIPPED_PROPERTIES);
+        }
+        conn.set_tls(tls);
+
+        let will = MQTTyWillSettings::default();
+        if let Some(will_dict) = lookup_dict(&dict, "will") {
+            properties_from_dict(&will, &will_dict, &[]);
+
+            if let Ok(Some(user_properties)) =
+                will_dict.lookup::<Vec<(String, String)>>("user-properties")
+            {
+                will.set_user_properties(&user_properties);
+            }
+        }
+        conn.set_will(will);
+
+        Some(conn)
     }
 }
 
@@ -100,15 +307,107 @@ impl From<MQTTySettingConnectionTuple> for MQTTySettingConnection {
     }
 }
 
-impl From<MQTTySettingConnection> for MQTTySettingConnectionTuple {
+impl From<MQTTySettingConnection> for glib::Variant {
     fn from(value: MQTTySettingConnection) -> Self {
-        (value.url(), value.topic())
+        let dict = properties_to_dict(&value, CONNECTION_SKIPPED_PROPERTIES);
+        dict.insert("version", CONNECTION_VERSION);
+
+        dict.insert_value(
+            "tls",
+            &properties_to_dict(&value.tls(), TLS_SKIPPED_PROPERTIES).end(),
+        );
+
+        let will = value.will();
+        let will_dict = properties_to_dict(&will, &[]);
+        will_dict.insert("user-properties", will.user_properties());
+        dict.insert_value("will", &will_dict.end());
+
+        dict.end()
     }
 }
 
-impl From<MQTTySettingConnection> for glib::Variant {
-    fn from(value: MQTTySettingConnection) -> Self {
-        // Converts to tuple then to GVariant
-        Into::<MQTTySettingConnectionTuple>::into(value).into()
+fn lookup_dict(dict: &glib::VariantDict, key: &str) -> Option<glib::VariantDict> {
+    dict.lookup_value(key, Some(glib::VariantTy::VARDICT))
+        .map(|v| glib::VariantDict::new(Some(&v)))
+}
+
+/// Stores the readable and writable properties of `object` in a dictionary, properties
+/// holding objects are skipped, they must be stored by the caller
+fn properties_to_dict(object: &impl IsA<glib::Object>, skip: &[&str]) -> glib::VariantDict {
+    let dict = glib::VariantDict::new(None);
+
+    for pspec in object.list_properties() {
+        if skip.contains(&pspec.name()) || !pspec.flags().contains(glib::ParamFlags::READWRITE) {
+            continue;
+        }
+
+        let value = object.property_value(pspec.name());
+
+        if let Some(variant) = value_to_variant(&value) {
+            dict.insert_value(pspec.name(), &variant);
+        }
+    }
+
+    dict
+}
+
+/// Reverse operation of [`properties_to_dict`], keys with an unexpected type are ignored
+/// so that the property keeps its default value
+fn properties_from_dict(object: &impl IsA<glib::Object>, dict: &glib::VariantDict, skip: &[&str]) {
+    for pspec in object.list_properties() {
+        if skip.contains(&pspec.name()) || !pspec.flags().contains(glib::ParamFlags::READWRITE) {
+            continue;
+        }
+
+        let Some(variant) = dict.lookup_value(pspec.name(), None) else {
+            continue;
+        };
+
+        match variant_to_value(&variant, pspec.value_type()) {
+            Some(value) => object.set_property_from_value(pspec.name(), &value),
+            None => tracing::warn!(
+                "Ignoring saved key '{}' with unexpected format '{}'",
+                pspec.name(),
+                variant.type_()
+            ),
+        }
+    }
+}
+
+fn value_to_variant(value: &glib::Value) -> Option<glib::Variant> {
+    if let Some((_, enum_value)) = glib::EnumValue::from_value(value) {
+        return Some(enum_value.nick().to_variant());
+    }
+
+    match value.type_() {
+        glib::Type::STRING => Some(
+            value
+                .get::<Option<String>>()
+                .ok()?
+                .unwrap_or_default()
+                .to_variant(),
+        ),
+        glib::Type::BOOL => Some(value.get::<bool>().ok()?.to_variant()),
+        glib::Type::U32 => Some(value.get::<u32>().ok()?.to_variant()),
+        glib::Type::I32 => Some(value.get::<i32>().ok()?.to_variant()),
+        glib::Type::U64 => Some(value.get::<u64>().ok()?.to_variant()),
+        glib::Type::F64 => Some(value.get::<f64>().ok()?.to_variant()),
+        _ => None,
+    }
+}
+
+fn variant_to_value(variant: &glib::Variant, type_: glib::Type) -> Option<glib::Value> {
+    if type_.is_a(glib::Type::ENUM) {
+        return glib::EnumClass::with_type(type_)?.to_value_by_nick(variant.str()?);
+    }
+
+    match type_ {
+        glib::Type::STRING => Some(variant.get::<String>()?.to_value()),
+        glib::Type::BOOL => Some(variant.get::<bool>()?.to_value()),
+        glib::Type::U32 => Some(variant.get::<u32>()?.to_value()),
+        glib::Type::I32 => Some(variant.get::<i32>()?.to_value()),
+        glib::Type::U64 => Some(variant.get::<u64>()?.to_value()),
+        glib::Type::F64 => Some(variant.get::<f64>()?.to_value()),
+        _ => None,
     }
 }
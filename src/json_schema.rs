@@ -0,0 +1,480 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::LazyLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
+use gtk::glib;
+use gtk::glib::subclass::Signal;
+
+use crate::application::MQTTyApplication;
+use crate::client::{topic_matches_filter, MQTTyClientMessage};
+use crate::payload_decoder::MQTTyPayloadDecoder;
+
+/// Compiled JSON Schema
+pub struct MQTTyJsonSchema {
+    validator: jsonschema::Validator,
+}
+
+/// Problem found by [`MQTTyJsonSchema::validate()`]
+pub struct MQTTySchemaViolation {
+    /// Byte range of the offending text, if it could be located
+    pub range: Option<Range<usize>>,
+
+    /// Human readable explanation, prefixed by the JSON pointer of the offending value
+    pub message: String,
+}
+
+impl MQTTyJsonSchema {
+    /// Compiles the schema, the String error is a human readable explanation
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let schema = serde_json::from_str::<serde_json::Value>(text).map_err(|e| e.to_string())?;
+
+        let validator = jsonschema::validator_for(&schema).map_err(|e| e.to_string())?;
+
+        Ok(Self { validator })
+    }
+
+    /// Validates JSON text, text that is not valid JSON results in a single violation
+    /// at the position of the syntax error
+    pub fn validate(&self, text: &str) -> Vec<MQTTySchemaViolation> {
+        let instance = match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(instance) => instance,
+            Err(e) => {
+                return vec![MQTTySchemaViolation {
+                    range: Some(syntax_error_range(text, e.line(), e.column())),
+                    message: e.to_string(),
+                }];
+            }
+        };
+
+        self.validator
+            .iter_errors(&instance)
+            .map(|error| {
+                let pointer = error.instance_path().as_str();
+
+                MQTTySchemaViolation {
+                    range: pointer_range(text, pointer),
+                    message: if pointer.is_empty() {
+                        error.to_string()
+                    } else {
+                        format!("{pointer}: {error}")
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default)]
+    pub struct MQTTyJsonSchemas {
+        /// Topic filter, schema text and compiled schema, saved in the "json-schemas"
+        /// setting
+        pub schemas: RefCell<Vec<(String, String, Rc<MQTTyJsonSchema>)>>,
+
+        /// Bumped every time the schemas change, so that the violations cached on
+        /// messages are discarded
+        pub generation: Cell<u64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyJsonSchemas {
+        const NAME: &'static str = "MQTTyJsonSchemas";
+
+        type Type = super::MQTTyJsonSchemas;
+
+        type ParentType = glib::Object;
+    }
+
+    impl ObjectImpl for MQTTyJsonSchemas {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: LazyLock<Vec<Signal>> =
+                LazyLock::new(|| vec![Signal::builder("schemas-changed").build()]);
+            &*SIGNALS
+        }
+    }
+}
+
+glib::wrapper! {
+    /// JSON Schemas attached to topic filters, they validate the bodies published to
+    /// matching topics, unless the publish tab has its own schema, and flag the
+    /// received messages that don't conform to them
+    pub struct MQTTyJsonSchemas(ObjectSubclass<imp::MQTTyJsonSchemas>);
+}
+
+thread_local! {
+    static SCHEMAS: MQTTyJsonSchemas = MQTTyJsonSchemas::load();
+}
+
+impl MQTTyJsonSchemas {
+    pub fn get_singleton() -> Self {
+        SCHEMAS.with(Clone::clone)
+    }
+
+    fn load() -> Self {
+        let schemas = glib::Object::new::<Self>();
+
+        let loaded = MQTTyApplication::get_singleton()
+            .settings()
+            .get::<Vec<(String, String)>>("json-schemas")
+            .into_iter()
+            .filter_map(|(topic_filter, text)| match MQTTyJsonSchema::parse(&text) {
+                Ok(schema) => Some((topic_filter, text, Rc::new(schema))),
+                Err(e) => {
+                    tracing::error!("Could not load the JSON Schema of {}: {}", topic_filter, e);
+                    None
+                }
+            })
+            .collect();
+
+        schemas.imp().schemas.replace(loaded);
+
+        schemas
+    }
+
+    fn bump_generation(&self) {
+        let generation = &self.imp().generation;
+        generation.set(generation.get() + 1);
+    }
+
+    /// Violations of the schema attached to the topic of `message`, see
+    /// [`Self::topic_schema()`]. The result is cached on the message until its body
+    /// changes, or the schemas or the decoding settings change
+    pub fn message_violations(&self, message: &MQTTyClientMessage) -> Rc<Vec<String>> {
+        let decoder = MQTTyPayloadDecoder::get_singleton();
+
+        // Bodies are validated once decoded, so the decoding settings are part of the
+        // cache key too
+        let generation = (self.imp().generation.get(), decoder.generation());
+
+        if let Some(violations) = message.cached_schema_violations(generation) {
+            return violations;
+        }
+
+        let violations = match self.topic_schema(&message.topic()) {
+            None => vec![],
+            Some(schema) => {
+                let decoded = decoder.decode_message(message);
+
+                match &decoded.text {
+                    Some(text) if decoded.format.language_id() == Some("json") => schema
+                        .validate(text)
+                        .into_iter()
+                        .map(|violation| violation.message)
+                        .collect(),
+                    _ => vec![not_json_violation()],
+                }
+            }
+        };
+
+        let violations = Rc::new(violations);
+
+        message.cache_schema_violations(generation, violations.clone());
+
+        violations
+    }
+
+    /// Pairs of topic filter and schema text
+    pub fn schemas(&self) -> Vec<(String, String)> {
+        self.imp()
+            .schemas
+            .borrow()
+            .iter()
+            .map(|(topic_filter, text, _)| (topic_filter.clone(), text.clone()))
+            .collect()
+    }
+
+    /// Schema of the first topic filter that matches `topic`
+    pub fn topic_schema(&self, topic: &str) -> Option<Rc<MQTTyJsonSchema>> {
+        self.imp()
+            .schemas
+            .borrow()
+            .iter()
+            .find(|(topic_filter, _, _)| topic_matches_filter(topic_filter, topic))
+            .map(|(_, _, schema)| schema.clone())
+    }
+
+    /// Schema that validates a body published to `topic`, `inline` is the schema of the
+    /// publish tab, which has precedence over the topic filters if it's not empty
+    pub fn publish_schema(
+        &self,
+        inline: &str,
+        topic: &str,
+    ) -> Result<Option<Rc<MQTTyJsonSchema>>, String> {
+        if inline.trim().is_empty() {
+            return Ok(self.topic_schema(topic));
+        }
+
+        MQTTyJsonSchema::parse(inline).map(|schema| Some(Rc::new(schema)))
+    }
+
+    /// Attaches the schema to `topic_filter`, replacing its previous schema. The String
+    /// error is a human readable explanation of why the schema is not valid
+    pub fn set_topic_schema(&self, topic_filter: &str, text: &str) -> Result<(), String> {
+        let schema = MQTTyJsonSchema::parse(text)?;
+
+        {
+            let mut schemas = self.imp().schemas.borrow_mut();
+            schemas.retain(|(f, _, _)| f != topic_filter);
+            schemas.push((topic_filter.to_string(), text.to_string(), Rc::new(schema)));
+        }
+
+        self.save_schemas();
+
+        Ok(())
+    }
+
+    pub fn remove_topic_schema(&self, topic_filter: &str) {
+        self.imp()
+            .schemas
+            .borrow_mut()
+            .retain(|(f, _, _)| f != topic_filter);
+
+        self.save_schemas();
+    }
+
+    fn save_schemas(&self) {
+        if let Err(e) = MQTTyApplication::get_singleton()
+            .settings()
+            .set("json-schemas", self.schemas())
+        {
+            tracing::error!("Could not save the JSON Schemas: {}", e);
+        }
+
+        self.bump_generation();
+        self.emit_by_name::<()>("schemas-changed", &[]);
+    }
+}
+
+/// Explanation used when a JSON Schema applies to a body that is not JSON
+pub fn not_json_violation() -> String {
+    gettext("The body is not JSON, but a JSON Schema applies to it")
+}
+
+/// Human readable explanation of a schema that can't be compiled
+pub fn invalid_schema_violation(error: &str) -> String {
+    formatx!(gettext("The JSON Schema is not valid: {}"), error).unwrap_or_default()
+}
+
+/// Range of the character at the 1-based `line` and `column` reported by serde_json,
+/// column 0 means the end of the previous line
+fn syntax_error_range(text: &str, line: usize, column: usize) -> Range<usize> {
+    let line_start = text
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+
+    let start = text[line_start..]
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map(|(i, _)| line_start + i)
+        .unwrap_or(text.len());
+
+    let end = text[start..]
+        .chars()
+        .next()
+        .map(|c| start + c.len_utf8())
+        .unwrap_or(start);
+
+    // Errors at the end of the text, such as an unclosed object, are shown on the last
+    // character
+    if start == end {
+        let trimmed = text.trim_end();
+        let start = trimmed.char_indices().last().map(|(i, _)| i).unwrap_or(0);
+        return start..trimmed.len();
+    }
+
+    start..end
+}
+
+/// Byte range of the value at the JSON pointer, only the first line of objects and
+/// arrays is included so that big values are not underlined entirely
+fn pointer_range(text: &str, pointer: &str) -> Option<Range<usize>> {
+    let segments = pointer
+        .split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect::<Vec<_>>();
+
+    let range = JsonScanner { text, pos: 0 }.find(&segments)?;
+
+    let end = text[range.clone()]
+        .find('\n')
+        .map(|i| range.start + i)
+        .unwrap_or(range.end);
+
+    Some(range.start..end)
+}
+
+/// Minimal JSON scanner that finds the position of values, it assumes that the text is
+/// valid JSON
+struct JsonScanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl JsonScanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_separator(&mut self, separator: u8) {
+        self.skip_whitespace();
+
+        if self.peek() == Some(separator) {
+            self.pos += 1;
+        }
+    }
+
+    fn find(&mut self, segments: &[String]) -> Option<Range<usize>> {
+        self.skip_whitespace();
+
+        let start = self.pos;
+
+        let Some((segment, rest)) = segments.split_first() else {
+            self.skip_value()?;
+            return Some(start..self.pos);
+        };
+
+        match self.peek()? {
+            b'{' => {
+                self.pos += 1;
+
+                loop {
+                    self.skip_whitespace();
+
+                    if self.peek()? == b'}' {
+                        return None;
+                    }
+
+                    let key_start = self.pos;
+                    self.skip_string()?;
+                    let key =
+                        serde_json::from_str::<String>(&self.text[key_start..self.pos]).ok()?;
+
+                    self.skip_separator(b':');
+
+                    if key == *segment {
+                        return self.find(rest);
+                    }
+
+                    self.skip_value()?;
+                    self.skip_separator(b',');
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+
+                let index = segment.parse::<usize>().ok()?;
+                let mut i = 0;
+
+                loop {
+                    self.skip_whitespace();
+
+                    if self.peek()? == b']' {
+                        return None;
+                    }
+
+                    if i == index {
+                        return self.find(rest);
+                    }
+
+                    self.skip_value()?;
+                    self.skip_separator(b',');
+                    i += 1;
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_whitespace();
+
+        match self.peek()? {
+            b'"' => self.skip_string(),
+            b'{' | b'[' => {
+                let mut depth = 0;
+
+                loop {
+                    match self.peek()? {
+                        b'"' => {
+                            self.skip_string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+
+                            if depth == 0 {
+                                self.pos += 1;
+                                return Some(());
+                            }
+                        }
+                        _ => {}
+                    }
+
+                    self.pos += 1;
+                }
+            }
+            _ => {
+                while self
+                    .peek()
+                    .is_some_and(|b| !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace())
+                {
+                    self.pos += 1;
+                }
+
+                Some(())
+            }
+        }
+    }
+
+    fn skip_string(&mut self) -> Option<()> {
+        if self.peek()? != b'"' {
+            return None;
+        }
+
+        self.pos += 1;
+
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+}
@@ -20,14 +20,20 @@ mod client;
 #[rustfmt::skip]
 mod config;
 mod content_type;
+mod credentials;
 mod display_mode;
 mod gsettings;
+mod hex;
+mod json_schema;
 mod main_window;
 mod objects;
 mod pages;
+mod payload_decoder;
+mod protobuf_registry;
 mod subclass;
 mod toast;
 mod widgets;
+mod workspace;
 
 use std::path::PathBuf;
 
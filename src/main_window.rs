@@ -13,12 +13,19 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::cell::RefCell;
+
 use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
 use gtk::prelude::*;
 use gtk::{gio, glib};
 
 use crate::application::MQTTyApplication;
 use crate::config;
+use crate::toast::MQTTyToastBuilder;
+use crate::widgets::{MQTTyPublishView, MQTTySubscriptionsView};
+use crate::workspace::{self, MQTTyWorkspace};
 
 mod imp {
 
@@ -29,6 +36,17 @@ mod imp {
     pub struct MQTTyWindow {
         #[template_child]
         pub toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        window_title: TemplateChild<adw::WindowTitle>,
+        #[template_child]
+        recent_workspaces_menu: TemplateChild<gio::Menu>,
+        #[template_child]
+        publish_view: TemplateChild<MQTTyPublishView>,
+        #[template_child]
+        subscriptions_view: TemplateChild<MQTTySubscriptionsView>,
+
+        /// File of the current workspace, None if it was never saved
+        workspace_file: RefCell<Option<gio::File>>,
     }
 
     #[glib::object_subclass]
@@ -38,6 +56,41 @@ mod imp {
         type ParentType = adw::ApplicationWindow;
 
         fn class_init(klass: &mut Self::Class) {
+            klass.install_action_async("win.open-workspace", None, |this, _, _| async move {
+                let dialog = gtk::FileDialog::builder()
+                    .title(gettext("Open Workspace"))
+                    .filters(&workspace_filters())
+                    .modal(true)
+                    .build();
+
+                if let Ok(file) = dialog.open_future(Some(&this)).await {
+                    this.imp().open_workspace(&file).await;
+                }
+            });
+
+            klass.install_action_async(
+                "win.open-recent-workspace",
+                Some(glib::VariantTy::STRING),
+                |this, _, param| async move {
+                    let Some(uri) = param.and_then(|param| param.get::<String>()) else {
+                        return;
+                    };
+                    this.imp().open_workspace(&gio::File::for_uri(&uri)).await;
+                },
+            );
+
+            klass.install_action_async("win.save-workspace", None, |this, _, _| async move {
+                let file = this.imp().workspace_file.borrow().clone();
+                match file {
+                    Some(file) => this.imp().save_workspace(&file).await,
+                    None => this.imp().save_workspace_as().await,
+                }
+            });
+
+            klass.install_action_async("win.save-workspace-as", None, |this, _, _| async move {
+                this.imp().save_workspace_as().await;
+            });
+
             klass.bind_template();
         }
 
@@ -59,6 +112,34 @@ mod imp {
 
             // Load latest window state
             obj.load_window_size();
+
+            MQTTyApplication::get_singleton()
+                .settings()
+                .connect_changed(
+                    Some("recent-workspaces"),
+                    glib::clone!(
+                        #[weak(rename_to = this)]
+                        self,
+                        move |_, _| this.update_recent_workspaces_menu()
+                    ),
+                );
+
+            self.update_recent_workspaces_menu();
+
+            // The latest workspace is reopened, unless it doesn't exist anymore
+            if let Some(file) = workspace::recent_workspaces().into_iter().next() {
+                glib::spawn_future_local(glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    async move {
+                        if file.query_exists(gio::Cancellable::NONE) {
+                            this.open_workspace(&file).await;
+                        } else {
+                            workspace::remove_recent_workspace(&file);
+                        }
+                    }
+                ));
+            }
         }
     }
 
@@ -75,6 +156,116 @@ mod imp {
 
     impl ApplicationWindowImpl for MQTTyWindow {}
     impl AdwApplicationWindowImpl for MQTTyWindow {}
+
+    impl MQTTyWindow {
+        async fn open_workspace(&self, file: &gio::File) {
+            match MQTTyWorkspace::load(file).await {
+                Ok(workspace) => {
+                    workspace.apply(&self.publish_view, &self.subscriptions_view);
+                    self.set_workspace_file(Some(file));
+                    workspace::add_recent_workspace(file);
+                }
+                Err(e) => self.toast_error(
+                    formatx!(gettext("Couldn't open the workspace: {}"), e.message())
+                        .unwrap_or_default(),
+                ),
+            }
+        }
+
+        async fn save_workspace(&self, file: &gio::File) {
+            let workspace = MQTTyWorkspace::new(&self.publish_view, &self.subscriptions_view);
+
+            match workspace.save(file).await {
+                Ok(()) => {
+                    self.set_workspace_file(Some(file));
+                    workspace::add_recent_workspace(file);
+
+                    self.obj().toast(
+                        &MQTTyToastBuilder::new()
+                            .title(gettext("Workspace saved"))
+                            .timeout(2)
+                            .build(),
+                    );
+                }
+                Err(e) => self.toast_error(
+                    formatx!(gettext("Couldn't save the workspace: {}"), e.message())
+                        .unwrap_or_default(),
+                ),
+            }
+        }
+
+        async fn save_workspace_as(&self) {
+            let obj = self.obj();
+
+            let dialog = gtk::FileDialog::builder()
+                .title(gettext("Save Workspace"))
+                .initial_name("workspace.json")
+                .filters(&workspace_filters())
+                .modal(true)
+                .build();
+
+            if let Ok(file) = dialog.save_future(Some(&*obj)).await {
+                self.save_workspace(&file).await;
+            }
+        }
+
+        fn set_workspace_file(&self, file: Option<&gio::File>) {
+            self.workspace_file.replace(file.cloned());
+
+            self.window_title.set_subtitle(
+                &file
+                    .and_then(|file| file.basename())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            );
+        }
+
+        fn update_recent_workspaces_menu(&self) {
+            let menu = &self.recent_workspaces_menu;
+
+            menu.remove_all();
+
+            for file in workspace::recent_workspaces() {
+                let label = file
+                    .basename()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| file.uri().to_string());
+
+                let item = gio::MenuItem::new(Some(&label), None);
+                item.set_action_and_target_value(
+                    Some("win.open-recent-workspace"),
+                    Some(&file.uri().to_variant()),
+                );
+
+                menu.append_item(&item);
+            }
+        }
+
+        fn toast_error(&self, title: String) {
+            self.obj().toast(
+                &MQTTyToastBuilder::new()
+                    .title(title)
+                    .icon(
+                        gtk::Image::builder()
+                            .icon_name("dialog-error-symbolic")
+                            .build()
+                            .as_ref(),
+                    )
+                    .timeout(5)
+                    .build(),
+            );
+        }
+    }
+}
+
+fn workspace_filters() -> gio::ListStore {
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some(&gettext("Workspaces")));
+    filter.add_suffix("json");
+
+    let filters = gio::ListStore::new::<gtk::FileFilter>();
+    filters.append(&filter);
+    filters
 }
 
 glib::wrapper! {
@@ -14,5 +14,11 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 mod key_value;
+mod subscription;
+mod tls_settings;
+mod will_settings;
 
 pub use key_value::MQTTyKeyValue;
+pub use subscription::MQTTySubscription;
+pub use tls_settings::{MQTTyTlsSettings, MQTTyTlsVersion};
+pub use will_settings::MQTTyWillSettings;
@@ -19,6 +19,7 @@ use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::glib;
 use gtk::glib::subclass::Signal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::widgets::MQTTyKeyValueRow;
 
@@ -78,7 +79,7 @@ glib::wrapper! {
     /// This type is used for data serialization and deserialization for MQTTyKeyValueRow, but it doesn't
     /// handles presentation
     ///
-    /// TODO: add serde functions
+    /// It's represented in JSON as `{ "key": "...", "value": "...", "active": true }`
     pub struct MQTTyKeyValue(ObjectSubclass<imp::MQTTyKeyValue>);
 }
 
@@ -89,7 +90,7 @@ impl Default for MQTTyKeyValue {
 }
 
 impl MQTTyKeyValue {
-    fn new(key: &str, value: &str, active: bool) -> Self {
+    pub fn new(key: &str, value: &str, active: bool) -> Self {
         glib::Object::builder()
             .property("key", key)
             .property("value", value)
@@ -103,3 +104,29 @@ impl From<MQTTyKeyValueRow> for MQTTyKeyValue {
         Self::new(&value.key(), &value.value(), value.active())
     }
 }
+
+#[derive(Serialize, Deserialize)]
+struct KeyValueRepr {
+    key: String,
+    value: String,
+    active: bool,
+}
+
+impl Serialize for MQTTyKeyValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        KeyValueRepr {
+            key: self.key(),
+            value: self.value(),
+            active: self.active(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MQTTyKeyValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = KeyValueRepr::deserialize(deserializer)?;
+
+        Ok(Self::new(&repr.key, &repr.value, repr.active))
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::client::MQTTySubscribeOptions;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::MQTTySubscription)]
+    pub struct MQTTySubscription {
+        #[property(get, construct_only)]
+        topic_filter: RefCell<String>,
+
+        #[property(get, construct_only)]
+        options: RefCell<MQTTySubscribeOptions>,
+
+        /// Result of the latest subscribe request, empty while it hasn't been sent
+        #[property(get, set)]
+        status: RefCell<String>,
+
+        /// Whether the broker accepted the latest subscribe request
+        #[property(get, set)]
+        active: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTySubscription {
+        const NAME: &'static str = "MQTTySubscription";
+
+        type Type = super::MQTTySubscription;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTySubscription {}
+}
+
+glib::wrapper! {
+    /// Topic filter subscribed by the subscriptions view, along with its options
+    pub struct MQTTySubscription(ObjectSubclass<imp::MQTTySubscription>);
+}
+
+impl MQTTySubscription {
+    pub fn new(topic_filter: &str, options: &MQTTySubscribeOptions) -> Self {
+        glib::Object::builder()
+            .property("topic_filter", topic_filter)
+            .property("options", options)
+            .build()
+    }
+}
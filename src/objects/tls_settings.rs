@@ -0,0 +1,175 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::pgettext;
+use gtk::glib;
+
+#[derive(Default, Clone, Copy, glib::Enum, PartialEq, Debug)]
+#[enum_type(name = "MQTTyTlsVersion")]
+pub enum MQTTyTlsVersion {
+    /// Let the TLS library pick the version
+    #[default]
+    Default,
+    Tls10,
+    Tls11,
+    Tls12,
+}
+
+impl MQTTyTlsVersion {
+    pub fn listed() -> &'static [MQTTyTlsVersion] {
+        &[
+            MQTTyTlsVersion::Default,
+            MQTTyTlsVersion::Tls10,
+            MQTTyTlsVersion::Tls11,
+            MQTTyTlsVersion::Tls12,
+        ]
+    }
+
+    pub fn translated(&self) -> String {
+        match self {
+            MQTTyTlsVersion::Default => pgettext("tls version", "Default"),
+            MQTTyTlsVersion::Tls10 => pgettext("tls version", "TLS 1.0"),
+            MQTTyTlsVersion::Tls11 => pgettext("tls version", "TLS 1.1"),
+            MQTTyTlsVersion::Tls12 => pgettext("tls version", "TLS 1.2"),
+        }
+    }
+}
+
+mod imp {
+
+    use super::*;
+
+    #[derive(glib::Properties)]
+    #[properties(wrapper_type = super::MQTTyTlsSettings)]
+    pub struct MQTTyTlsSettings {
+        /// PEM file with the certificate authorities used to verify the broker
+        /// certificate, if empty, the system ones are used
+        #[property(get, set)]
+        ca_file: RefCell<String>,
+
+        /// PEM file with the client certificate, used for mutual TLS, it can also
+        /// contain the private key
+        #[property(get, set)]
+        client_cert_file: RefCell<String>,
+
+        /// PEM file with the client private key, only needed if it's not included in
+        /// the client_cert_file
+        #[property(get, set)]
+        client_key_file: RefCell<String>,
+
+        /// Passphrase for encrypted private keys
+        #[property(get, set)]
+        client_key_passphrase: RefCell<String>,
+
+        /// Disable this for brokers with self-signed certificates that are not
+        /// included in the ca_file
+        #[property(get, set)]
+        verify_server_cert: Cell<bool>,
+
+        #[property(get, set)]
+        verify_hostname: Cell<bool>,
+
+        #[property(get, set, builder(Default::default()))]
+        min_tls_version: Cell<MQTTyTlsVersion>,
+
+        /// OpenSSL cipher list string, e.g. "HIGH:!aNULL", if empty, the TLS library
+        /// defaults are used
+        #[property(get, set)]
+        cipher_list: RefCell<String>,
+
+        /// Comma separated list of ALPN protocols, e.g. "mqtt,x-amzn-mqtt-ca"
+        #[property(get, set)]
+        alpn_protocols: RefCell<String>,
+    }
+
+    impl Default for MQTTyTlsSettings {
+        fn default() -> Self {
+            Self {
+                ca_file: Default::default(),
+                client_cert_file: Default::default(),
+                client_key_file: Default::default(),
+                client_key_passphrase: Default::default(),
+                verify_server_cert: Cell::new(true),
+                verify_hostname: Cell::new(true),
+                min_tls_version: Default::default(),
+                cipher_list: Default::default(),
+                alpn_protocols: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyTlsSettings {
+        const NAME: &'static str = "MQTTyTlsSettings";
+
+        type Type = super::MQTTyTlsSettings;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyTlsSettings {}
+}
+
+glib::wrapper! {
+    /// Model for the TLS options of a broker connection, it's only used by the client when
+    /// the URL scheme is a secure one (ssl://, mqtts://)
+    pub struct MQTTyTlsSettings(ObjectSubclass<imp::MQTTyTlsSettings>);
+}
+
+impl Default for MQTTyTlsSettings {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl MQTTyTlsSettings {
+    /// Returns a new object with the same values, useful for taking a snapshot of
+    /// settings that are being edited by the user
+    pub fn duplicate(&self) -> Self {
+        let settings = Self::default();
+
+        for pspec in self.list_properties() {
+            settings.set_property_from_value(pspec.name(), &self.property_value(pspec.name()));
+        }
+
+        settings
+    }
+
+    pub fn is_equivalent(&self, other: &MQTTyTlsSettings) -> bool {
+        self.ca_file() == other.ca_file()
+            && self.client_cert_file() == other.client_cert_file()
+            && self.client_key_file() == other.client_key_file()
+            && self.client_key_passphrase() == other.client_key_passphrase()
+            && self.verify_server_cert() == other.verify_server_cert()
+            && self.verify_hostname() == other.verify_hostname()
+            && self.min_tls_version() == other.min_tls_version()
+            && self.cipher_list() == other.cipher_list()
+            && self.alpn_protocols() == other.alpn_protocols()
+    }
+
+    pub fn alpn_protocols_list(&self) -> Vec<String> {
+        self.alpn_protocols()
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .collect()
+    }
+}
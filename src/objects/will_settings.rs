@@ -0,0 +1,130 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::client::MQTTyClientQos;
+use crate::content_type::MQTTyContentType;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::MQTTyWillSettings)]
+    pub struct MQTTyWillSettings {
+        /// If disabled, no will message is sent to the broker when connecting
+        #[property(get, set)]
+        enabled: Cell<bool>,
+
+        #[property(get, set)]
+        topic: RefCell<String>,
+
+        #[property(get, set)]
+        body: RefCell<String>,
+
+        #[property(get, set, builder(Default::default()))]
+        content_type: Cell<MQTTyContentType>,
+
+        #[property(get, set, builder(Default::default()))]
+        qos: Cell<MQTTyClientQos>,
+
+        #[property(get, set)]
+        retain: Cell<bool>,
+
+        /// Seconds the broker waits before publishing the will message, only MQTT v5
+        #[property(get, set)]
+        delay_interval: Cell<u32>,
+
+        /// Lifetime in seconds of the will message once published, 0 means no expiry,
+        /// only MQTT v5
+        #[property(get, set)]
+        message_expiry_interval: Cell<u32>,
+
+        /// Only MQTT v5
+        pub user_properties: RefCell<Vec<(String, String)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyWillSettings {
+        const NAME: &'static str = "MQTTyWillSettings";
+
+        type Type = super::MQTTyWillSettings;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyWillSettings {}
+}
+
+glib::wrapper! {
+    /// Model for the Last Will and Testament of a broker connection, the broker publishes
+    /// this message when the client disconnects ungracefully
+    pub struct MQTTyWillSettings(ObjectSubclass<imp::MQTTyWillSettings>);
+}
+
+impl Default for MQTTyWillSettings {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl MQTTyWillSettings {
+    /// Returns a new object with the same values, useful for taking a snapshot of
+    /// settings that are being edited by the user
+    pub fn duplicate(&self) -> Self {
+        let settings = Self::default();
+
+        for pspec in self.list_properties() {
+            settings.set_property_from_value(pspec.name(), &self.property_value(pspec.name()));
+        }
+
+        settings.set_user_properties(&self.user_properties());
+
+        settings
+    }
+
+    pub fn is_equivalent(&self, other: &MQTTyWillSettings) -> bool {
+        // Disabled wills are equivalent no matter what the rest of the fields are
+        if !self.enabled() && !other.enabled() {
+            return true;
+        }
+
+        self.enabled() == other.enabled()
+            && self.topic() == other.topic()
+            && self.body() == other.body()
+            && self.content_type() == other.content_type()
+            && self.qos() == other.qos()
+            && self.retain() == other.retain()
+            && self.delay_interval() == other.delay_interval()
+            && self.message_expiry_interval() == other.message_expiry_interval()
+            && self.user_properties() == other.user_properties()
+    }
+
+    pub fn user_properties(&self) -> Vec<(String, String)> {
+        self.imp().user_properties.borrow().clone()
+    }
+
+    pub fn set_user_properties(&self, user_properties: &[(String, String)]) {
+        let mut v = self.imp().user_properties.borrow_mut();
+        v.clear();
+        v.extend_from_slice(user_properties);
+    }
+}
@@ -64,13 +64,25 @@ mod imp {
     impl MQTTyAddConnPage {
         #[template_callback]
         fn on_save_conn(&self) {
-            let app = MQTTyApplication::get_singleton();
-
             let obj = self.obj();
 
-            app.settings_set_n_connection(-1, obj.conn_model());
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    let app = MQTTyApplication::get_singleton();
+
+                    let conn_model = obj.conn_model();
+
+                    if let Err(e) = conn_model.store_credentials().await {
+                        tracing::error!("Could not store the connection password: {}", e);
+                    }
+
+                    app.settings_set_n_connection(-1, conn_model);
 
-            obj.activate_action("navigation.pop", None).unwrap();
+                    obj.activate_action("navigation.pop", None).unwrap();
+                }
+            ));
         }
     }
 }
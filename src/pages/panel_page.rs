@@ -32,7 +32,7 @@ mod imp {
     #[template(resource = "/io/github/otaxhu/MQTTy/ui/pages/panel_page.ui")]
     #[properties(wrapper_type = super::MQTTyPanelPage)]
     pub struct MQTTyPanelPage {
-        /// N-connection in GSettings "connections" key, used for retrieving the connection
+        /// N-connection in GSettings "saved-connections" key, used for retrieving the connection
         /// data, it's unsigned integer because we expect to retrieve always valid data
         #[property(get, set, construct)]
         nth_conn: Cell<u32>,
@@ -74,7 +74,7 @@ mod imp {
             let conn_model = app.settings_n_connection(obj.nth_conn()).unwrap();
 
             obj.upcast_ref::<adw::NavigationPage>()
-                .set_title(&conn_model.topic());
+                .set_title(&conn_model.display_name());
 
             self.view_stack
                 .connect_visible_child_name_notify(glib::clone!(
@@ -88,6 +88,16 @@ mod imp {
                     }
                 ));
 
+            glib::spawn_future_local(glib::clone!(
+                #[strong]
+                conn_model,
+                async move {
+                    if let Err(e) = conn_model.load_credentials().await {
+                        tracing::error!("Could not retrieve the connection password: {}", e);
+                    }
+                }
+            ));
+
             obj.set_conn_model(conn_model);
         }
     }
@@ -99,16 +109,26 @@ mod imp {
     impl MQTTyPanelPage {
         #[template_callback]
         fn on_save_conn(&self) {
-            let app = MQTTyApplication::get_singleton();
-
             let obj = self.obj();
 
-            let conn_model = obj.conn_model();
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    let app = MQTTyApplication::get_singleton();
 
-            app.settings_set_n_connection(obj.nth_conn().into(), conn_model.clone());
+                    let conn_model = obj.conn_model();
 
-            obj.upcast_ref::<adw::NavigationPage>()
-                .set_title(&conn_model.topic());
+                    if let Err(e) = conn_model.store_credentials().await {
+                        tracing::error!("Could not store the connection password: {}", e);
+                    }
+
+                    app.settings_set_n_connection(obj.nth_conn().into(), conn_model.clone());
+
+                    obj.upcast_ref::<adw::NavigationPage>()
+                        .set_title(&conn_model.display_name());
+                }
+            ));
         }
 
         #[template_callback]
@@ -119,6 +139,13 @@ mod imp {
 
             app.settings_delete_n_connection(obj.nth_conn());
 
+            let conn_model = obj.conn_model();
+            glib::spawn_future_local(async move {
+                if let Err(e) = conn_model.delete_credentials().await {
+                    tracing::error!("Could not delete the connection password: {}", e);
+                }
+            });
+
             obj.activate_action("navigation.pop", None).unwrap();
         }
     }
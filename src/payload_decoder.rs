@@ -0,0 +1,562 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+use std::io::Read;
+use std::rc::Rc;
+use std::sync::LazyLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use base64::prelude::*;
+use gettextrs::pgettext;
+use gtk::glib;
+use gtk::glib::subclass::Signal;
+
+use crate::application::MQTTyApplication;
+use crate::client::{topic_matches_filter, MQTTyClientMessage};
+use crate::content_type::MQTTyContentType;
+use crate::protobuf_registry::MQTTyProtobufRegistry;
+
+/// Maximum size of a decompressed payload, bigger payloads are shown as they are
+const DECOMPRESSED_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// Maximum number of nested encodings, e.g. base64 of gzip is 2
+const ENCODINGS_LIMIT: usize = 4;
+
+/// Base64 text shorter than this is not decoded, since short words are often valid
+/// base64 by chance
+const BASE64_MIN_LEN: usize = 16;
+
+/// How a payload is shown in the message detail
+#[derive(Clone, Copy, glib::Enum, PartialEq, Debug)]
+#[enum_type(name = "MQTTyPayloadFormat")]
+pub enum MQTTyPayloadFormat {
+    Json,
+    Xml,
+    Yaml,
+    Text,
+    /// Shown as JSON
+    Cbor,
+    /// Shown as JSON
+    MessagePack,
+    /// Shown as JSON, only used for topics mapped in [`MQTTyProtobufRegistry`]
+    Protobuf,
+    Image,
+    Binary,
+}
+
+impl MQTTyPayloadFormat {
+    /// Formats that can be chosen for a topic filter
+    pub fn listed() -> &'static [MQTTyPayloadFormat] {
+        &[
+            MQTTyPayloadFormat::Json,
+            MQTTyPayloadFormat::Xml,
+            MQTTyPayloadFormat::Yaml,
+            MQTTyPayloadFormat::Text,
+            MQTTyPayloadFormat::Cbor,
+            MQTTyPayloadFormat::MessagePack,
+            MQTTyPayloadFormat::Image,
+            MQTTyPayloadFormat::Binary,
+        ]
+    }
+
+    pub fn translated(&self) -> String {
+        match self {
+            MQTTyPayloadFormat::Json => pgettext("payload format", "JSON"),
+            MQTTyPayloadFormat::Xml => pgettext("payload format", "XML"),
+            MQTTyPayloadFormat::Yaml => pgettext("payload format", "YAML"),
+            MQTTyPayloadFormat::Text => pgettext("payload format", "Plain Text"),
+            MQTTyPayloadFormat::Cbor => pgettext("payload format", "CBOR"),
+            MQTTyPayloadFormat::MessagePack => pgettext("payload format", "MessagePack"),
+            MQTTyPayloadFormat::Protobuf => pgettext("payload format", "Protobuf"),
+            MQTTyPayloadFormat::Image => pgettext("payload format", "Image"),
+            MQTTyPayloadFormat::Binary => pgettext("payload format", "Binary"),
+        }
+    }
+
+    /// Name used in the "payload-formats" setting
+    fn nick(&self) -> &'static str {
+        match self {
+            MQTTyPayloadFormat::Json => "json",
+            MQTTyPayloadFormat::Xml => "xml",
+            MQTTyPayloadFormat::Yaml => "yaml",
+            MQTTyPayloadFormat::Text => "text",
+            MQTTyPayloadFormat::Cbor => "cbor",
+            MQTTyPayloadFormat::MessagePack => "messagepack",
+            MQTTyPayloadFormat::Protobuf => "protobuf",
+            MQTTyPayloadFormat::Image => "image",
+            MQTTyPayloadFormat::Binary => "binary",
+        }
+    }
+
+    fn from_nick(nick: &str) -> Option<Self> {
+        Self::listed()
+            .iter()
+            .chain([&MQTTyPayloadFormat::Protobuf])
+            .find(|format| format.nick() == nick)
+            .copied()
+    }
+
+    /// Sourceview language used for highlighting the text of this format
+    pub fn language_id(&self) -> Option<&'static str> {
+        match self {
+            MQTTyPayloadFormat::Json
+            | MQTTyPayloadFormat::Cbor
+            | MQTTyPayloadFormat::MessagePack
+            | MQTTyPayloadFormat::Protobuf => Some("json"),
+            MQTTyPayloadFormat::Xml => Some("xml"),
+            MQTTyPayloadFormat::Yaml => Some("yaml"),
+            MQTTyPayloadFormat::Text | MQTTyPayloadFormat::Image | MQTTyPayloadFormat::Binary => {
+                None
+            }
+        }
+    }
+}
+
+impl From<MQTTyContentType> for MQTTyPayloadFormat {
+    fn from(content_type: MQTTyContentType) -> Self {
+        match content_type {
+            MQTTyContentType::Json => MQTTyPayloadFormat::Json,
+            MQTTyContentType::Xml => MQTTyPayloadFormat::Xml,
+            MQTTyContentType::Yaml => MQTTyPayloadFormat::Yaml,
+            MQTTyContentType::None | MQTTyContentType::Text => MQTTyPayloadFormat::Text,
+            MQTTyContentType::Cbor => MQTTyPayloadFormat::Cbor,
+            MQTTyContentType::MessagePack => MQTTyPayloadFormat::MessagePack,
+            MQTTyContentType::Protobuf => MQTTyPayloadFormat::Protobuf,
+            MQTTyContentType::Raw => MQTTyPayloadFormat::Binary,
+        }
+    }
+}
+
+/// Layer wrapping the actual payload, removed before detecting its format
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MQTTyPayloadEncoding {
+    Gzip,
+    Zstd,
+    Base64,
+}
+
+impl MQTTyPayloadEncoding {
+    pub fn translated(&self) -> String {
+        match self {
+            MQTTyPayloadEncoding::Gzip => pgettext("payload encoding", "gzip"),
+            MQTTyPayloadEncoding::Zstd => pgettext("payload encoding", "zstd"),
+            MQTTyPayloadEncoding::Base64 => pgettext("payload encoding", "base64"),
+        }
+    }
+}
+
+/// Result of [`MQTTyPayloadDecoder::decode()`]
+pub struct MQTTyDecodedPayload {
+    pub format: MQTTyPayloadFormat,
+
+    /// Encodings that were removed, outermost first
+    pub encodings: Vec<MQTTyPayloadEncoding>,
+
+    /// Payload without the encodings
+    pub data: Vec<u8>,
+
+    /// Pretty printed text, None for images and binary payloads
+    pub text: Option<String>,
+}
+
+impl MQTTyDecodedPayload {
+    /// Format followed by the encodings, e.g. "JSON (gzip, base64)"
+    pub fn description(&self) -> String {
+        if self.encodings.is_empty() {
+            return self.format.translated();
+        }
+
+        format!(
+            "{} ({})",
+            self.format.translated(),
+            self.encodings
+                .iter()
+                .map(MQTTyPayloadEncoding::translated)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default)]
+    pub struct MQTTyPayloadDecoder {
+        /// Pairs of topic filter and format that override the detected format, saved in
+        /// the "payload-formats" setting
+        pub formats: RefCell<Vec<(String, MQTTyPayloadFormat)>>,
+
+        /// Bumped every time the result of decoding a payload may change, so that the
+        /// decoded bodies cached on messages are discarded
+        pub generation: Cell<u64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyPayloadDecoder {
+        const NAME: &'static str = "MQTTyPayloadDecoder";
+
+        type Type = super::MQTTyPayloadDecoder;
+
+        type ParentType = glib::Object;
+    }
+
+    impl ObjectImpl for MQTTyPayloadDecoder {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: LazyLock<Vec<Signal>> =
+                LazyLock::new(|| vec![Signal::builder("formats-changed").build()]);
+            &*SIGNALS
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Detects the format of received payloads, removing compression and base64 layers
+    /// first. The format is chosen in this order: the topic filters set by the user, the
+    /// topics mapped to protobuf message types, the content type of MQTT v5 messages,
+    /// and lastly sniffing the payload
+    pub struct MQTTyPayloadDecoder(ObjectSubclass<imp::MQTTyPayloadDecoder>);
+}
+
+thread_local! {
+    static DECODER: MQTTyPayloadDecoder = MQTTyPayloadDecoder::load();
+}
+
+impl MQTTyPayloadDecoder {
+    pub fn get_singleton() -> Self {
+        DECODER.with(Clone::clone)
+    }
+
+    fn load() -> Self {
+        let decoder = glib::Object::new::<Self>();
+
+        let formats = MQTTyApplication::get_singleton()
+            .settings()
+            .get::<Vec<(String, String)>>("payload-formats")
+            .into_iter()
+            .filter_map(|(topic_filter, nick)| {
+                Some((topic_filter, MQTTyPayloadFormat::from_nick(&nick)?))
+            })
+            .collect();
+
+        decoder.imp().formats.replace(formats);
+
+        // Protobuf payloads are decoded with the message types mapped to their topics
+        MQTTyProtobufRegistry::get_singleton().connect_closure(
+            "topic-types-changed",
+            false,
+            glib::closure_local!(
+                #[weak]
+                decoder,
+                move |_: MQTTyProtobufRegistry| decoder.bump_generation()
+            ),
+        );
+
+        decoder
+    }
+
+    /// Generation of the settings that affect decoding, see [`Self::decode_message()`]
+    pub fn generation(&self) -> u64 {
+        self.imp().generation.get()
+    }
+
+    fn bump_generation(&self) {
+        let generation = &self.imp().generation;
+        generation.set(generation.get() + 1);
+    }
+
+    /// Body of `message` decoded, the result is cached on the message until its body
+    /// changes or the decoding settings change
+    pub fn decode_message(&self, message: &MQTTyClientMessage) -> Rc<MQTTyDecodedPayload> {
+        let generation = self.generation();
+
+        if let Some(decoded) = message.cached_decoded_body(generation) {
+            return decoded;
+        }
+
+        let decoded = Rc::new(self.decode(
+            &message.topic(),
+            message.content_type().as_deref(),
+            &message.body(),
+        ));
+
+        message.cache_decoded_body(generation, decoded.clone());
+
+        decoded
+    }
+
+    /// Body of `message` as text, see [`Self::decode_message()`]. Bodies that are not
+    /// text are shown as lossy UTF-8
+    pub fn message_text(&self, message: &MQTTyClientMessage) -> String {
+        let decoded = self.decode_message(message);
+
+        match &decoded.text {
+            Some(text) => text.clone(),
+            None => String::from_utf8_lossy(&decoded.data).into_owned(),
+        }
+    }
+
+    pub fn decode(
+        &self,
+        topic: &str,
+        mime_type: Option<&str>,
+        payload: &[u8],
+    ) -> MQTTyDecodedPayload {
+        let (encodings, data) = remove_encodings(payload);
+
+        let chosen = self
+            .topic_format(topic)
+            .or_else(|| {
+                MQTTyProtobufRegistry::get_singleton()
+                    .topic_message_type(topic)
+                    .map(|_| MQTTyPayloadFormat::Protobuf)
+            })
+            .or_else(|| {
+                mime_type
+                    .and_then(MQTTyContentType::from_mime_type)
+                    .map(MQTTyPayloadFormat::from)
+            });
+
+        // A declared format that doesn't match the payload is ignored, except when
+        // chosen by the user
+        let (format, text) = match chosen {
+            Some(format) => match format_text(format, topic, &data) {
+                Some(text) => (format, text),
+                None if self.topic_format(topic).is_some() => {
+                    (format, Some(String::from_utf8_lossy(&data).into_owned()))
+                }
+                None => sniff(&data),
+            },
+            None => sniff(&data),
+        };
+
+        MQTTyDecodedPayload {
+            format,
+            encodings,
+            data,
+            text,
+        }
+    }
+
+    pub fn formats(&self) -> Vec<(String, MQTTyPayloadFormat)> {
+        self.imp().formats.borrow().clone()
+    }
+
+    /// Format of the first topic filter that matches `topic`
+    pub fn topic_format(&self, topic: &str) -> Option<MQTTyPayloadFormat> {
+        self.imp()
+            .formats
+            .borrow()
+            .iter()
+            .find(|(topic_filter, _)| topic_matches_filter(topic_filter, topic))
+            .map(|(_, format)| *format)
+    }
+
+    /// Maps `topic_filter` to `format`, replacing the previous mapping of the same topic
+    /// filter
+    pub fn set_topic_format(&self, topic_filter: &str, format: MQTTyPayloadFormat) {
+        {
+            let mut formats = self.imp().formats.borrow_mut();
+            formats.retain(|(f, _)| f != topic_filter);
+            formats.push((topic_filter.to_string(), format));
+        }
+
+        self.save_formats();
+    }
+
+    pub fn remove_topic_format(&self, topic_filter: &str) {
+        self.imp()
+            .formats
+            .borrow_mut()
+            .retain(|(f, _)| f != topic_filter);
+
+        self.save_formats();
+    }
+
+    fn save_formats(&self) {
+        let formats = self
+            .formats()
+            .into_iter()
+            .map(|(topic_filter, format)| (topic_filter, format.nick().to_string()))
+            .collect::<Vec<_>>();
+
+        if let Err(e) = MQTTyApplication::get_singleton()
+            .settings()
+            .set("payload-formats", formats)
+        {
+            tracing::error!("Could not save the payload formats: {}", e);
+        }
+
+        self.bump_generation();
+        self.emit_by_name::<()>("formats-changed", &[]);
+    }
+}
+
+/// Removes the compression and base64 layers of `payload`, returns the removed
+/// encodings, outermost first, and the data inside them
+fn remove_encodings(payload: &[u8]) -> (Vec<MQTTyPayloadEncoding>, Vec<u8>) {
+    let mut encodings = vec![];
+    let mut data = payload.to_vec();
+
+    while encodings.len() < ENCODINGS_LIMIT {
+        let Some((encoding, inner)) = remove_encoding(&data) else {
+            break;
+        };
+
+        encodings.push(encoding);
+        data = inner;
+    }
+
+    (encodings, data)
+}
+
+fn remove_encoding(data: &[u8]) -> Option<(MQTTyPayloadEncoding, Vec<u8>)> {
+    if data.starts_with(&[0x1f, 0x8b]) {
+        return decompress(flate2::read::GzDecoder::new(data))
+            .map(|inner| (MQTTyPayloadEncoding::Gzip, inner));
+    }
+
+    if data.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        return ruzstd::decoding::StreamingDecoder::new(data)
+            .ok()
+            .and_then(decompress)
+            .map(|inner| (MQTTyPayloadEncoding::Zstd, inner));
+    }
+
+    let text = std::str::from_utf8(data).ok()?.trim();
+
+    if text.len() < BASE64_MIN_LEN || text.starts_with(['{', '[', '<']) {
+        return None;
+    }
+
+    let inner = BASE64_STANDARD
+        .decode(text)
+        .or_else(|_| BASE64_URL_SAFE.decode(text))
+        .ok()?;
+
+    // Text that happens to be valid base64 decodes to garbage, so the result is only
+    // accepted if it's something recognizable
+    let recognizable = remove_encoding(&inner).is_some()
+        || matches!(
+            sniff(&inner).0,
+            MQTTyPayloadFormat::Json | MQTTyPayloadFormat::Xml | MQTTyPayloadFormat::Image
+        );
+
+    recognizable.then_some((MQTTyPayloadEncoding::Base64, inner))
+}
+
+fn decompress(reader: impl Read) -> Option<Vec<u8>> {
+    let mut inner = vec![];
+
+    reader
+        .take(DECOMPRESSED_LIMIT + 1)
+        .read_to_end(&mut inner)
+        .ok()?;
+
+    (inner.len() as u64 <= DECOMPRESSED_LIMIT).then_some(inner)
+}
+
+/// Detects the format of `data` by its contents, binary serialization formats are only
+/// detected if the whole payload is a single map or array
+fn sniff(data: &[u8]) -> (MQTTyPayloadFormat, Option<String>) {
+    if is_image(data) {
+        return (MQTTyPayloadFormat::Image, None);
+    }
+
+    if let Ok(text) = std::str::from_utf8(data) {
+        let trimmed = text.trim_start();
+
+        if trimmed.starts_with(['{', '[']) {
+            if let Some(pretty) = pretty_json(text) {
+                return (MQTTyPayloadFormat::Json, Some(pretty));
+            }
+        }
+
+        if trimmed.starts_with('<') && text.trim_end().ends_with('>') {
+            return (MQTTyPayloadFormat::Xml, Some(text.to_string()));
+        }
+
+        return (MQTTyPayloadFormat::Text, Some(text.to_string()));
+    }
+
+    for format in [MQTTyPayloadFormat::Cbor, MQTTyPayloadFormat::MessagePack] {
+        if let Some(value) = binary_value(format, data) {
+            if value.is_object() || value.is_array() {
+                return (format, serde_json::to_string_pretty(&value).ok());
+            }
+        }
+    }
+
+    (MQTTyPayloadFormat::Binary, None)
+}
+
+/// Text of `data` shown as `format`, the outer None means that the data is not valid
+/// for the format
+fn format_text(format: MQTTyPayloadFormat, topic: &str, data: &[u8]) -> Option<Option<String>> {
+    match format {
+        MQTTyPayloadFormat::Json => pretty_json(std::str::from_utf8(data).ok()?).map(Some),
+        MQTTyPayloadFormat::Xml | MQTTyPayloadFormat::Yaml | MQTTyPayloadFormat::Text => {
+            String::from_utf8(data.to_vec()).ok().map(Some)
+        }
+        MQTTyPayloadFormat::Cbor | MQTTyPayloadFormat::MessagePack => binary_value(format, data)
+            .and_then(|value| serde_json::to_string_pretty(&value).ok())
+            .map(Some),
+        MQTTyPayloadFormat::Protobuf => {
+            let registry = MQTTyProtobufRegistry::get_singleton();
+
+            registry
+                .topic_message_type(topic)
+                .and_then(|message_type| registry.decode(&message_type, data))
+                .map(Some)
+        }
+        MQTTyPayloadFormat::Image => is_image(data).then_some(None),
+        MQTTyPayloadFormat::Binary => Some(None),
+    }
+}
+
+/// Decodes CBOR or MessagePack, trailing bytes are not allowed
+fn binary_value(format: MQTTyPayloadFormat, mut data: &[u8]) -> Option<serde_json::Value> {
+    let value = match format {
+        MQTTyPayloadFormat::Cbor => ciborium::from_reader(&mut data).ok()?,
+        MQTTyPayloadFormat::MessagePack => {
+            let mut deserializer = rmp_serde::Deserializer::new(&mut data);
+            serde::Deserialize::deserialize(&mut deserializer).ok()?
+        }
+        _ => return None,
+    };
+
+    data.is_empty().then_some(value)
+}
+
+fn pretty_json(text: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+}
+
+/// Returns true if `data` starts with the signature of an image format supported by
+/// GdkTexture
+fn is_image(data: &[u8]) -> bool {
+    data.starts_with(b"\x89PNG\r\n\x1a\n")
+        || data.starts_with(&[0xff, 0xd8, 0xff])
+        || data.starts_with(b"GIF87a")
+        || data.starts_with(b"GIF89a")
+        || (data.len() > 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP")
+        || data.starts_with(b"II*\0")
+        || data.starts_with(b"MM\0*")
+}
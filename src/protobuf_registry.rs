@@ -0,0 +1,307 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
+use gtk::glib::subclass::Signal;
+use gtk::{gio, glib};
+use prost_reflect::prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, SerializeOptions};
+
+use crate::application::MQTTyApplication;
+use crate::client::topic_matches_filter;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::MQTTyProtobufRegistry)]
+    pub struct MQTTyProtobufRegistry {
+        pub pool: RefCell<DescriptorPool>,
+
+        /// Full names of the registered message types, sorted alphabetically
+        #[property(get)]
+        message_types: gtk::StringList,
+
+        /// Pairs of topic filter and message type used for decoding received messages,
+        /// saved in the "protobuf-topic-types" setting
+        pub topic_types: RefCell<Vec<(String, String)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyProtobufRegistry {
+        const NAME: &'static str = "MQTTyProtobufRegistry";
+
+        type Type = super::MQTTyProtobufRegistry;
+
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyProtobufRegistry {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: LazyLock<Vec<Signal>> =
+                LazyLock::new(|| vec![Signal::builder("topic-types-changed").build()]);
+            &*SIGNALS
+        }
+    }
+
+    impl MQTTyProtobufRegistry {
+        /// Adds the descriptors to the pool, the pool is left untouched if any of them
+        /// is invalid
+        pub fn add_descriptor_set(&self, bytes: &[u8]) -> Result<(), String> {
+            let mut pool = self.pool.borrow().clone();
+
+            pool.decode_file_descriptor_set(bytes)
+                .map_err(|e| e.to_string())?;
+
+            let mut message_types = pool
+                .all_messages()
+                .map(|message| message.full_name().to_string())
+                .collect::<Vec<_>>();
+            message_types.sort();
+
+            self.pool.replace(pool);
+
+            let n_items = self.message_types.n_items();
+            self.message_types.splice(
+                0,
+                n_items,
+                &message_types.iter().map(String::as_str).collect::<Vec<_>>(),
+            );
+
+            Ok(())
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Protobuf message types loaded from `.proto` files or compiled `FileDescriptorSet`s,
+    /// the descriptor sets are copied to the user data directory so that they are
+    /// available after restarting the application
+    pub struct MQTTyProtobufRegistry(ObjectSubclass<imp::MQTTyProtobufRegistry>);
+}
+
+thread_local! {
+    static REGISTRY: MQTTyProtobufRegistry = MQTTyProtobufRegistry::load();
+}
+
+impl MQTTyProtobufRegistry {
+    pub fn get_singleton() -> Self {
+        REGISTRY.with(Clone::clone)
+    }
+
+    fn load() -> Self {
+        let registry = glib::Object::new::<Self>();
+        let private = registry.imp();
+
+        if let Ok(entries) = std::fs::read_dir(descriptors_dir()) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+
+                let result = std::fs::read(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|bytes| private.add_descriptor_set(&bytes));
+
+                if let Err(e) = result {
+                    tracing::error!("Could not load descriptors {}: {}", path.display(), e);
+                }
+            }
+        }
+
+        private
+            .topic_types
+            .replace(
+                MQTTyApplication::get_singleton()
+                    .settings()
+                    .get::<Vec<(String, String)>>("protobuf-topic-types"),
+            );
+
+        registry
+    }
+
+    /// Registers the message types of a `.proto` file, which is compiled with `protoc`,
+    /// or of a compiled `FileDescriptorSet`
+    pub async fn register_file(&self, file: &gio::File) -> Result<(), glib::Error> {
+        let bytes = match file.path() {
+            Some(path) if path.extension().is_some_and(|ext| ext == "proto") => {
+                compile_proto(&path).await?
+            }
+            _ => file.load_contents_future().await?.0.to_vec(),
+        };
+
+        self.imp()
+            .add_descriptor_set(&bytes)
+            .map_err(|e| glib::Error::new(gio::IOErrorEnum::InvalidData, &e))?;
+
+        let name =
+            glib::compute_checksum_for_data(glib::ChecksumType::Sha256, &bytes).unwrap_or_default();
+
+        let dir = descriptors_dir();
+        std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(dir.join(format!("{name}.binpb")), &bytes))
+            .map_err(|e| glib::Error::new(gio::IOErrorEnum::Failed, &e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Converts the JSON representation of `message_type` to protobuf wire format, the
+    /// String error is a human readable explanation
+    pub fn encode(&self, message_type: &str, json: &str) -> Result<Vec<u8>, String> {
+        let descriptor = self
+            .imp()
+            .pool
+            .borrow()
+            .get_message_by_name(message_type)
+            .ok_or_else(|| {
+                formatx!(
+                    gettext("Unknown protobuf message type \"{}\""),
+                    message_type
+                )
+                .unwrap_or_default()
+            })?;
+
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+
+        let message = DynamicMessage::deserialize(descriptor, &mut deserializer)
+            .and_then(|message| deserializer.end().map(|_| message))
+            .map_err(|e| e.to_string())?;
+
+        Ok(message.encode_to_vec())
+    }
+
+    /// Reverse operation of [`MQTTyProtobufRegistry::encode()`], every field is included
+    /// in the JSON, even the ones with default values. Returns None if the message type
+    /// is unknown or the payload is not valid for it
+    pub fn decode(&self, message_type: &str, payload: &[u8]) -> Option<String> {
+        let descriptor = self.imp().pool.borrow().get_message_by_name(message_type)?;
+
+        let message = DynamicMessage::decode(descriptor, payload).ok()?;
+
+        let mut serializer = serde_json::Serializer::pretty(vec![]);
+        message
+            .serialize_with_options(
+                &mut serializer,
+                &SerializeOptions::new().skip_default_fields(false),
+            )
+            .ok()?;
+
+        String::from_utf8(serializer.into_inner()).ok()
+    }
+
+    pub fn topic_types(&self) -> Vec<(String, String)> {
+        self.imp().topic_types.borrow().clone()
+    }
+
+    /// Message type of the first topic filter that matches `topic`
+    pub fn topic_message_type(&self, topic: &str) -> Option<String> {
+        self.imp()
+            .topic_types
+            .borrow()
+            .iter()
+            .find(|(topic_filter, _)| topic_matches_filter(topic_filter, topic))
+            .map(|(_, message_type)| message_type.clone())
+    }
+
+    /// Maps `topic_filter` to `message_type`, replacing the previous mapping of the
+    /// same topic filter
+    pub fn set_topic_type(&self, topic_filter: &str, message_type: &str) {
+        {
+            let mut topic_types = self.imp().topic_types.borrow_mut();
+            topic_types.retain(|(f, _)| f != topic_filter);
+            topic_types.push((topic_filter.to_string(), message_type.to_string()));
+        }
+
+        self.save_topic_types();
+    }
+
+    pub fn remove_topic_type(&self, topic_filter: &str) {
+        self.imp()
+            .topic_types
+            .borrow_mut()
+            .retain(|(f, _)| f != topic_filter);
+
+        self.save_topic_types();
+    }
+
+    fn save_topic_types(&self) {
+        if let Err(e) = MQTTyApplication::get_singleton()
+            .settings()
+            .set("protobuf-topic-types", self.topic_types())
+        {
+            tracing::error!("Could not save the protobuf topic types: {}", e);
+        }
+
+        self.emit_by_name::<()>("topic-types-changed", &[]);
+    }
+}
+
+fn descriptors_dir() -> PathBuf {
+    glib::user_data_dir().join("MQTTy").join("protobuf")
+}
+
+/// Compiles a `.proto` file to a `FileDescriptorSet`, imports are looked up in the
+/// directory of the file
+async fn compile_proto(path: &Path) -> Result<Vec<u8>, glib::Error> {
+    let out = glib::tmp_dir().join(format!("MQTTy-{}.binpb", glib::uuid_string_random()));
+
+    let mut descriptor_set_out = OsString::from("--descriptor_set_out=");
+    descriptor_set_out.push(&out);
+
+    let mut proto_path = OsString::from("--proto_path=");
+    proto_path.push(path.parent().unwrap_or(Path::new(".")));
+
+    let process = gio::Subprocess::newv(
+        &[
+            "protoc".as_ref(),
+            "--include_imports".as_ref(),
+            descriptor_set_out.as_os_str(),
+            proto_path.as_os_str(),
+            path.as_os_str(),
+        ],
+        gio::SubprocessFlags::STDERR_PIPE,
+    )
+    .map_err(|_| {
+        glib::Error::new(
+            gio::IOErrorEnum::NotFound,
+            &gettext(
+                "protoc is needed for loading .proto files, load a compiled descriptor set instead",
+            ),
+        )
+    })?;
+
+    let (_, stderr) = process.communicate_utf8_future(None).await?;
+
+    let result = if process.is_successful() {
+        std::fs::read(&out).map_err(|e| glib::Error::new(gio::IOErrorEnum::Failed, &e.to_string()))
+    } else {
+        Err(glib::Error::new(
+            gio::IOErrorEnum::InvalidData,
+            stderr.as_deref().unwrap_or_default().trim(),
+        ))
+    };
+
+    let _ = std::fs::remove_file(&out);
+
+    result
+}
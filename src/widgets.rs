@@ -14,21 +14,48 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 mod add_conn_card;
+mod broker_capabilities_group;
 mod conn_card;
 mod edit_conn_list_box;
+mod hex_view;
+mod json_schema_row;
+mod json_schemas_group;
 mod key_value_row;
+mod message_detail;
+mod message_row;
+mod payload_formats_group;
+mod protobuf_types_group;
 mod publish_view;
+mod replay_dialog;
 mod source_view;
+mod subscriptions_view;
+mod tls_settings_group;
+mod topic_node_row;
+mod topic_tree_view;
 
 pub mod base_card;
 
 pub use add_conn_card::MQTTyAddConnCard;
 pub use base_card::MQTTyBaseCard;
+pub use broker_capabilities_group::MQTTyBrokerCapabilitiesGroup;
 pub use conn_card::MQTTyConnCard;
 pub use edit_conn_list_box::MQTTyEditConnListBox;
+pub use hex_view::MQTTyHexView;
+pub use json_schema_row::MQTTyJsonSchemaRow;
+pub use json_schemas_group::MQTTyJsonSchemasGroup;
 pub use key_value_row::MQTTyKeyValueRow;
+pub use message_detail::MQTTyMessageDetail;
+pub use message_row::MQTTyMessageRow;
+pub use payload_formats_group::MQTTyPayloadFormatsGroup;
+pub use protobuf_types_group::{load_descriptors, MQTTyProtobufTypesGroup};
 pub use publish_view::{
-    MQTTyPublishAuthTab, MQTTyPublishBodyTab, MQTTyPublishGeneralTab, MQTTyPublishUserPropsTab,
-    MQTTyPublishView,
+    MQTTyPublishAuthTab, MQTTyPublishBodyTab, MQTTyPublishGeneralTab, MQTTyPublishPropertiesTab,
+    MQTTyPublishResponseTab, MQTTyPublishUserPropsTab, MQTTyPublishView, MQTTyPublishViewNotebook,
+    MQTTyPublishWillTab,
 };
+pub use replay_dialog::MQTTyReplayDialog;
 pub use source_view::MQTTySourceView;
+pub use subscriptions_view::{ask_password, MQTTySubscriptionsView};
+pub use tls_settings_group::MQTTyTlsSettingsGroup;
+pub use topic_node_row::MQTTyTopicNodeRow;
+pub use topic_tree_view::MQTTyTopicTreeView;
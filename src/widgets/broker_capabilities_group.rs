@@ -0,0 +1,156 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
+use gtk::glib;
+
+use crate::client::MQTTyBrokerCapabilities;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/broker_capabilities_group.ui")]
+    #[properties(wrapper_type = super::MQTTyBrokerCapabilitiesGroup)]
+    pub struct MQTTyBrokerCapabilitiesGroup {
+        /// The group is hidden while this is None
+        #[property(get, set = Self::set_capabilities, nullable)]
+        capabilities: RefCell<Option<MQTTyBrokerCapabilities>>,
+
+        #[template_child]
+        maximum_qos_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        retain_available_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        wildcard_subscription_available_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        shared_subscription_available_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        subscription_identifiers_available_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        topic_alias_maximum_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        receive_maximum_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        maximum_packet_size_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        server_keep_alive_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        assigned_client_identifier_row: TemplateChild<adw::ActionRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyBrokerCapabilitiesGroup {
+        const NAME: &'static str = "MQTTyBrokerCapabilitiesGroup";
+
+        type Type = super::MQTTyBrokerCapabilitiesGroup;
+
+        type ParentType = adw::PreferencesGroup;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyBrokerCapabilitiesGroup {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.obj().set_visible(false);
+        }
+    }
+    impl WidgetImpl for MQTTyBrokerCapabilitiesGroup {}
+    impl PreferencesGroupImpl for MQTTyBrokerCapabilitiesGroup {}
+
+    impl MQTTyBrokerCapabilitiesGroup {
+        fn set_capabilities(&self, capabilities: Option<MQTTyBrokerCapabilities>) {
+            self.obj().set_visible(capabilities.is_some());
+
+            if let Some(capabilities) = &capabilities {
+                let available = |available: bool| {
+                    if available {
+                        gettext("Available")
+                    } else {
+                        gettext("Not available")
+                    }
+                };
+
+                let unlimited_or = |value: u32, unit: String| {
+                    if value == 0 {
+                        gettext("Unlimited")
+                    } else {
+                        formatx!(unit, value).unwrap_or_default()
+                    }
+                };
+
+                self.maximum_qos_row
+                    .set_subtitle(&(capabilities.maximum_qos() as i32).to_string());
+                self.retain_available_row
+                    .set_subtitle(&available(capabilities.retain_available()));
+                self.wildcard_subscription_available_row
+                    .set_subtitle(&available(capabilities.wildcard_subscription_available()));
+                self.shared_subscription_available_row
+                    .set_subtitle(&available(capabilities.shared_subscription_available()));
+                self.subscription_identifiers_available_row
+                    .set_subtitle(&available(
+                        capabilities.subscription_identifiers_available(),
+                    ));
+                self.topic_alias_maximum_row.set_subtitle(
+                    &match capabilities.topic_alias_maximum() {
+                        0 => gettext("Topic aliases not accepted"),
+                        v => v.to_string(),
+                    },
+                );
+                self.receive_maximum_row
+                    .set_subtitle(&capabilities.receive_maximum().to_string());
+                self.maximum_packet_size_row.set_subtitle(&unlimited_or(
+                    capabilities.maximum_packet_size(),
+                    gettext("{} bytes"),
+                ));
+                self.server_keep_alive_row
+                    .set_subtitle(&match capabilities.server_keep_alive() {
+                        0 => gettext("Not imposed by the broker"),
+                        v => formatx!(gettext("{} seconds"), v).unwrap_or_default(),
+                    });
+
+                let assigned_client_identifier = capabilities.assigned_client_identifier();
+                self.assigned_client_identifier_row
+                    .set_visible(assigned_client_identifier.is_some());
+                self.assigned_client_identifier_row
+                    .set_subtitle(&assigned_client_identifier.unwrap_or_default());
+            }
+
+            self.capabilities.replace(capabilities);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Read only view of a MQTTyBrokerCapabilities object
+    pub struct MQTTyBrokerCapabilitiesGroup(ObjectSubclass<imp::MQTTyBrokerCapabilitiesGroup>)
+        @extends gtk::Widget, adw::PreferencesGroup,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
@@ -58,10 +58,10 @@ glib::wrapper! {
 }
 
 impl MQTTyConnCard {
-    pub fn new(host: &String, topic: &String) -> Self {
+    pub fn new(host: &String, name: &String) -> Self {
         glib::Object::builder::<Self>()
             .property("subtitle", host)
-            .property("title", topic)
+            .property("title", name)
             .build()
     }
 }
@@ -76,6 +76,6 @@ impl Default for MQTTyConnCard {
 impl From<MQTTySettingConnection> for MQTTyConnCard {
     fn from(value: MQTTySettingConnection) -> Self {
         // TODO: Extract host and pass as parameter instead of whole url
-        Self::new(&value.url(), &value.topic())
+        Self::new(&value.url(), &value.display_name())
     }
 }
@@ -21,7 +21,9 @@ use adw::subclass::prelude::*;
 use gtk::glib;
 use gtk::glib::subclass::Signal;
 
+use crate::client::MQTTyClientVersion;
 use crate::gsettings::MQTTySettingConnection;
+use crate::widgets::{MQTTyPublishWillTab, MQTTyTlsSettingsGroup};
 
 mod imp {
 
@@ -37,11 +39,38 @@ mod imp {
         #[property(get, set, construct)]
         editing: Cell<bool>,
 
+        #[template_child]
+        name_row: TemplateChild<adw::EntryRow>,
+
         #[template_child]
         url_row: TemplateChild<adw::EntryRow>,
 
         #[template_child]
         topic_row: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        mqtt_version_row: TemplateChild<adw::ComboRow>,
+
+        #[template_child]
+        client_id_row: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        username_row: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        remember_password_row: TemplateChild<adw::SwitchRow>,
+
+        #[template_child]
+        password_row: TemplateChild<adw::PasswordEntryRow>,
+
+        #[template_child]
+        keep_alive_interval_row: TemplateChild<adw::SpinRow>,
+
+        #[template_child]
+        tls_group: TemplateChild<MQTTyTlsSettingsGroup>,
+
+        #[template_child]
+        will_tab: TemplateChild<MQTTyPublishWillTab>,
     }
 
     #[glib::object_subclass]
@@ -74,6 +103,12 @@ mod imp {
 
                 let private = obj.imp();
 
+                conn_model
+                    .bind_property("name", &*private.name_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build();
+
                 conn_model
                     .bind_property("url", &*private.url_row, "text")
                     .bidirectional()
@@ -85,6 +120,68 @@ mod imp {
                     .bidirectional()
                     .sync_create()
                     .build();
+
+                conn_model
+                    .bind_property("mqtt_version", &*private.mqtt_version_row, "selected")
+                    .bidirectional()
+                    .sync_create()
+                    .transform_to(|_, version: MQTTyClientVersion| {
+                        Some(match version {
+                            MQTTyClientVersion::V3X => 0_u32,
+                            MQTTyClientVersion::V5 => 1_u32,
+                        })
+                    })
+                    .transform_from(|_, idx: u32| {
+                        Some(if idx == 1 {
+                            MQTTyClientVersion::V5
+                        } else {
+                            MQTTyClientVersion::V3X
+                        })
+                    })
+                    .build();
+
+                conn_model
+                    .bind_property("client_id", &*private.client_id_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build();
+
+                conn_model
+                    .bind_property("username", &*private.username_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build();
+
+                conn_model
+                    .bind_property(
+                        "remember_password",
+                        &*private.remember_password_row,
+                        "active",
+                    )
+                    .bidirectional()
+                    .sync_create()
+                    .build();
+
+                conn_model
+                    .bind_property("password", &*private.password_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build();
+
+                conn_model
+                    .bind_property(
+                        "keep_alive_interval",
+                        &*private.keep_alive_interval_row,
+                        "value",
+                    )
+                    .bidirectional()
+                    .sync_create()
+                    .build();
+
+                conn_model
+                    .bind_property("tls", &*private.tls_group, "settings")
+                    .sync_create()
+                    .build();
             });
         }
 
@@ -107,6 +204,9 @@ mod imp {
         fn on_save_conn(&self) {
             let obj = self.obj();
 
+            // User properties of the will are only copied to the model on demand
+            self.will_tab.apply_user_properties();
+
             obj.emit_by_name::<()>("saving-conn", &[]);
         }
 
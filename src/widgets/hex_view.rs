@@ -0,0 +1,228 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::hex;
+
+/// Bytes shown in each row
+const ROW_LEN: usize = 16;
+
+/// Maximum number of bytes shown when the view is not editable, rendering the text of
+/// bigger payloads would freeze the UI
+const READ_ONLY_LIMIT: usize = 1024 * 1024;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/hex_view.ui")]
+    #[properties(wrapper_type = super::MQTTyHexView)]
+    pub struct MQTTyHexView {
+        #[property(get, set = Self::set_bytes)]
+        bytes: RefCell<glib::Bytes>,
+
+        #[property(get, set)]
+        editable: Cell<bool>,
+
+        /// True if the text typed by the user is not valid hexadecimal, :bytes keeps the
+        /// last valid value meanwhile
+        #[property(get)]
+        invalid: Cell<bool>,
+
+        #[template_child]
+        offsets_view: TemplateChild<gtk::TextView>,
+        #[template_child]
+        hex_view: TemplateChild<gtk::TextView>,
+        #[template_child]
+        ascii_view: TemplateChild<gtk::TextView>,
+    }
+
+    impl Default for MQTTyHexView {
+        fn default() -> Self {
+            Self {
+                bytes: RefCell::new(glib::Bytes::from_static(b"")),
+                editable: Default::default(),
+                invalid: Default::default(),
+                offsets_view: Default::default(),
+                hex_view: Default::default(),
+                ascii_view: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyHexView {
+        const NAME: &'static str = "MQTTyHexView";
+
+        type Type = super::MQTTyHexView;
+
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyHexView {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.hex_view.buffer().connect_changed(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |buffer| {
+                    let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+                    this.on_hex_changed(&text);
+                }
+            ));
+
+            // Typed text is only reformatted when the user leaves the view, so that the
+            // cursor doesn't jump while typing
+            let focus = gtk::EventControllerFocus::new();
+            focus.connect_leave(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_| {
+                    if !this.invalid.get() {
+                        this.update_text(true);
+                    }
+                }
+            ));
+            self.hex_view.add_controller(focus);
+
+            // The size limit depends on it
+            self.obj()
+                .connect_editable_notify(|obj| obj.imp().update_text(true));
+
+            self.update_text(true);
+        }
+    }
+    impl WidgetImpl for MQTTyHexView {}
+    impl BinImpl for MQTTyHexView {}
+
+    impl MQTTyHexView {
+        fn set_bytes(&self, bytes: glib::Bytes) {
+            if *self.bytes.borrow() == bytes {
+                return;
+            }
+
+            self.bytes.replace(bytes);
+            self.set_invalid(false);
+            self.update_text(true);
+        }
+
+        fn on_hex_changed(&self, text: &str) {
+            let Some(bytes) = hex::decode(text) else {
+                self.set_invalid(true);
+                return;
+            };
+
+            self.set_invalid(false);
+
+            if *self.bytes.borrow() == *bytes {
+                return;
+            }
+
+            self.bytes.replace(glib::Bytes::from_owned(bytes));
+            self.update_text(false);
+            self.obj().notify_bytes();
+        }
+
+        fn set_invalid(&self, invalid: bool) {
+            if self.invalid.replace(invalid) == invalid {
+                return;
+            }
+
+            if invalid {
+                self.hex_view.add_css_class("error");
+            } else {
+                self.hex_view.remove_css_class("error");
+            }
+
+            self.obj().notify_invalid();
+        }
+
+        /// Updates the offsets and ASCII columns, and the hex column too if `hex` is true
+        fn update_text(&self, hex: bool) {
+            let bytes = self.bytes.borrow().clone();
+
+            let shown = if self.editable.get() {
+                &bytes[..]
+            } else {
+                &bytes[..bytes.len().min(READ_ONLY_LIMIT)]
+            };
+
+            let mut offsets = vec![];
+            let mut hex_rows = vec![];
+            let mut ascii_rows = vec![];
+
+            for (i, row) in shown.chunks(ROW_LEN).enumerate() {
+                offsets.push(format!("{:08x}", i * ROW_LEN));
+                hex_rows.push(
+                    row.iter()
+                        .map(|b| format!("{b:02x}"))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                );
+                ascii_rows.push(
+                    row.iter()
+                        .map(|&b| {
+                            if b.is_ascii_graphic() || b == b' ' {
+                                b as char
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect::<String>(),
+                );
+            }
+
+            if shown.len() < bytes.len() {
+                offsets.push("…".to_string());
+            }
+
+            self.offsets_view.buffer().set_text(&offsets.join("\n"));
+            self.ascii_view.buffer().set_text(&ascii_rows.join("\n"));
+
+            if hex {
+                let buffer = self.hex_view.buffer();
+                let text = hex_rows.join("\n");
+
+                if buffer.text(&buffer.start_iter(), &buffer.end_iter(), false) != text {
+                    buffer.set_text(&text);
+                }
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Hexadecimal editor of binary data, with columns for the offset and the ASCII
+    /// characters of each row. Only the first megabyte is shown when it's not editable
+    pub struct MQTTyHexView(ObjectSubclass<imp::MQTTyHexView>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
@@ -0,0 +1,179 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
+use gtk::{gio, glib};
+use sourceview::prelude::*;
+
+use crate::application::MQTTyApplication;
+use crate::main_window::MQTTyWindow;
+use crate::toast::MQTTyToastBuilder;
+use crate::widgets::MQTTySourceView;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/json_schema_row.ui")]
+    #[properties(wrapper_type = super::MQTTyJsonSchemaRow)]
+    pub struct MQTTyJsonSchemaRow {
+        /// Text of the schema, empty if there is none
+        #[property(get, set)]
+        schema: RefCell<String>,
+
+        #[template_child]
+        source_view: TemplateChild<MQTTySourceView>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyJsonSchemaRow {
+        const NAME: &'static str = "MQTTyJsonSchemaRow";
+
+        type Type = super::MQTTyJsonSchemaRow;
+
+        type ParentType = adw::ExpanderRow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyJsonSchemaRow {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let language = sourceview::LanguageManager::default().language("json");
+
+            self.source_view
+                .buffer()
+                .downcast::<sourceview::Buffer>()
+                .unwrap()
+                .set_language(language.as_ref());
+        }
+    }
+    impl WidgetImpl for MQTTyJsonSchemaRow {}
+    impl ListBoxRowImpl for MQTTyJsonSchemaRow {}
+    impl PreferencesRowImpl for MQTTyJsonSchemaRow {}
+    impl ExpanderRowImpl for MQTTyJsonSchemaRow {}
+
+    #[gtk::template_callbacks]
+    impl MQTTyJsonSchemaRow {
+        #[template_callback]
+        fn is_schema_set(&self, schema: &str) -> bool {
+            !schema.is_empty()
+        }
+
+        #[template_callback]
+        fn on_clear_schema(&self) {
+            self.obj().set_schema("");
+        }
+
+        #[template_callback]
+        fn on_load_schema(&self) {
+            let obj = self.obj();
+
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    let filter = gtk::FileFilter::new();
+                    filter.set_name(Some(&gettext("JSON Schemas")));
+                    filter.add_suffix("json");
+                    filter.add_mime_type("application/schema+json");
+
+                    let filters = gio::ListStore::new::<gtk::FileFilter>();
+                    filters.append(&filter);
+
+                    let dialog = gtk::FileDialog::builder()
+                        .title(gettext("Load JSON Schema"))
+                        .filters(&filters)
+                        .modal(true)
+                        .build();
+
+                    let window = obj.root().and_downcast::<gtk::Window>();
+
+                    let Ok(file) = dialog.open_future(window.as_ref()).await else {
+                        return;
+                    };
+
+                    let result = file
+                        .load_contents_future()
+                        .await
+                        .map_err(|e| e.message().to_string())
+                        .and_then(|(bytes, _)| {
+                            String::from_utf8(bytes.to_vec()).map_err(|e| e.to_string())
+                        });
+
+                    match result {
+                        Ok(schema) => {
+                            obj.set_schema(schema);
+                            obj.set_expanded(true);
+                        }
+                        Err(e) => toast_error(
+                            formatx!(
+                                gettext("Couldn't load {}: {}"),
+                                file.basename()
+                                    .map(|name| name.display().to_string())
+                                    .unwrap_or_default(),
+                                e
+                            )
+                            .unwrap_or_default(),
+                        ),
+                    }
+                }
+            ));
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Editor of a JSON Schema, which can be typed or loaded from a file
+    pub struct MQTTyJsonSchemaRow(ObjectSubclass<imp::MQTTyJsonSchemaRow>)
+        @extends gtk::Widget, gtk::ListBoxRow, adw::PreferencesRow, adw::ExpanderRow,
+        @implements gtk::Accessible, gtk::Actionable, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+fn toast_error(title: String) {
+    let app = MQTTyApplication::get_singleton();
+
+    let Some(window) = app.active_window().and_downcast::<MQTTyWindow>() else {
+        return;
+    };
+
+    window.toast(
+        &MQTTyToastBuilder::new()
+            .title(title)
+            .icon(
+                gtk::Image::builder()
+                    .icon_name("dialog-error-symbolic")
+                    .build()
+                    .as_ref(),
+            )
+            .timeout(5)
+            .build(),
+    );
+}
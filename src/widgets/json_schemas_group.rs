@@ -0,0 +1,164 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::glib;
+
+use crate::json_schema::{invalid_schema_violation, MQTTyJsonSchema, MQTTyJsonSchemas};
+use crate::widgets::MQTTyJsonSchemaRow;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/json_schemas_group.ui")]
+    pub struct MQTTyJsonSchemasGroup {
+        #[template_child]
+        topic_filter_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        schema_row: TemplateChild<MQTTyJsonSchemaRow>,
+        #[template_child]
+        schemas_list: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyJsonSchemasGroup {
+        const NAME: &'static str = "MQTTyJsonSchemasGroup";
+
+        type Type = super::MQTTyJsonSchemasGroup;
+
+        type ParentType = adw::PreferencesGroup;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MQTTyJsonSchemasGroup {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            MQTTyJsonSchemas::get_singleton().connect_closure(
+                "schemas-changed",
+                false,
+                glib::closure_local!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_: MQTTyJsonSchemas| this.update_schemas()
+                ),
+            );
+
+            self.schema_row.connect_schema_notify(|row| {
+                let schema = row.schema();
+
+                let subtitle = match MQTTyJsonSchema::parse(&schema) {
+                    Err(e) if !schema.trim().is_empty() => invalid_schema_violation(&e),
+                    _ => String::new(),
+                };
+
+                row.set_subtitle(&subtitle);
+            });
+
+            self.update_schemas();
+        }
+    }
+    impl WidgetImpl for MQTTyJsonSchemasGroup {}
+    impl PreferencesGroupImpl for MQTTyJsonSchemasGroup {}
+
+    #[gtk::template_callbacks]
+    impl MQTTyJsonSchemasGroup {
+        #[template_callback]
+        fn on_add_schema(&self) {
+            let topic_filter = self.topic_filter_row.text();
+            let schema = self.schema_row.schema();
+
+            if topic_filter.is_empty() || schema.trim().is_empty() {
+                return;
+            }
+
+            if let Err(e) =
+                MQTTyJsonSchemas::get_singleton().set_topic_schema(&topic_filter, &schema)
+            {
+                self.schema_row.set_subtitle(&invalid_schema_violation(&e));
+                return;
+            }
+
+            self.topic_filter_row.set_text("");
+            self.schema_row.set_schema("");
+            self.schema_row.set_expanded(false);
+        }
+    }
+
+    impl MQTTyJsonSchemasGroup {
+        fn update_schemas(&self) {
+            let schemas = MQTTyJsonSchemas::get_singleton().schemas();
+
+            self.schemas_list.remove_all();
+            self.schemas_list.set_visible(!schemas.is_empty());
+
+            for (topic_filter, schema) in schemas {
+                let row = adw::ActionRow::builder()
+                    .title(&topic_filter)
+                    .subtitle(schema_title(&schema))
+                    .use_markup(false)
+                    .build();
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(gettext("Remove"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+
+                remove_button.connect_clicked(move |_| {
+                    MQTTyJsonSchemas::get_singleton().remove_topic_schema(&topic_filter);
+                });
+
+                row.add_suffix(&remove_button);
+
+                self.schemas_list.append(&row);
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Attaches JSON Schemas to topic filters, see [`MQTTyJsonSchemas`]
+    pub struct MQTTyJsonSchemasGroup(ObjectSubclass<imp::MQTTyJsonSchemasGroup>)
+        @extends gtk::Widget, adw::PreferencesGroup,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+/// The "title" keyword of the schema, or its "$id" if it has no title
+fn schema_title(schema: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(schema)
+        .ok()
+        .and_then(|value| {
+            value
+                .get("title")
+                .or_else(|| value.get("$id"))
+                .and_then(|title| title.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| gettext("Untitled schema"))
+}
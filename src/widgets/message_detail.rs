@@ -0,0 +1,249 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::{gdk, glib};
+use sourceview::prelude::*;
+
+use crate::client::MQTTyClientMessage;
+use crate::hex;
+use crate::json_schema::MQTTyJsonSchemas;
+use crate::payload_decoder::{MQTTyPayloadDecoder, MQTTyPayloadFormat};
+use crate::widgets::{MQTTyHexView, MQTTySourceView};
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/message_detail.ui")]
+    #[properties(wrapper_type = super::MQTTyMessageDetail)]
+    pub struct MQTTyMessageDetail {
+        #[property(get, set = Self::set_message, nullable)]
+        message: RefCell<Option<MQTTyClientMessage>>,
+
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        topic_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        qos_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        retained_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        direction_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        timestamp_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        size_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        content_type_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        format_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        schema_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        response_topic_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        correlation_data_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        user_properties_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        body_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        source_view: TemplateChild<MQTTySourceView>,
+        #[template_child]
+        hex_view: TemplateChild<MQTTyHexView>,
+        #[template_child]
+        picture: TemplateChild<gtk::Picture>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyMessageDetail {
+        const NAME: &'static str = "MQTTyMessageDetail";
+
+        type Type = super::MQTTyMessageDetail;
+
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyMessageDetail {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            // The shown message is decoded and validated again with the new settings
+            MQTTyPayloadDecoder::get_singleton().connect_closure(
+                "formats-changed",
+                false,
+                glib::closure_local!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_: MQTTyPayloadDecoder| this.reload_message()
+                ),
+            );
+
+            MQTTyJsonSchemas::get_singleton().connect_closure(
+                "schemas-changed",
+                false,
+                glib::closure_local!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_: MQTTyJsonSchemas| this.reload_message()
+                ),
+            );
+        }
+    }
+    impl WidgetImpl for MQTTyMessageDetail {}
+    impl BinImpl for MQTTyMessageDetail {}
+
+    impl MQTTyMessageDetail {
+        fn reload_message(&self) {
+            let message = self.message.borrow().clone();
+            self.set_message(message);
+        }
+
+        fn set_message(&self, message: Option<MQTTyClientMessage>) {
+            let Some(msg) = &message else {
+                self.stack.set_visible_child_name("empty");
+                self.source_view.buffer().set_text("");
+                self.hex_view.set_bytes(glib::Bytes::from_static(b""));
+                self.picture.set_paintable(gdk::Paintable::NONE);
+                self.message.replace(None);
+                return;
+            };
+
+            self.topic_row.set_subtitle(&msg.topic());
+            self.qos_row.set_subtitle(&(msg.qos() as i32).to_string());
+            self.retained_row.set_subtitle(&if msg.retained() {
+                gettext("Yes")
+            } else {
+                gettext("No")
+            });
+            self.direction_row
+                .set_subtitle(&msg.direction().translated());
+            self.timestamp_row.set_subtitle(
+                &msg.timestamp()
+                    .and_then(|t| t.format("%Y-%m-%d %H:%M:%S").ok())
+                    .unwrap_or_default(),
+            );
+            self.size_row
+                .set_subtitle(&glib::format_size(msg.body().len() as u64));
+
+            // Rows of properties that the message doesn't have are hidden, this is
+            // always the case for MQTT v3 messages
+            let content_type = msg.content_type().unwrap_or_default();
+            self.content_type_row.set_visible(!content_type.is_empty());
+            self.content_type_row.set_subtitle(&content_type);
+
+            let response_topic = msg.response_topic().unwrap_or_default();
+            self.response_topic_row
+                .set_visible(!response_topic.is_empty());
+            self.response_topic_row.set_subtitle(&response_topic);
+
+            let correlation_data = msg
+                .correlation_data()
+                .map(|data| hex::encode(&data))
+                .unwrap_or_default();
+            self.correlation_data_row
+                .set_visible(!correlation_data.is_empty());
+            self.correlation_data_row.set_subtitle(&correlation_data);
+
+            let user_properties = msg
+                .user_properties()
+                .iter()
+                .map(|(k, v)| format!("{k}: {v}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            self.user_properties_row
+                .set_visible(!user_properties.is_empty());
+            self.user_properties_row.set_subtitle(&user_properties);
+
+            let decoded = MQTTyPayloadDecoder::get_singleton().decode_message(msg);
+
+            self.format_row.set_subtitle(&decoded.description());
+
+            let violations = MQTTyJsonSchemas::get_singleton().message_violations(msg);
+            self.schema_row.set_visible(!violations.is_empty());
+            self.schema_row.set_subtitle(&violations.join("\n"));
+
+            let language = decoded
+                .format
+                .language_id()
+                .and_then(|id| sourceview::LanguageManager::default().language(id));
+            self.source_view
+                .buffer()
+                .downcast::<sourceview::Buffer>()
+                .unwrap()
+                .set_language(language.as_ref());
+
+            self.hex_view.set_bytes(glib::Bytes::from_owned(msg.body()));
+
+            // Images are shown decoded, the text and hex views are still available
+            let texture = match decoded.format {
+                MQTTyPayloadFormat::Image => {
+                    gdk::Texture::from_bytes(&glib::Bytes::from_owned(decoded.data.clone()))
+                        .inspect_err(|e| tracing::debug!("Could not load image payload: {}", e))
+                        .ok()
+                }
+                _ => None,
+            };
+            self.picture.set_paintable(texture.as_ref());
+            self.body_stack
+                .page(&*self.picture)
+                .set_visible(texture.is_some());
+
+            let page = if texture.is_some() {
+                "image"
+            } else if decoded.text.is_some() {
+                "text"
+            } else {
+                "hex"
+            };
+
+            self.source_view
+                .buffer()
+                .set_text(&MQTTyPayloadDecoder::get_singleton().message_text(msg));
+
+            self.body_stack.set_visible_child_name(page);
+
+            self.stack.set_visible_child_name("message");
+
+            self.message.replace(message);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Shows every field of a message, with its body in a read-only source view, hex view
+    /// or picture, depending on the format detected by [`MQTTyPayloadDecoder`]
+    ///
+    /// [`MQTTyPayloadDecoder`]: crate::payload_decoder::MQTTyPayloadDecoder
+    pub struct MQTTyMessageDetail(ObjectSubclass<imp::MQTTyMessageDetail>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
+use gtk::glib;
+
+use crate::client::{MQTTyClientMessage, MQTTyMessageDirection};
+use crate::json_schema::MQTTyJsonSchemas;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/message_row.ui")]
+    #[properties(wrapper_type = super::MQTTyMessageRow)]
+    pub struct MQTTyMessageRow {
+        #[property(get, set = Self::set_message, nullable)]
+        message: RefCell<Option<MQTTyClientMessage>>,
+
+        #[template_child]
+        topic_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        details_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        schema_icon: TemplateChild<gtk::Image>,
+        #[template_child]
+        time_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyMessageRow {
+        const NAME: &'static str = "MQTTyMessageRow";
+
+        type Type = super::MQTTyMessageRow;
+
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyMessageRow {}
+    impl WidgetImpl for MQTTyMessageRow {}
+    impl BoxImpl for MQTTyMessageRow {}
+
+    impl MQTTyMessageRow {
+        fn set_message(&self, message: Option<MQTTyClientMessage>) {
+            let Some(msg) = &message else {
+                self.topic_label.set_label("");
+                self.details_label.set_label("");
+                self.time_label.set_label("");
+                self.schema_icon.set_visible(false);
+                self.message.replace(None);
+                return;
+            };
+
+            self.topic_label.set_label(&msg.topic());
+
+            let mut details = vec![
+                formatx!(gettext("QoS {}"), msg.qos() as i32).unwrap_or_default(),
+                glib::format_size(msg.body().len() as u64).to_string(),
+            ];
+
+            if msg.direction() == MQTTyMessageDirection::Sent {
+                details.insert(0, msg.direction().translated());
+            }
+
+            if msg.retained() {
+                details.push(gettext("Retained"));
+            }
+
+            if let Some(content_type) = msg.content_type() {
+                details.push(content_type);
+            }
+
+            self.details_label.set_label(&details.join(" · "));
+
+            let violations = MQTTyJsonSchemas::get_singleton().message_violations(msg);
+            self.schema_icon.set_visible(!violations.is_empty());
+            self.schema_icon
+                .set_tooltip_text(Some(&violations.join("\n")));
+
+            self.time_label.set_label(
+                &msg.timestamp()
+                    .and_then(|t| t.format("%H:%M:%S").ok())
+                    .unwrap_or_default(),
+            );
+
+            self.message.replace(message);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Compact summary of a message, meant to be used in message lists
+    pub struct MQTTyMessageRow(ObjectSubclass<imp::MQTTyMessageRow>)
+        @extends gtk::Widget, gtk::Box,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl Default for MQTTyMessageRow {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::glib;
+
+use crate::payload_decoder::{MQTTyPayloadDecoder, MQTTyPayloadFormat};
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/payload_formats_group.ui")]
+    pub struct MQTTyPayloadFormatsGroup {
+        #[template_child]
+        topic_filter_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        format_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        formats_list: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyPayloadFormatsGroup {
+        const NAME: &'static str = "MQTTyPayloadFormatsGroup";
+
+        type Type = super::MQTTyPayloadFormatsGroup;
+
+        type ParentType = adw::PreferencesGroup;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MQTTyPayloadFormatsGroup {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let list = gtk::StringList::new(&[]);
+
+            for i in MQTTyPayloadFormat::listed() {
+                list.append(&i.translated());
+            }
+
+            self.format_row.set_model(Some(&list));
+
+            MQTTyPayloadDecoder::get_singleton().connect_closure(
+                "formats-changed",
+                false,
+                glib::closure_local!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_: MQTTyPayloadDecoder| this.update_formats()
+                ),
+            );
+
+            self.update_formats();
+        }
+    }
+    impl WidgetImpl for MQTTyPayloadFormatsGroup {}
+    impl PreferencesGroupImpl for MQTTyPayloadFormatsGroup {}
+
+    #[gtk::template_callbacks]
+    impl MQTTyPayloadFormatsGroup {
+        #[template_callback]
+        fn on_add_format(&self) {
+            let topic_filter = self.topic_filter_row.text();
+
+            if topic_filter.is_empty() {
+                return;
+            }
+
+            let format = MQTTyPayloadFormat::listed()[self.format_row.selected() as usize];
+
+            MQTTyPayloadDecoder::get_singleton().set_topic_format(&topic_filter, format);
+
+            self.topic_filter_row.set_text("");
+        }
+    }
+
+    impl MQTTyPayloadFormatsGroup {
+        fn update_formats(&self) {
+            let formats = MQTTyPayloadDecoder::get_singleton().formats();
+
+            self.formats_list.remove_all();
+            self.formats_list.set_visible(!formats.is_empty());
+
+            for (topic_filter, format) in formats {
+                let row = adw::ActionRow::builder()
+                    .title(&topic_filter)
+                    .subtitle(format.translated())
+                    .use_markup(false)
+                    .build();
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(gettext("Remove"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+
+                remove_button.connect_clicked(move |_| {
+                    MQTTyPayloadDecoder::get_singleton().remove_topic_format(&topic_filter);
+                });
+
+                row.add_suffix(&remove_button);
+
+                self.formats_list.append(&row);
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Maps topic filters to payload formats, overriding the format detected for the
+    /// received messages whose topic matches them
+    pub struct MQTTyPayloadFormatsGroup(ObjectSubclass<imp::MQTTyPayloadFormatsGroup>)
+        @extends gtk::Widget, adw::PreferencesGroup,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
@@ -0,0 +1,226 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
+use gtk::{gio, glib};
+
+use crate::application::MQTTyApplication;
+use crate::main_window::MQTTyWindow;
+use crate::protobuf_registry::MQTTyProtobufRegistry;
+use crate::toast::MQTTyToastBuilder;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/protobuf_types_group.ui")]
+    pub struct MQTTyProtobufTypesGroup {
+        #[template_child]
+        topic_filter_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        message_type_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        topic_types_list: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyProtobufTypesGroup {
+        const NAME: &'static str = "MQTTyProtobufTypesGroup";
+
+        type Type = super::MQTTyProtobufTypesGroup;
+
+        type ParentType = adw::PreferencesGroup;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MQTTyProtobufTypesGroup {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let registry = MQTTyProtobufRegistry::get_singleton();
+
+            self.message_type_row
+                .set_model(Some(&registry.message_types()));
+
+            registry.connect_closure(
+                "topic-types-changed",
+                false,
+                glib::closure_local!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_: MQTTyProtobufRegistry| this.update_topic_types()
+                ),
+            );
+
+            self.update_topic_types();
+        }
+    }
+    impl WidgetImpl for MQTTyProtobufTypesGroup {}
+    impl PreferencesGroupImpl for MQTTyProtobufTypesGroup {}
+
+    #[gtk::template_callbacks]
+    impl MQTTyProtobufTypesGroup {
+        #[template_callback]
+        fn on_load_descriptors(&self) {
+            let obj = self.obj();
+
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    load_descriptors(&obj).await;
+                }
+            ));
+        }
+
+        #[template_callback]
+        fn on_add_topic_type(&self) {
+            let topic_filter = self.topic_filter_row.text();
+
+            let Some(message_type) = self
+                .message_type_row
+                .selected_item()
+                .and_downcast::<gtk::StringObject>()
+            else {
+                return;
+            };
+
+            if topic_filter.is_empty() {
+                return;
+            }
+
+            MQTTyProtobufRegistry::get_singleton()
+                .set_topic_type(&topic_filter, &message_type.string());
+
+            self.topic_filter_row.set_text("");
+        }
+    }
+
+    impl MQTTyProtobufTypesGroup {
+        fn update_topic_types(&self) {
+            let topic_types = MQTTyProtobufRegistry::get_singleton().topic_types();
+
+            self.topic_types_list.remove_all();
+            self.topic_types_list.set_visible(!topic_types.is_empty());
+
+            for (topic_filter, message_type) in topic_types {
+                let row = adw::ActionRow::builder()
+                    .title(&topic_filter)
+                    .subtitle(&message_type)
+                    .use_markup(false)
+                    .build();
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(gettext("Remove"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+
+                remove_button.connect_clicked(move |_| {
+                    MQTTyProtobufRegistry::get_singleton().remove_topic_type(&topic_filter);
+                });
+
+                row.add_suffix(&remove_button);
+
+                self.topic_types_list.append(&row);
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Maps topic filters to protobuf message types, received messages are decoded with
+    /// the message type of the first topic filter that matches their topic
+    pub struct MQTTyProtobufTypesGroup(ObjectSubclass<imp::MQTTyProtobufTypesGroup>)
+        @extends gtk::Widget, adw::PreferencesGroup,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+/// Asks the user for `.proto` files or compiled descriptor sets and registers them,
+/// errors are shown in a toast
+pub async fn load_descriptors(parent: &impl IsA<gtk::Widget>) {
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some(&gettext("Protobuf descriptors")));
+    filter.add_suffix("proto");
+    filter.add_suffix("binpb");
+    filter.add_suffix("pb");
+    filter.add_suffix("desc");
+
+    let filters = gio::ListStore::new::<gtk::FileFilter>();
+    filters.append(&filter);
+
+    let dialog = gtk::FileDialog::builder()
+        .title(gettext("Load Protobuf Descriptors"))
+        .modal(true)
+        .filters(&filters)
+        .build();
+
+    let window = parent.root().and_downcast::<gtk::Window>();
+
+    let Ok(files) = dialog.open_multiple_future(window.as_ref()).await else {
+        return;
+    };
+
+    let registry = MQTTyProtobufRegistry::get_singleton();
+
+    for file in files.iter::<gio::File>().flatten() {
+        if let Err(e) = registry.register_file(&file).await {
+            toast_error(
+                formatx!(
+                    gettext("Couldn't load {}: {}"),
+                    file.basename()
+                        .map(|name| name.display().to_string())
+                        .unwrap_or_default(),
+                    e.message()
+                )
+                .unwrap_or_default(),
+            );
+        }
+    }
+}
+
+fn toast_error(title: String) {
+    let app = MQTTyApplication::get_singleton();
+
+    let Some(window) = app.active_window().and_downcast::<MQTTyWindow>() else {
+        return;
+    };
+
+    window.toast(
+        &MQTTyToastBuilder::new()
+            .title(title)
+            .icon(
+                gtk::Image::builder()
+                    .icon_name("dialog-error-symbolic")
+                    .build()
+                    .as_ref(),
+            )
+            .timeout(5)
+            .build(),
+    );
+}
@@ -16,25 +16,33 @@
 mod publish_auth_tab;
 mod publish_body_tab;
 mod publish_general_tab;
+mod publish_properties_tab;
+mod publish_response_tab;
 mod publish_user_props_tab;
 mod publish_view_notebook;
+mod publish_will_tab;
 
 pub use publish_auth_tab::MQTTyPublishAuthTab;
 pub use publish_body_tab::MQTTyPublishBodyTab;
 pub use publish_general_tab::MQTTyPublishGeneralTab;
+pub use publish_properties_tab::MQTTyPublishPropertiesTab;
+pub use publish_response_tab::MQTTyPublishResponseTab;
 pub use publish_user_props_tab::MQTTyPublishUserPropsTab;
 pub use publish_view_notebook::MQTTyPublishViewNotebook;
+pub use publish_will_tab::MQTTyPublishWillTab;
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use formatx::formatx;
 use gettextrs::gettext;
-use gtk::glib;
+use gtk::{gio, glib};
 
 use crate::application::MQTTyApplication;
+use crate::client::{MQTTyClient, MQTTyClientError, MQTTyClientState};
 use crate::display_mode::{MQTTyDisplayMode, MQTTyDisplayModeIface};
+use crate::gsettings::MQTTySettingConnection;
 use crate::main_window::MQTTyWindow;
 use crate::subclass::prelude::*;
 
@@ -51,8 +59,13 @@ mod imp {
         #[property(get, set, override_interface = MQTTyDisplayModeIface)]
         display_mode: Cell<MQTTyDisplayMode>,
 
+        /// Saved connection of the panel page that shows this view, new tabs start with
+        /// its will and use its keep alive interval
+        #[property(get, set, nullable)]
+        conn_model: RefCell<Option<MQTTySettingConnection>>,
+
         #[template_child]
-        tab_view: TemplateChild<adw::TabView>,
+        pub tab_view: TemplateChild<adw::TabView>,
 
         #[template_child]
         stack: TemplateChild<gtk::Stack>,
@@ -65,6 +78,7 @@ mod imp {
         fn default() -> Self {
             Self {
                 display_mode: Cell::new(MQTTyDisplayMode::Desktop),
+                conn_model: Default::default(),
                 tab_view: Default::default(),
                 stack: Default::default(),
                 send_button: Default::default(),
@@ -84,43 +98,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             klass.install_action("publish-view.new-tab", None, |this, _, _| {
-                let notebook = MQTTyPublishViewNotebook::new();
-                this.bind_property("display_mode", &notebook, "display_mode")
-                    .sync_create()
-                    .build();
-
-                let topic_expr = notebook
-                    .property_expression_weak("topic")
-                    .chain_closure::<String>(glib::closure!(
-                        move |_: Option<glib::Object>, topic: String| {
-                            if topic.is_empty() {
-                                gettext("(untitled)")
-                            } else {
-                                topic
-                            }
-                        }
-                    ));
-
-                let page = this.imp().tab_view.append(&notebook);
-
-                topic_expr.bind(&page, "title", glib::Object::NONE);
-
-                // We create a tooltip based on topic and url values, so that users knows how to
-                // differentiate between similar messages
-                gtk::ClosureExpression::new::<String>(
-                    [
-                        topic_expr.upcast(),
-                        notebook.property_expression_weak("url").upcast(),
-                    ],
-                    glib::closure!(move |_: Option<glib::Object>, topic: String, url: String| {
-                        if url.is_empty() {
-                            topic
-                        } else {
-                            [topic, url].join("\r\n")
-                        }
-                    }),
-                )
-                .bind(&page, "tooltip", glib::Object::NONE);
+                this.new_tab();
             });
 
             klass.install_action("publish-view.send", None, |this, _, _| {
@@ -153,15 +131,22 @@ mod imp {
 
                     publishing_toast.dismiss();
 
+                    if matches!(ret, Err(MQTTyClientError::Cancelled)) {
+                        return;
+                    }
+
                     let toast = match ret {
                         Ok(_) => MQTTyToastBuilder::new()
-                            .title(
+                            .title(if notebook.request_mode() {
                                 formatx!(
-                                    gettext("Message published to topic {}"),
-                                    notebook.topic()
+                                    gettext("Response received in {} ms"),
+                                    notebook.response_latency()
                                 )
-                                .unwrap(),
-                            )
+                                .unwrap()
+                            } else {
+                                formatx!(gettext("Message published to topic {}"), notebook.topic())
+                                    .unwrap()
+                            })
                             .icon(
                                 gtk::Image::builder()
                                     .icon_name("object-select-symbolic")
@@ -173,14 +158,20 @@ mod imp {
                             .build(),
 
                         Err(e) => MQTTyToastBuilder::new()
-                            .title(formatx!(gettext("Error while publishing: {}"), e).unwrap())
+                            .title(if e.is_retryable() {
+                                formatx!(gettext("Error while publishing: {}, try again later"), e)
+                                    .unwrap()
+                            } else {
+                                formatx!(gettext("Error while publishing: {}"), e).unwrap()
+                            })
                             .icon(
                                 gtk::Image::builder()
                                     .icon_name("network-error-symbolic")
                                     .build()
                                     .as_ref(),
                             )
-                            .timeout(2)
+                            // Longer timeout, errors take more time to read
+                            .timeout(5)
                             .build(),
                     };
 
@@ -223,8 +214,130 @@ mod imp {
     impl MQTTyDisplayModeIfaceImpl for MQTTyPublishView {}
 }
 
+// Tabs that never connected don't have any state to show
+fn connection_state_icon(
+    client: Option<&MQTTyClient>,
+    state: MQTTyClientState,
+) -> Option<gio::Icon> {
+    client?;
+
+    let icon_name = match state {
+        MQTTyClientState::Connected => "network-transmit-receive-symbolic",
+        MQTTyClientState::Connecting | MQTTyClientState::Reconnecting => "network-idle-symbolic",
+        MQTTyClientState::Disconnected => "network-offline-symbolic",
+    };
+
+    Some(gio::ThemedIcon::new(icon_name).upcast())
+}
+
+fn connection_state_tooltip(client: Option<&MQTTyClient>, state: MQTTyClientState) -> String {
+    if client.is_none() {
+        return String::new();
+    }
+
+    match state {
+        MQTTyClientState::Connected => gettext("Connected"),
+        MQTTyClientState::Connecting => gettext("Connecting…"),
+        MQTTyClientState::Reconnecting => gettext("Reconnecting…"),
+        MQTTyClientState::Disconnected => gettext("Disconnected"),
+    }
+}
+
 glib::wrapper! {
     pub struct MQTTyPublishView(ObjectSubclass<imp::MQTTyPublishView>)
         @extends gtk::Widget, adw::Bin,
         @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
 }
+
+impl MQTTyPublishView {
+    /// Appends a new empty tab and returns its notebook
+    pub fn new_tab(&self) -> MQTTyPublishViewNotebook {
+        let notebook = MQTTyPublishViewNotebook::new();
+        self.bind_property("display_mode", &notebook, "display_mode")
+            .sync_create()
+            .build();
+
+        if let Some(conn) = self.conn_model() {
+            notebook.set_will(conn.will().duplicate());
+            conn.bind_property("keep_alive_interval", &notebook, "keep_alive_interval")
+                .sync_create()
+                .build();
+        }
+
+        let topic_expr = notebook
+            .property_expression_weak("topic")
+            .chain_closure::<String>(glib::closure!(
+                move |_: Option<glib::Object>, topic: String| {
+                    if topic.is_empty() {
+                        gettext("(untitled)")
+                    } else {
+                        topic
+                    }
+                }
+            ));
+
+        let page = self.imp().tab_view.append(&notebook);
+
+        topic_expr.bind(&page, "title", glib::Object::NONE);
+
+        // We create a tooltip based on topic and url values, so that users knows how to
+        // differentiate between similar messages
+        gtk::ClosureExpression::new::<String>(
+            [
+                topic_expr.upcast(),
+                notebook.property_expression_weak("url").upcast(),
+            ],
+            glib::closure!(move |_: Option<glib::Object>, topic: String, url: String| {
+                if url.is_empty() {
+                    topic
+                } else {
+                    [topic, url].join("\r\n")
+                }
+            }),
+        )
+        .bind(&page, "tooltip", glib::Object::NONE);
+
+        // Indicates the state of the pooled connection that is used by the tab
+        let client_expr = notebook.property_expression_weak("client");
+        let state_expr = notebook.property_expression_weak("connection_state");
+
+        gtk::ClosureExpression::new::<Option<gio::Icon>>(
+            [client_expr.clone().upcast(), state_expr.clone().upcast()],
+            glib::closure!(move |_: Option<glib::Object>,
+                                 client: Option<MQTTyClient>,
+                                 state: MQTTyClientState| {
+                connection_state_icon(client.as_ref(), state)
+            }),
+        )
+        .bind(&page, "indicator-icon", glib::Object::NONE);
+
+        gtk::ClosureExpression::new::<String>(
+            [client_expr.upcast(), state_expr.upcast()],
+            glib::closure!(move |_: Option<glib::Object>,
+                                 client: Option<MQTTyClient>,
+                                 state: MQTTyClientState| {
+                connection_state_tooltip(client.as_ref(), state)
+            }),
+        )
+        .bind(&page, "indicator-tooltip", glib::Object::NONE);
+
+        notebook
+    }
+
+    /// Notebooks of the open tabs, in the order they are shown
+    pub fn tabs(&self) -> Vec<MQTTyPublishViewNotebook> {
+        let tab_view = &self.imp().tab_view;
+
+        (0..tab_view.n_pages())
+            .filter_map(|i| tab_view.nth_page(i).child().downcast().ok())
+            .collect()
+    }
+
+    pub fn close_all_tabs(&self) {
+        let tab_view = &self.imp().tab_view;
+
+        for i in (0..tab_view.n_pages()).rev() {
+            tab_view.close_page(&tab_view.nth_page(i));
+        }
+    }
+}
@@ -19,6 +19,8 @@ use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::glib;
 
+use crate::objects::MQTTyTlsSettings;
+
 mod imp {
 
     use super::*;
@@ -32,6 +34,9 @@ mod imp {
 
         #[property(get, set)]
         password: RefCell<String>,
+
+        #[property(get, set)]
+        tls: RefCell<MQTTyTlsSettings>,
     }
 
     #[glib::object_subclass]
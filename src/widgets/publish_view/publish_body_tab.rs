@@ -17,12 +17,16 @@ use std::cell::{Cell, RefCell};
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
-use gtk::glib;
+use formatx::formatx;
+use gettextrs::{gettext, ngettext};
+use gtk::{gio, glib};
 
 use crate::content_type::MQTTyContentType;
 use crate::display_mode::{MQTTyDisplayMode, MQTTyDisplayModeIface};
+use crate::json_schema::{invalid_schema_violation, MQTTyJsonSchemas};
+use crate::protobuf_registry::MQTTyProtobufRegistry;
 use crate::subclass::prelude::*;
-use crate::widgets::MQTTySourceView;
+use crate::widgets::{load_descriptors, MQTTyJsonSchemaRow, MQTTySourceView};
 
 mod imp {
 
@@ -36,15 +40,42 @@ mod imp {
         display_mode: Cell<MQTTyDisplayMode>,
 
         #[property(get, set)]
-        body: RefCell<String>,
+        body: RefCell<glib::Bytes>,
+
+        /// File sent instead of :body, see [`MQTTyPublishViewNotebook::body_file()`]
+        ///
+        /// [`MQTTyPublishViewNotebook::body_file()`]: crate::widgets::MQTTyPublishViewNotebook::body_file
+        #[property(get, set, nullable)]
+        body_file: RefCell<Option<gio::File>>,
+
+        /// Set while the text of the source view is being updated from :body
+        syncing_text: Cell<bool>,
+
+        /// Inline JSON Schema of the tab, if empty the schema attached to :topic is used,
+        /// see [`MQTTyJsonSchemas::publish_schema()`]
+        #[property(get, set)]
+        json_schema: RefCell<String>,
+
+        /// Topic the body is published to, only used for looking up its JSON Schema
+        #[property(get, set)]
+        topic: RefCell<String>,
 
         #[property(get, set, builder(Default::default()))]
         content_type: Cell<MQTTyContentType>,
 
+        /// Full name of the protobuf message type, only used if :content-type is
+        /// [`MQTTyContentType::Protobuf`]
+        #[property(get, set)]
+        message_type: RefCell<String>,
+
         #[template_child]
         source_view: TemplateChild<MQTTySourceView>,
         #[template_child]
         content_type_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        message_type_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        schema_row: TemplateChild<MQTTyJsonSchemaRow>,
     }
 
     impl Default for MQTTyPublishBodyTab {
@@ -53,8 +84,15 @@ mod imp {
                 display_mode: Cell::new(MQTTyDisplayMode::Desktop),
                 source_view: Default::default(),
                 content_type_combo: Default::default(),
-                body: Default::default(),
+                message_type_row: Default::default(),
+                schema_row: Default::default(),
+                body: RefCell::new(glib::Bytes::from_static(b"")),
+                body_file: Default::default(),
+                syncing_text: Default::default(),
+                json_schema: Default::default(),
+                topic: Default::default(),
                 content_type: Default::default(),
+                message_type: Default::default(),
             }
         }
     }
@@ -105,32 +143,86 @@ mod imp {
 
             selected_content_type.bind(&*obj, "content_type", glib::Object::NONE);
 
-            let source_view_is_visible =
-                selected_content_type.chain_closure::<bool>(glib::closure!(
-                    move |_: Option<glib::Object>, content_type: MQTTyContentType| {
-                        match content_type {
-                            MQTTyContentType::None => false,
-                            _ => true,
-                        }
+            // Keeps the combo in sync when the content type is set from outside
+            obj.connect_content_type_notify(|obj| {
+                let content_type = obj.content_type();
+
+                if let Some(idx) = MQTTyContentType::listed()
+                    .iter()
+                    .position(|c| *c == content_type)
+                {
+                    obj.imp().content_type_combo.set_selected(idx as u32);
+                }
+            });
+
+            // The text is kept in sync by hand, since :body is binary and the source view
+            // shows it as UTF-8, invalid sequences being replaced
+            self.source_view.buffer().connect_changed(glib::clone!(
+                #[weak]
+                obj,
+                move |buffer| {
+                    if obj.imp().syncing_text.get() {
+                        return;
                     }
-                ));
 
-            source_view_is_visible.bind(&*self.source_view, "visible", glib::Object::NONE);
+                    let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+                    obj.set_body(glib::Bytes::from_owned(text.as_bytes().to_vec()));
+                }
+            ));
+
+            obj.connect_body_notify(|obj| obj.imp().sync_text());
+
+            // The body is validated live, the schema may come from the topic
+            for property in ["body", "body-file", "content-type", "json-schema", "topic"] {
+                obj.connect_notify_local(Some(property), |obj, _| obj.imp().validate());
+            }
+
+            MQTTyJsonSchemas::get_singleton().connect_closure(
+                "schemas-changed",
+                false,
+                glib::closure_local!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_: MQTTyJsonSchemas| this.validate()
+                ),
+            );
 
             let selected_language = selected_content_type
                 .chain_closure::<Option<sourceview::Language>>(glib::closure!(
                     move |_: Option<glib::Object>, content_type: MQTTyContentType| {
-                        let language_manager = sourceview::LanguageManager::default();
+                        content_type
+                            .language_id()
+                            .and_then(|id| sourceview::LanguageManager::default().language(id))
+                    }
+                ));
+
+            selected_language.bind(&self.source_view.buffer(), "language", glib::Object::NONE);
+
+            let message_types = MQTTyProtobufRegistry::get_singleton().message_types();
+
+            self.message_type_row.set_model(Some(&message_types));
 
-                        match content_type {
-                            MQTTyContentType::None | MQTTyContentType::Raw => None,
-                            MQTTyContentType::Json => language_manager.language("json"),
-                            MQTTyContentType::Xml => language_manager.language("xml"),
+            // The message type is kept even if it's not registered, so that it's not lost
+            // when descriptors are loaded after restoring a workspace
+            self.message_type_row
+                .connect_selected_item_notify(glib::clone!(
+                    #[weak]
+                    obj,
+                    move |row| {
+                        if let Some(item) = row.selected_item().and_downcast::<gtk::StringObject>()
+                        {
+                            obj.set_message_type(item.string());
                         }
                     }
                 ));
 
-            selected_language.bind(&self.source_view.buffer(), "language", glib::Object::NONE);
+            obj.connect_message_type_notify(|obj| obj.imp().select_message_type());
+
+            message_types.connect_items_changed(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_, _, _, _| this.select_message_type()
+            ));
         }
     }
     impl WidgetImpl for MQTTyPublishBodyTab {}
@@ -138,8 +230,184 @@ mod imp {
 
     impl MQTTyDisplayModeIfaceImpl for MQTTyPublishBodyTab {}
 
+    impl MQTTyPublishBodyTab {
+        fn sync_text(&self) {
+            let body = self.body.borrow().clone();
+            let text = String::from_utf8_lossy(&body);
+
+            let buffer = self.source_view.buffer();
+
+            if buffer
+                .text(&buffer.start_iter(), &buffer.end_iter(), false)
+                .as_str()
+                == text
+            {
+                return;
+            }
+
+            self.syncing_text.set(true);
+            buffer.set_text(&text);
+            self.syncing_text.set(false);
+        }
+
+        /// Underlines the parts of the body that don't conform to its JSON Schema
+        fn validate(&self) {
+            let obj = self.obj();
+
+            let schema = if self.is_json(obj.content_type(), obj.body_file()) {
+                MQTTyJsonSchemas::get_singleton().publish_schema(&obj.json_schema(), &obj.topic())
+            } else {
+                Ok(None)
+            };
+
+            let schema = match schema {
+                Ok(Some(schema)) => schema,
+                Ok(None) => {
+                    self.source_view.set_diagnostics(&[]);
+                    self.schema_row.set_subtitle("");
+                    return;
+                }
+                Err(e) => {
+                    self.source_view.set_diagnostics(&[]);
+                    self.schema_row.set_subtitle(&invalid_schema_violation(&e));
+                    return;
+                }
+            };
+
+            let violations = schema.validate(&String::from_utf8_lossy(&obj.body()));
+
+            // Violations that can't be located are shown at the start of the body
+            self.source_view.set_diagnostics(
+                &violations
+                    .iter()
+                    .map(|v| (v.range.clone().unwrap_or(0..0), v.message.clone()))
+                    .collect::<Vec<_>>(),
+            );
+
+            let n = violations.len() as u32;
+
+            self.schema_row.set_subtitle(&if n == 0 {
+                gettext("The body conforms to the schema")
+            } else {
+                formatx!(ngettext("{} violation", "{} violations", n), n).unwrap_or_default()
+            });
+        }
+
+        fn select_message_type(&self) {
+            let message_type = self.message_type.borrow().clone();
+
+            let position = MQTTyProtobufRegistry::get_singleton()
+                .message_types()
+                .iter::<gtk::StringObject>()
+                .flatten()
+                .position(|item| item.string() == message_type);
+
+            match position {
+                Some(position) => self.message_type_row.set_selected(position as u32),
+                // Nothing chosen yet, so the type shown in the row is the one used
+                None if message_type.is_empty() => {
+                    if let Some(item) = self
+                        .message_type_row
+                        .selected_item()
+                        .and_downcast::<gtk::StringObject>()
+                    {
+                        self.obj().set_message_type(item.string());
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
     #[gtk::template_callbacks]
     impl MQTTyPublishBodyTab {
+        #[template_callback]
+        fn is_protobuf(&self, content_type: MQTTyContentType) -> bool {
+            matches!(content_type, MQTTyContentType::Protobuf)
+        }
+
+        /// JSON Schemas only apply to bodies edited as JSON
+        #[template_callback]
+        fn is_json(&self, content_type: MQTTyContentType, body_file: Option<gio::File>) -> bool {
+            content_type.language_id() == Some("json") && body_file.is_none()
+        }
+
+        #[template_callback]
+        fn is_content_type_set(&self, content_type: MQTTyContentType) -> bool {
+            content_type != MQTTyContentType::None
+        }
+
+        #[template_callback]
+        fn is_body_file_set(&self, body_file: Option<gio::File>) -> bool {
+            body_file.is_some()
+        }
+
+        #[template_callback]
+        fn is_editor_visible(
+            &self,
+            content_type: MQTTyContentType,
+            body_file: Option<gio::File>,
+        ) -> bool {
+            content_type != MQTTyContentType::None && body_file.is_none()
+        }
+
+        /// Raw bodies are edited in hexadecimal, the rest as text
+        #[template_callback]
+        fn editor_name(&self, content_type: MQTTyContentType) -> &'static str {
+            match content_type {
+                MQTTyContentType::Raw => "hex",
+                _ => "text",
+            }
+        }
+
+        #[template_callback]
+        fn body_file_name(&self, body_file: Option<gio::File>) -> String {
+            match body_file {
+                Some(file) => file.parse_name().into(),
+                None => gettext("None"),
+            }
+        }
+
+        #[template_callback]
+        fn on_clear_body_file(&self) {
+            self.obj().set_body_file(gio::File::NONE);
+        }
+
+        #[template_callback]
+        fn on_select_body_file(&self) {
+            let obj = self.obj();
+
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    let dialog = gtk::FileDialog::builder()
+                        .title(gettext("Select Body File"))
+                        .modal(true)
+                        .build();
+
+                    let window = obj.root().and_downcast::<gtk::Window>();
+
+                    if let Ok(file) = dialog.open_future(window.as_ref()).await {
+                        obj.set_body_file(Some(file));
+                    }
+                }
+            ));
+        }
+
+        #[template_callback]
+        fn on_load_descriptors(&self) {
+            let obj = self.obj();
+
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    load_descriptors(&obj).await;
+                }
+            ));
+        }
+
         #[template_callback]
         fn display_mode_to_orientation(&self, display_mode: MQTTyDisplayMode) -> gtk::Orientation {
             match display_mode {
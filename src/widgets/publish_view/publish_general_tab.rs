@@ -13,12 +13,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
+use gettextrs::gettext;
 use gtk::glib;
 
+use crate::client::{MQTTyBrokerCapabilities, MQTTyUrlScheme};
+
 mod imp {
 
     use super::*;
@@ -39,6 +42,42 @@ mod imp {
         #[property(get, set)]
         password: RefCell<String>,
 
+        #[property(get, set)]
+        auto_reconnect: Cell<bool>,
+
+        #[property(get, set)]
+        reconnect_max_retries: Cell<u32>,
+
+        #[property(get, set)]
+        ws_path: RefCell<String>,
+
+        #[property(get, set)]
+        ws_subprotocol: RefCell<String>,
+
+        #[property(get, set)]
+        client_id: RefCell<String>,
+
+        #[property(get, set)]
+        clean_start: Cell<bool>,
+
+        #[property(get, set)]
+        session_expiry_interval: Cell<u32>,
+
+        /// Session present flag received from the broker in the last connection
+        #[property(get, set)]
+        session_present: Cell<bool>,
+
+        /// Capabilities of the broker this tab is connected to, if any
+        #[property(get, set, nullable)]
+        capabilities: RefCell<Option<MQTTyBrokerCapabilities>>,
+
+        /// Whether the URL scheme is ws:// or wss://
+        #[property(get)]
+        websocket: Cell<bool>,
+
+        #[template_child]
+        url_row: TemplateChild<adw::EntryRow>,
+
         #[template_child]
         mqtt_3_button: TemplateChild<gtk::CheckButton>,
         #[template_child]
@@ -81,6 +120,25 @@ mod imp {
             self.qos_0_button.set_action_target(Some("0"));
             self.qos_1_button.set_action_target(Some("1"));
             self.qos_2_button.set_action_target(Some("2"));
+
+            self.obj().connect_url_notify(|obj| {
+                let url = obj.url();
+                let scheme = MQTTyUrlScheme::parse(&url);
+
+                let private = obj.imp();
+
+                // Empty URLs are not marked as errors, the user didn't type anything yet
+                if scheme.is_none() && !url.is_empty() {
+                    private.url_row.add_css_class("error");
+                } else {
+                    private.url_row.remove_css_class("error");
+                }
+
+                let websocket = scheme.is_some_and(|s| s.is_websocket());
+                if private.websocket.replace(websocket) != websocket {
+                    obj.notify_websocket();
+                }
+            });
         }
     }
     impl WidgetImpl for MQTTyPublishGeneralTab {}
@@ -92,6 +150,15 @@ mod imp {
         fn or(&self, a: bool, b: bool) -> bool {
             a || b
         }
+
+        #[template_callback]
+        fn session_present_to_string(&self, session_present: bool) -> String {
+            if session_present {
+                gettext("The broker resumed a previous session")
+            } else {
+                gettext("No previous session was resumed")
+            }
+        }
     }
 }
 
@@ -0,0 +1,107 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::hex;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/publish_view/publish_properties_tab.ui")]
+    #[properties(wrapper_type = super::MQTTyPublishPropertiesTab)]
+    pub struct MQTTyPublishPropertiesTab {
+        #[property(get, set)]
+        payload_utf8: Cell<bool>,
+
+        #[property(get, set)]
+        message_expiry_interval: Cell<u32>,
+
+        #[property(get, set)]
+        topic_alias: Cell<u32>,
+
+        #[property(get, set)]
+        response_topic: RefCell<String>,
+
+        #[property(get, set)]
+        correlation_data: RefCell<String>,
+
+        #[property(get, set)]
+        correlation_data_hex: Cell<bool>,
+
+        #[property(get, set)]
+        request_mode: Cell<bool>,
+
+        #[property(get, set)]
+        request_timeout: Cell<u32>,
+
+        #[template_child]
+        correlation_data_row: TemplateChild<adw::EntryRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyPublishPropertiesTab {
+        const NAME: &'static str = "MQTTyPublishPropertiesTab";
+
+        type Type = super::MQTTyPublishPropertiesTab;
+
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyPublishPropertiesTab {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            let validate = |obj: &super::MQTTyPublishPropertiesTab| {
+                let row = &obj.imp().correlation_data_row;
+
+                if obj.correlation_data_hex() && hex::decode(&obj.correlation_data()).is_none() {
+                    row.add_css_class("error");
+                } else {
+                    row.remove_css_class("error");
+                }
+            };
+
+            obj.connect_correlation_data_notify(validate);
+            obj.connect_correlation_data_hex_notify(validate);
+        }
+    }
+    impl WidgetImpl for MQTTyPublishPropertiesTab {}
+    impl BinImpl for MQTTyPublishPropertiesTab {}
+}
+
+glib::wrapper! {
+    /// Editor of the MQTT v5 PUBLISH properties that don't have a dedicated tab
+    pub struct MQTTyPublishPropertiesTab(ObjectSubclass<imp::MQTTyPublishPropertiesTab>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
@@ -0,0 +1,135 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+use std::time::Duration;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
+use gtk::glib;
+use sourceview::prelude::*;
+
+use crate::client::MQTTyClientMessage;
+use crate::hex;
+use crate::payload_decoder::MQTTyPayloadDecoder;
+use crate::widgets::MQTTySourceView;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/publish_view/publish_response_tab.ui")]
+    #[properties(wrapper_type = super::MQTTyPublishResponseTab)]
+    pub struct MQTTyPublishResponseTab {
+        #[property(get, nullable)]
+        response: RefCell<Option<MQTTyClientMessage>>,
+
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        topic_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        latency_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        content_type_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        correlation_data_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        source_view: TemplateChild<MQTTySourceView>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyPublishResponseTab {
+        const NAME: &'static str = "MQTTyPublishResponseTab";
+
+        type Type = super::MQTTyPublishResponseTab;
+
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyPublishResponseTab {}
+    impl WidgetImpl for MQTTyPublishResponseTab {}
+    impl BinImpl for MQTTyPublishResponseTab {}
+
+    impl MQTTyPublishResponseTab {
+        pub fn set_response(&self, response: Option<&MQTTyClientMessage>, latency: Duration) {
+            self.response.replace(response.cloned());
+
+            let Some(response) = response else {
+                self.stack.set_visible_child_name("empty");
+                self.obj().notify_response();
+                return;
+            };
+
+            self.topic_row.set_subtitle(&response.topic());
+            self.latency_row
+                .set_subtitle(&formatx!(gettext("{} ms"), latency.as_millis()).unwrap_or_default());
+            self.content_type_row
+                .set_subtitle(&response.content_type().unwrap_or_default());
+            self.correlation_data_row.set_subtitle(
+                &response
+                    .correlation_data()
+                    .map(|data| hex::encode(&data))
+                    .unwrap_or_default(),
+            );
+
+            let decoder = MQTTyPayloadDecoder::get_singleton();
+            let decoded = decoder.decode_message(response);
+
+            self.source_view
+                .buffer()
+                .set_text(&decoder.message_text(response));
+
+            let language = decoded
+                .format
+                .language_id()
+                .and_then(|id| sourceview::LanguageManager::default().language(id));
+            self.source_view
+                .buffer()
+                .downcast::<sourceview::Buffer>()
+                .unwrap()
+                .set_language(language.as_ref());
+
+            self.stack.set_visible_child_name("response");
+
+            self.obj().notify_response();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Shows the response received by a publish tab in request mode
+    pub struct MQTTyPublishResponseTab(ObjectSubclass<imp::MQTTyPublishResponseTab>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl MQTTyPublishResponseTab {
+    pub fn set_response(&self, response: Option<&MQTTyClientMessage>, latency: Duration) {
+        self.imp().set_response(response, latency);
+    }
+}
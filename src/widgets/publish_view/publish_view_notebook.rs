@@ -13,17 +13,34 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::cell::{Cell, OnceCell, RefCell};
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
 use gtk::{gio, glib};
 
-use crate::client::{MQTTyClient, MQTTyClientMessage, MQTTyClientQos, MQTTyClientVersion};
+use crate::application::MQTTyApplication;
+use crate::client::{
+    client_id_from_template, websocket_url, MQTTyClient, MQTTyClientError, MQTTyClientMessage,
+    MQTTyClientQos, MQTTyClientState, MQTTyClientVersion, MQTTyUrlScheme,
+};
 use crate::content_type::MQTTyContentType;
 use crate::display_mode::{MQTTyDisplayMode, MQTTyDisplayModeIface};
+use crate::hex;
+use crate::json_schema::{invalid_schema_violation, MQTTyJsonSchemas};
+use crate::objects::{MQTTyKeyValue, MQTTyTlsSettings, MQTTyWillSettings};
 use crate::subclass::prelude::*;
-use crate::widgets::MQTTyPublishUserPropsTab;
+use crate::widgets::{MQTTyPublishResponseTab, MQTTyPublishUserPropsTab, MQTTyPublishWillTab};
+
+/// Largest packet allowed by the MQTT specification, used when the broker doesn't
+/// announce a maximum packet size
+const MQTT_MAXIMUM_PACKET_SIZE: usize = 268_435_460;
+
+/// Size of the chunks in which body files are read
+const BODY_FILE_CHUNK_SIZE: usize = 64 * 1024;
 
 mod imp {
 
@@ -33,7 +50,39 @@ mod imp {
     #[template(resource = "/io/github/otaxhu/MQTTy/ui/publish_view/publish_view_notebook.ui")]
     #[properties(wrapper_type = super::MQTTyPublishViewNotebook)]
     pub struct MQTTyPublishViewNotebook {
-        pub client: OnceCell<MQTTyClient>,
+        /// Pooled client used in the latest send, it's reset when any of the connection
+        /// options changes
+        #[property(get, set = Self::set_client, nullable)]
+        client: RefCell<Option<MQTTyClient>>,
+
+        client_state_handler: RefCell<Option<glib::SignalHandlerId>>,
+
+        /// Connection state of the pooled client
+        #[property(get, builder(MQTTyClientState::Disconnected))]
+        connection_state: Cell<MQTTyClientState>,
+
+        #[property(get, set)]
+        auto_reconnect: Cell<bool>,
+
+        #[property(get, set)]
+        reconnect_max_retries: Cell<u32>,
+
+        /// Client ID template, see [`client_id_from_template()`]
+        #[property(get, set)]
+        client_id: RefCell<String>,
+
+        /// Client ID expanded from the :client-id template, it's kept until the template
+        /// changes so that the broker sees the same client ID between connections
+        pub resolved_client_id: RefCell<Option<String>>,
+
+        #[property(get, set)]
+        clean_start: Cell<bool>,
+
+        #[property(get, set)]
+        session_expiry_interval: Cell<u32>,
+
+        #[property(get, set, default = 60)]
+        keep_alive_interval: Cell<u32>,
 
         #[property(get, set, override_interface = MQTTyDisplayModeIface)]
         display_mode: Cell<MQTTyDisplayMode>,
@@ -47,26 +96,101 @@ mod imp {
         #[property(get, set)]
         url: RefCell<String>,
 
+        /// Overrides the path of WebSocket URLs when not empty
+        #[property(get, set)]
+        ws_path: RefCell<String>,
+
+        /// Extra WebSocket subprotocol requested in the handshake when not empty
+        #[property(get, set)]
+        ws_subprotocol: RefCell<String>,
+
         #[property(get, set, builder(Default::default()))]
         qos: Cell<MQTTyClientQos>,
 
         #[property(get, set)]
-        body: RefCell<String>,
+        body: RefCell<glib::Bytes>,
+
+        /// File whose contents are sent instead of :body, it's read when sending so
+        /// that it can be changed between messages
+        #[property(get, set, nullable)]
+        body_file: RefCell<Option<gio::File>>,
 
         #[property(get, set, builder(Default::default()))]
         content_type: Cell<MQTTyContentType>,
 
+        /// Full name of the protobuf message type, only used if :content-type is
+        /// [`MQTTyContentType::Protobuf`]
+        #[property(get, set)]
+        message_type: RefCell<String>,
+
+        /// Inline JSON Schema of the body, see [`MQTTyJsonSchemas::publish_schema()`]
+        #[property(get, set)]
+        json_schema: RefCell<String>,
+
         #[property(get, set)]
         username: RefCell<String>,
 
         #[property(get, set)]
         password: RefCell<String>,
 
+        #[property(get, set)]
+        tls: RefCell<MQTTyTlsSettings>,
+
+        #[property(get, set)]
+        will: RefCell<MQTTyWillSettings>,
+
+        // MQTT v5 PUBLISH properties
+        #[property(get, set)]
+        payload_utf8: Cell<bool>,
+
+        #[property(get, set)]
+        message_expiry_interval: Cell<u32>,
+
+        #[property(get, set)]
+        topic_alias: Cell<u32>,
+
+        #[property(get, set)]
+        response_topic: RefCell<String>,
+
+        #[property(get, set)]
+        correlation_data: RefCell<String>,
+
+        /// Whether :correlation-data is hexadecimal text instead of UTF-8 text
+        #[property(get, set)]
+        correlation_data_hex: Cell<bool>,
+
+        /// If enabled, send() waits for a response, see MQTTyClient::request()
+        #[property(get, set)]
+        request_mode: Cell<bool>,
+
+        /// Seconds to wait for a response in request mode
+        #[property(get, set, minimum = 1)]
+        request_timeout: Cell<u32>,
+
+        /// Round-trip latency in milliseconds of the last response
+        #[property(get)]
+        pub response_latency: Cell<u64>,
+
         #[template_child]
         pub user_properties_tab: TemplateChild<MQTTyPublishUserPropsTab>,
 
         #[template_child]
         user_properties_stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        properties_stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub response_tab: TemplateChild<MQTTyPublishResponseTab>,
+
+        #[template_child]
+        pub will_tab: TemplateChild<MQTTyPublishWillTab>,
+
+        #[template_child]
+        pub http_headers_tab: TemplateChild<MQTTyPublishUserPropsTab>,
+
+        #[template_child]
+        http_headers_stack: TemplateChild<gtk::Stack>,
     }
 
     impl Default for MQTTyPublishViewNotebook {
@@ -76,14 +200,44 @@ mod imp {
                 mqtt_version: Default::default(),
                 topic: Default::default(),
                 url: Default::default(),
+                ws_path: Default::default(),
+                ws_subprotocol: Default::default(),
+                will: Default::default(),
+                will_tab: Default::default(),
                 qos: Default::default(),
                 client: Default::default(),
-                body: Default::default(),
+                client_state_handler: Default::default(),
+                connection_state: Default::default(),
+                auto_reconnect: Default::default(),
+                reconnect_max_retries: Cell::new(5),
+                client_id: Default::default(),
+                resolved_client_id: Default::default(),
+                clean_start: Cell::new(true),
+                session_expiry_interval: Default::default(),
+                keep_alive_interval: Cell::new(60),
+                body: RefCell::new(glib::Bytes::from_static(b"")),
+                body_file: Default::default(),
                 content_type: Default::default(),
+                message_type: Default::default(),
+                json_schema: Default::default(),
                 user_properties_tab: Default::default(),
                 username: Default::default(),
                 password: Default::default(),
+                tls: Default::default(),
                 user_properties_stack: Default::default(),
+                properties_stack: Default::default(),
+                payload_utf8: Default::default(),
+                message_expiry_interval: Default::default(),
+                topic_alias: Default::default(),
+                response_topic: Default::default(),
+                correlation_data: Default::default(),
+                correlation_data_hex: Default::default(),
+                request_mode: Default::default(),
+                request_timeout: Cell::new(10),
+                response_latency: Default::default(),
+                response_tab: Default::default(),
+                http_headers_tab: Default::default(),
+                http_headers_stack: Default::default(),
             }
         }
     }
@@ -181,11 +335,75 @@ mod imp {
                 .transform_to(|_, state: glib::Variant| state.str().map(String::from))
                 .sync_create()
                 .build();
+
+            mqtt_version_state
+                .bind_property("state", &*self.properties_stack, "visible-child-name")
+                .transform_to(|_, state: glib::Variant| state.str().map(String::from))
+                .sync_create()
+                .build();
+
+            obj.bind_property("url", &*self.http_headers_stack, "visible-child-name")
+                .transform_to(|_, url: String| {
+                    let websocket = MQTTyUrlScheme::parse(&url).is_some_and(|s| s.is_websocket());
+
+                    Some(if websocket { "websocket" } else { "tcp" })
+                })
+                .sync_create()
+                .build();
+
+            // The pooled client no longer matches the connection options
+            let reset_client = |obj: &super::MQTTyPublishViewNotebook| obj.imp().set_client(None);
+
+            obj.connect_url_notify(reset_client);
+            obj.connect_ws_path_notify(reset_client);
+            obj.connect_ws_subprotocol_notify(reset_client);
+            obj.connect_clean_start_notify(reset_client);
+            obj.connect_session_expiry_interval_notify(reset_client);
+            obj.connect_keep_alive_interval_notify(reset_client);
+            obj.connect_client_id_notify(|obj| {
+                obj.imp().resolved_client_id.take();
+                obj.imp().set_client(None);
+            });
+            obj.connect_mqtt_version_notify(reset_client);
+            obj.connect_username_notify(reset_client);
+            obj.connect_password_notify(reset_client);
         }
     }
     impl WidgetImpl for MQTTyPublishViewNotebook {}
     impl BinImpl for MQTTyPublishViewNotebook {}
     impl MQTTyDisplayModeIfaceImpl for MQTTyPublishViewNotebook {}
+
+    impl MQTTyPublishViewNotebook {
+        pub fn set_client(&self, client: Option<MQTTyClient>) {
+            let obj = self.obj();
+
+            if let Some(old_client) = self.client.replace(client.clone()) {
+                if let Some(handler) = self.client_state_handler.take() {
+                    old_client.disconnect(handler);
+                }
+            }
+
+            if let Some(client) = &client {
+                let handler = client.connect_state_notify(glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |client| this.set_connection_state(client.state())
+                ));
+
+                self.client_state_handler.replace(Some(handler));
+            }
+
+            self.set_connection_state(client.map_or(MQTTyClientState::Disconnected, |c| c.state()));
+
+            obj.notify_client();
+        }
+
+        fn set_connection_state(&self, state: MQTTyClientState) {
+            if self.connection_state.replace(state) != state {
+                self.obj().notify_connection_state();
+            }
+        }
+    }
 }
 
 glib::wrapper! {
@@ -199,25 +417,119 @@ impl MQTTyPublishViewNotebook {
         glib::Object::builder().build()
     }
 
-    pub async fn send(&self) -> Result<(), String> {
+    /// User properties of the message, including the inactive ones
+    pub fn user_properties(&self) -> Vec<MQTTyKeyValue> {
+        self.imp().user_properties_tab.entries()
+    }
+
+    pub fn set_user_properties(&self, user_properties: &[MQTTyKeyValue]) {
+        self.imp().user_properties_tab.set_entries(user_properties);
+    }
+
+    /// Payload to be sent, files and raw bodies are sent as they are, the rest is
+    /// encoded with the content type. Files are read in chunks and must not be larger
+    /// than `maximum_size`
+    async fn payload(&self, maximum_size: usize) -> Result<Vec<u8>, MQTTyClientError> {
+        if let Some(file) = self.body_file() {
+            return read_body_file(&file, maximum_size).await;
+        }
+
+        let body = self.body();
+
+        match self.content_type() {
+            MQTTyContentType::Raw => Ok(body.to_vec()),
+            content_type => {
+                let text = std::str::from_utf8(&body).map_err(|_| {
+                    MQTTyClientError::InvalidPayload(gettext("the body is not valid UTF-8"))
+                })?;
+
+                content_type
+                    .encode(text, &self.message_type())
+                    .map_err(MQTTyClientError::InvalidPayload)
+            }
+        }
+    }
+
+    pub async fn send(&self) -> Result<(), MQTTyClientError> {
         let mqtt_version = self.mqtt_version();
 
+        let app = MQTTyApplication::get_singleton();
+
+        let url = self.url();
+
+        let Some(scheme) = MQTTyUrlScheme::parse(&url) else {
+            return Err(MQTTyClientError::InvalidUrl(url));
+        };
+
+        let url = if scheme.is_websocket() {
+            websocket_url(&url, &self.ws_path())
+        } else {
+            url
+        };
+
+        let schema_violations = self.schema_violations();
+
+        if !schema_violations.is_empty() && !self.confirm_violations(&schema_violations).await {
+            return Err(MQTTyClientError::Cancelled);
+        }
+
+        self.imp().will_tab.apply_user_properties();
+
+        let client_id = self
+            .imp()
+            .resolved_client_id
+            .borrow_mut()
+            .get_or_insert_with(|| client_id_from_template(&self.client_id()))
+            .clone();
+
         let client = MQTTyClient::new(
-            &self.url(),
+            &url,
             mqtt_version,
             &self.username(),
             &self.password(),
+            &client_id,
+            &self.tls().duplicate(),
+            &self.will().duplicate(),
         );
 
-        client.connect_client().await?;
+        client.set_clean_start(self.clean_start());
+        client.set_session_expiry_interval(self.session_expiry_interval());
+        client.set_keep_alive_interval(self.keep_alive_interval());
+
+        if scheme.is_websocket() {
+            client.set_ws_subprotocol(self.ws_subprotocol());
+            client.set_http_headers(
+                self.imp()
+                    .http_headers_tab
+                    .entries()
+                    .iter()
+                    .filter(|i| i.active())
+                    .map(|i| (i.key(), i.value()))
+                    .collect::<Vec<_>>()
+                    .as_ref(),
+            );
+        }
+
+        let client = app.pooled_client(&client).await?;
+
+        self.imp().set_client(Some(client.clone()));
+
+        // The reconnection policy is not part of the pooled connection options, so the
+        // latest tab that used the client decides it
+        client.set_auto_reconnect(self.auto_reconnect());
+        client.set_reconnect_max_retries(self.reconnect_max_retries());
+
+        let maximum_size = client
+            .capabilities()
+            .map(|c| c.maximum_packet_size() as usize)
+            .filter(|size| *size > 0)
+            .unwrap_or(MQTT_MAXIMUM_PACKET_SIZE);
 
         let msg = MQTTyClientMessage::new();
 
         msg.set_topic(self.topic());
         msg.set_qos(self.qos());
-        if self.content_type() != MQTTyContentType::None {
-            msg.set_body(self.body().as_ref());
-        }
+        msg.set_body_vec(self.payload(maximum_size).await?);
         msg.set_mqtt_version(mqtt_version);
 
         // Specific to MQTT v5
@@ -232,8 +544,187 @@ impl MQTTyPublishViewNotebook {
                     .collect::<Vec<_>>()
                     .as_ref(),
             );
+
+            msg.set_payload_utf8(self.payload_utf8());
+            msg.set_message_expiry_interval(self.message_expiry_interval());
+            msg.set_topic_alias(self.topic_alias());
+
+            let response_topic = self.response_topic();
+            if !response_topic.is_empty() {
+                msg.set_response_topic(Some(response_topic));
+            }
+
+            let correlation_data = self.correlation_data();
+            if !correlation_data.is_empty() {
+                let correlation_data = if self.correlation_data_hex() {
+                    hex::decode(&correlation_data).ok_or_else(|| {
+                        MQTTyClientError::Other(gettext(
+                            "Correlation data is not valid hexadecimal",
+                        ))
+                    })?
+                } else {
+                    correlation_data.into_bytes()
+                };
+
+                msg.set_correlation_data(Some(&correlation_data));
+            }
+        }
+
+        if let Some(capabilities) = client.capabilities() {
+            let violations = capabilities.violations(&msg);
+
+            if !violations.is_empty() && !self.confirm_violations(&violations).await {
+                return Err(MQTTyClientError::Cancelled);
+            }
+        }
+
+        let store = app.message_store(&self.url());
+
+        // Bodies sent from a file can be as large as the broker allows, they are not
+        // kept in memory nor in the message store once they're sent
+        let body_file = self.body_file().is_some();
+
+        if !self.request_mode() {
+            client.publish(&msg).await?;
+
+            if body_file {
+                msg.set_body_vec(vec![]);
+            }
+
+            store.push(&msg);
+            return Ok(());
+        }
+
+        let response = client
+            .request(&msg, Duration::from_secs(self.request_timeout() as u64))
+            .await?;
+
+        if body_file {
+            response.request.set_body_vec(vec![]);
+        }
+
+        store.push(&response.request);
+        store.push(&response.message);
+
+        let private = self.imp();
+
+        private
+            .response_tab
+            .set_response(Some(&response.message), response.latency);
+
+        private
+            .response_latency
+            .set(response.latency.as_millis() as u64);
+        self.notify_response_latency();
+
+        Ok(())
+    }
+
+    /// Violations of the JSON Schema that applies to the body, bodies sent from a file
+    /// are not validated
+    fn schema_violations(&self) -> Vec<String> {
+        if self.content_type().language_id() != Some("json") || self.body_file().is_some() {
+            return vec![];
+        }
+
+        match MQTTyJsonSchemas::get_singleton().publish_schema(&self.json_schema(), &self.topic()) {
+            Ok(Some(schema)) => schema
+                .validate(&String::from_utf8_lossy(&self.body()))
+                .into_iter()
+                .map(|violation| violation.message)
+                .collect(),
+            Ok(None) => vec![],
+            Err(e) => vec![invalid_schema_violation(&e)],
+        }
+    }
+
+    /// Asks the user whether to publish a message that breaks the broker limits or the
+    /// JSON Schema of the body, returns true if the user wants to publish it anyway
+    async fn confirm_violations(&self, violations: &[String]) -> bool {
+        const RESPONSE_PUBLISH: &str = "publish";
+
+        let dialog = adw::AlertDialog::builder()
+            .heading(gettext("Publish anyway?"))
+            .body(violations.join("\n"))
+            .close_response("cancel")
+            .default_response("cancel")
+            .build();
+
+        dialog.add_response("cancel", &gettext("Cancel"));
+        dialog.add_response(RESPONSE_PUBLISH, &gettext("Publish Anyway"));
+        dialog.set_response_appearance(RESPONSE_PUBLISH, adw::ResponseAppearance::Destructive);
+
+        dialog.choose_future(self).await == RESPONSE_PUBLISH
+    }
+}
+
+/// Reads `file` in chunks, failing as soon as it's known to be larger than `maximum_size`
+async fn read_body_file(
+    file: &gio::File,
+    maximum_size: usize,
+) -> Result<Vec<u8>, MQTTyClientError> {
+    let read_error = |e: glib::Error| {
+        MQTTyClientError::Other(
+            formatx!(
+                gettext("Couldn't read {}: {}"),
+                file.parse_name(),
+                e.message()
+            )
+            .unwrap_or_default(),
+        )
+    };
+
+    let too_large = |size: usize| {
+        MQTTyClientError::InvalidPayload(
+            formatx!(
+                gettext("{} takes {} bytes, but the broker accepts messages of up to {} bytes"),
+                file.parse_name(),
+                size,
+                maximum_size
+            )
+            .unwrap_or_default(),
+        )
+    };
+
+    let info = file
+        .query_info_future(
+            gio::FILE_ATTRIBUTE_STANDARD_SIZE,
+            gio::FileQueryInfoFlags::NONE,
+            glib::Priority::DEFAULT,
+        )
+        .await
+        .map_err(read_error)?;
+
+    let size = info.size().max(0) as usize;
+
+    if size > maximum_size {
+        return Err(too_large(size));
+    }
+
+    let stream = file
+        .read_future(glib::Priority::DEFAULT)
+        .await
+        .map_err(read_error)?;
+
+    let mut payload = Vec::with_capacity(size);
+
+    loop {
+        let chunk = stream
+            .read_bytes_future(BODY_FILE_CHUNK_SIZE, glib::Priority::DEFAULT)
+            .await
+            .map_err(read_error)?;
+
+        if chunk.is_empty() {
+            break;
         }
 
-        client.publish(&msg).await
+        // The file may have grown since its size was queried
+        if payload.len() + chunk.len() > maximum_size {
+            return Err(too_large(payload.len() + chunk.len()));
+        }
+
+        payload.extend_from_slice(&chunk);
     }
+
+    Ok(payload)
 }
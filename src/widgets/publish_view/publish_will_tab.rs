@@ -0,0 +1,229 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::client::{MQTTyClientQos, MQTTyClientVersion};
+use crate::display_mode::{MQTTyDisplayMode, MQTTyDisplayModeIface};
+use crate::objects::{MQTTyKeyValue, MQTTyWillSettings};
+use crate::subclass::prelude::*;
+
+use super::{MQTTyPublishBodyTab, MQTTyPublishUserPropsTab};
+
+mod imp {
+
+    use super::*;
+
+    #[derive(gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/publish_view/publish_will_tab.ui")]
+    #[properties(wrapper_type = super::MQTTyPublishWillTab)]
+    pub struct MQTTyPublishWillTab {
+        #[property(get, set, override_interface = MQTTyDisplayModeIface)]
+        display_mode: Cell<MQTTyDisplayMode>,
+
+        #[property(get, set, builder(Default::default()))]
+        mqtt_version: Cell<MQTTyClientVersion>,
+
+        #[property(get, set = Self::set_will)]
+        will: RefCell<MQTTyWillSettings>,
+
+        /// Bindings between the current will object and the rows, they are removed
+        /// when the will object changes
+        bindings: RefCell<Vec<glib::Binding>>,
+
+        #[template_child]
+        enabled_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        topic_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        qos_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        retain_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        delay_interval_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        message_expiry_interval_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        body_tab: TemplateChild<MQTTyPublishBodyTab>,
+        #[template_child]
+        user_properties_tab: TemplateChild<MQTTyPublishUserPropsTab>,
+    }
+
+    impl Default for MQTTyPublishWillTab {
+        fn default() -> Self {
+            Self {
+                display_mode: Cell::new(MQTTyDisplayMode::Desktop),
+                mqtt_version: Default::default(),
+                will: Default::default(),
+                bindings: Default::default(),
+                enabled_row: Default::default(),
+                topic_row: Default::default(),
+                qos_row: Default::default(),
+                retain_row: Default::default(),
+                delay_interval_row: Default::default(),
+                message_expiry_interval_row: Default::default(),
+                body_tab: Default::default(),
+                user_properties_tab: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyPublishWillTab {
+        const NAME: &'static str = "MQTTyPublishWillTab";
+
+        type Type = super::MQTTyPublishWillTab;
+
+        type ParentType = adw::Bin;
+
+        type Interfaces = (MQTTyDisplayModeIface,);
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyPublishWillTab {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.bind_will();
+        }
+    }
+    impl WidgetImpl for MQTTyPublishWillTab {}
+    impl BinImpl for MQTTyPublishWillTab {}
+
+    impl MQTTyDisplayModeIfaceImpl for MQTTyPublishWillTab {}
+
+    #[gtk::template_callbacks]
+    impl MQTTyPublishWillTab {
+        #[template_callback]
+        fn is_mqtt_5(&self, mqtt_version: MQTTyClientVersion) -> bool {
+            mqtt_version == MQTTyClientVersion::V5
+        }
+    }
+
+    impl MQTTyPublishWillTab {
+        fn set_will(&self, will: MQTTyWillSettings) {
+            self.will.replace(will);
+            self.bind_will();
+        }
+
+        fn bind_will(&self) {
+            for binding in self.bindings.take() {
+                binding.unbind();
+            }
+
+            let will = self.will.borrow();
+
+            self.user_properties_tab.set_entries(
+                &will
+                    .user_properties()
+                    .iter()
+                    .map(|(key, value)| MQTTyKeyValue::new(key, value, true))
+                    .collect::<Vec<_>>(),
+            );
+
+            let bindings = vec![
+                will.bind_property("enabled", &*self.enabled_row, "active")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                will.bind_property("topic", &*self.topic_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                will.bind_property("retain", &*self.retain_row, "active")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                will.bind_property("delay_interval", &*self.delay_interval_row, "value")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                will.bind_property(
+                    "message_expiry_interval",
+                    &*self.message_expiry_interval_row,
+                    "value",
+                )
+                .bidirectional()
+                .sync_create()
+                .build(),
+                will.bind_property("body", &*self.body_tab, "body")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                will.bind_property("content_type", &*self.body_tab, "content_type")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                will.bind_property("qos", &*self.qos_row, "selected")
+                    .bidirectional()
+                    .sync_create()
+                    .transform_to(|_, qos: MQTTyClientQos| {
+                        MQTTyClientQos::listed()
+                            .iter()
+                            .position(|q| *q == qos)
+                            .map(|i| i as u32)
+                    })
+                    .transform_from(|_, idx: u32| {
+                        MQTTyClientQos::listed().get(idx as usize).copied()
+                    })
+                    .build(),
+            ];
+
+            self.bindings.replace(bindings);
+        }
+
+        /// Writes the user properties being edited into the will object, the rows of
+        /// the user properties editor are not bindable
+        pub fn apply_user_properties(&self) {
+            self.will.borrow().set_user_properties(
+                &self
+                    .user_properties_tab
+                    .entries()
+                    .iter()
+                    .filter(|i| i.active())
+                    .map(|i| (i.key(), i.value()))
+                    .collect::<Vec<_>>(),
+            );
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Editor of a MQTTyWillSettings object, changes made by the user are written directly
+    /// to the :will object, except user properties, see
+    /// [`MQTTyPublishWillTab::apply_user_properties()`]
+    pub struct MQTTyPublishWillTab(ObjectSubclass<imp::MQTTyPublishWillTab>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl MQTTyPublishWillTab {
+    pub fn apply_user_properties(&self) {
+        self.imp().apply_user_properties();
+    }
+}
@@ -0,0 +1,316 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::{gettext, ngettext};
+use gtk::{gio, glib};
+
+use crate::application::MQTTyApplication;
+use crate::client::{
+    client_id_from_template, MQTTyClient, MQTTyClientError, MQTTySessionPlayer, MQTTyUrlScheme,
+};
+use crate::gsettings::MQTTySettingConnection;
+use crate::widgets::ask_password;
+
+/// Speeds of the speed row, 0 means as fast as possible
+const SPEEDS: [f64; 4] = [0.5, 1.0, 2.0, 0.0];
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/replay_dialog.ui")]
+    #[properties(wrapper_type = super::MQTTyReplayDialog)]
+    pub struct MQTTyReplayDialog {
+        #[property(get)]
+        player: MQTTySessionPlayer,
+
+        #[template_child]
+        toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        file_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        connection_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        topic_prefix_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        new_topic_prefix_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        speed_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        loop_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        progress_bar: TemplateChild<gtk::ProgressBar>,
+        #[template_child]
+        play_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyReplayDialog {
+        const NAME: &'static str = "MQTTyReplayDialog";
+
+        type Type = super::MQTTyReplayDialog;
+
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.install_action("replay-dialog.toggle-playback", None, |this, _, _| {
+                let this = this.clone();
+                glib::spawn_future_local(async move {
+                    this.imp().toggle_playback().await;
+                });
+            });
+
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyReplayDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            let player = &self.player;
+
+            self.connection_row
+                .set_expression(Some(gtk::PropertyExpression::new(
+                    MQTTySettingConnection::static_type(),
+                    None::<gtk::Expression>,
+                    "url",
+                )));
+            self.connection_row.set_model(Some(
+                MQTTyApplication::get_singleton().settings_connections(),
+            ));
+
+            self.speed_row
+                .bind_property("selected", player, "speed")
+                .sync_create()
+                .transform_to(|_, idx: u32| SPEEDS.get(idx as usize).copied())
+                .build();
+
+            self.loop_row
+                .bind_property("active", player, "looping")
+                .sync_create()
+                .build();
+
+            self.topic_prefix_row
+                .bind_property("text", player, "topic_prefix")
+                .sync_create()
+                .build();
+
+            self.new_topic_prefix_row
+                .bind_property("text", player, "new_topic_prefix")
+                .sync_create()
+                .build();
+
+            player.connect_position_notify(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_| this.update_progress()
+            ));
+
+            player.connect_n_messages_notify(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_| this.update_progress()
+            ));
+
+            player.connect_playing_notify(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |player| {
+                    this.play_button.set_label(&if player.playing() {
+                        gettext("Stop")
+                    } else {
+                        gettext("Play")
+                    });
+                    this.update_actions();
+                }
+            ));
+
+            obj.connect_closed(|obj| obj.player().stop());
+
+            self.update_progress();
+            self.update_actions();
+        }
+    }
+    impl WidgetImpl for MQTTyReplayDialog {}
+    impl AdwDialogImpl for MQTTyReplayDialog {}
+
+    #[gtk::template_callbacks]
+    impl MQTTyReplayDialog {
+        #[template_callback]
+        async fn on_open_clicked(&self) {
+            let filters = gio::ListStore::new::<gtk::FileFilter>();
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some(&gettext("Sessions")));
+            filter.add_suffix("jsonl");
+            filters.append(&filter);
+
+            let dialog = gtk::FileDialog::builder()
+                .title(gettext("Open Session"))
+                .filters(&filters)
+                .modal(true)
+                .build();
+
+            let window = self.obj().root().and_downcast::<gtk::Window>();
+
+            let Ok(file) = dialog.open_future(window.as_ref()).await else {
+                return;
+            };
+
+            self.file_row.set_subtitle(&gettext("Loading…"));
+
+            match self.player.load(&file).await {
+                Ok(()) => {
+                    let name = file
+                        .basename()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let n_messages = self.player.n_messages();
+
+                    self.file_row.set_subtitle(
+                        &formatx!(
+                            ngettext("{} · {} message", "{} · {} messages", n_messages),
+                            glib::markup_escape_text(&name),
+                            n_messages
+                        )
+                        .unwrap_or_default(),
+                    );
+                }
+                Err(e) => {
+                    self.file_row.set_subtitle(&gettext("No session loaded"));
+                    self.toast(
+                        &formatx!(gettext("Couldn't load the session: {}"), e.message())
+                            .unwrap_or_default(),
+                    );
+                }
+            }
+
+            self.update_actions();
+        }
+    }
+
+    impl MQTTyReplayDialog {
+        async fn toggle_playback(&self) {
+            if self.player.playing() {
+                self.player.stop();
+                return;
+            }
+
+            let Some(conn) = self
+                .connection_row
+                .selected_item()
+                .and_downcast::<MQTTySettingConnection>()
+            else {
+                return;
+            };
+
+            match self.play(&conn).await {
+                Ok(()) | Err(MQTTyClientError::Cancelled) => {}
+                Err(e) => self.toast(&e.to_string()),
+            }
+        }
+
+        async fn play(&self, conn: &MQTTySettingConnection) -> Result<(), MQTTyClientError> {
+            let url = conn.url();
+
+            if MQTTyUrlScheme::parse(&url).is_none() {
+                return Err(MQTTyClientError::InvalidUrl(url));
+            }
+
+            let loaded = conn
+                .load_credentials()
+                .await
+                .inspect_err(|e| tracing::warn!("Could not retrieve the password: {}", e))
+                .is_ok();
+
+            // Same as connecting from the subscriptions view, the password is asked for
+            // if it's not remembered
+            let password = if loaded && conn.remember_password() {
+                conn.password()
+            } else {
+                match ask_password(&*self.obj(), &url).await {
+                    Some(password) => password,
+                    None => return Err(MQTTyClientError::Cancelled),
+                }
+            };
+
+            let client = MQTTyClient::new(
+                &url,
+                conn.mqtt_version(),
+                &conn.username(),
+                &password,
+                &client_id_from_template(&conn.client_id()),
+                &conn.tls().duplicate(),
+                &conn.will().duplicate(),
+            );
+
+            client.set_keep_alive_interval(conn.keep_alive_interval());
+
+            let client = MQTTyApplication::get_singleton()
+                .pooled_client(&client)
+                .await?;
+
+            self.player.play(&client).await
+        }
+
+        fn update_progress(&self) {
+            let position = self.player.position();
+            let n_messages = self.player.n_messages();
+
+            self.progress_bar.set_fraction(if n_messages == 0 {
+                0.0
+            } else {
+                position as f64 / n_messages as f64
+            });
+            self.progress_bar
+                .set_text(Some(&format!("{position} / {n_messages}")));
+        }
+
+        fn update_actions(&self) {
+            self.obj().action_set_enabled(
+                "replay-dialog.toggle-playback",
+                self.player.playing() || self.player.n_messages() != 0,
+            );
+        }
+
+        fn toast(&self, title: &str) {
+            self.toast_overlay.add_toast(adw::Toast::new(title));
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Replays a recorded session to one of the saved connections
+    pub struct MQTTyReplayDialog(ObjectSubclass<imp::MQTTyReplayDialog>)
+        @extends gtk::Widget, adw::Dialog,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl Default for MQTTyReplayDialog {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
@@ -13,16 +13,30 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::cell::RefCell;
+use std::ops::Range;
+
 use adw::subclass::prelude::*;
-use gtk::glib;
+use gtk::{glib, pango};
 use sourceview::prelude::*;
 use sourceview::subclass::prelude::*;
 
+/// Category of the source marks of the diagnostics, see
+/// [`MQTTySourceView::set_diagnostics()`]
+const DIAGNOSTIC_CATEGORY: &str = "diagnostic";
+
+/// Name of the tag that underlines the text of the diagnostics
+const DIAGNOSTIC_TAG: &str = "diagnostic";
+
 mod imp {
     use super::*;
 
     #[derive(Default)]
-    pub struct MQTTySourceView {}
+    pub struct MQTTySourceView {
+        /// Messages of the diagnostics, the source mark of each one is named after
+        /// its index
+        pub diagnostics: RefCell<Vec<String>>,
+    }
 
     #[glib::object_subclass]
     impl ObjectSubclass for MQTTySourceView {
@@ -38,6 +52,23 @@ mod imp {
             self.parent_constructed();
 
             self.init_style();
+
+            let attributes = sourceview::MarkAttributes::new();
+            attributes.set_icon_name("dialog-error-symbolic");
+            attributes.connect_query_tooltip_text(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                #[upgrade_or_default]
+                move |_, mark| {
+                    mark.name()
+                        .and_then(|name| name.strip_prefix("diagnostic-")?.parse::<usize>().ok())
+                        .and_then(|i| this.diagnostics.borrow().get(i).cloned())
+                        .unwrap_or_default()
+                }
+            ));
+
+            self.obj()
+                .set_mark_attributes(DIAGNOSTIC_CATEGORY, &attributes, 0);
         }
     }
     impl WidgetImpl for MQTTySourceView {}
@@ -88,3 +119,60 @@ glib::wrapper! {
         @extends gtk::TextView, gtk::Widget, sourceview::View,
         @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Scrollable;
 }
+
+impl MQTTySourceView {
+    /// Underlines the byte ranges of the text and shows an icon in the gutter next to
+    /// each one, with the message as its tooltip. The previous diagnostics are removed,
+    /// so an empty slice clears them
+    pub fn set_diagnostics(&self, diagnostics: &[(Range<usize>, String)]) {
+        let buffer = self.buffer().downcast::<sourceview::Buffer>().unwrap();
+
+        let (start, end) = buffer.bounds();
+
+        buffer.remove_source_marks(&start, &end, Some(DIAGNOSTIC_CATEGORY));
+
+        let tag = buffer
+            .tag_table()
+            .lookup(DIAGNOSTIC_TAG)
+            .or_else(|| {
+                buffer.create_tag(
+                    Some(DIAGNOSTIC_TAG),
+                    &[("underline", &pango::Underline::Error)],
+                )
+            })
+            .unwrap();
+
+        buffer.remove_tag(&tag, &start, &end);
+
+        let text = buffer.text(&start, &end, false);
+
+        let char_offset = |byte: usize| {
+            let byte = (0..=byte.min(text.len()))
+                .rev()
+                .find(|i| text.is_char_boundary(*i))
+                .unwrap_or_default();
+
+            text[..byte].chars().count() as i32
+        };
+
+        let mut messages = vec![];
+
+        for (i, (range, message)) in diagnostics.iter().enumerate() {
+            let start = buffer.iter_at_offset(char_offset(range.start));
+            let end = buffer.iter_at_offset(char_offset(range.end));
+
+            buffer.apply_tag(&tag, &start, &end);
+            buffer.create_source_mark(
+                Some(&format!("diagnostic-{i}")),
+                DIAGNOSTIC_CATEGORY,
+                &start,
+            );
+
+            messages.push(message.clone());
+        }
+
+        self.imp().diagnostics.replace(messages);
+
+        self.set_show_line_marks(!diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,1038 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, OnceCell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use futures::stream::{self, StreamExt};
+use gettextrs::{gettext, ngettext};
+use gtk::{gio, glib};
+
+use crate::application::MQTTyApplication;
+use crate::client::{
+    client_id_from_template, export_messages, MQTTyClient, MQTTyClientError, MQTTyClientMessage,
+    MQTTyClientQos, MQTTyClientState, MQTTyClientVersion, MQTTyExportFormat, MQTTyMessageDirection,
+    MQTTyMessageQuery, MQTTyMessageStore, MQTTySessionRecorder, MQTTySubscribeOptions,
+    MQTTyTopicTree, MQTTyUrlScheme,
+};
+use crate::display_mode::{MQTTyDisplayMode, MQTTyDisplayModeIface};
+use crate::main_window::MQTTyWindow;
+use crate::objects::{MQTTySubscription, MQTTyTlsSettings, MQTTyWillSettings};
+use crate::subclass::prelude::*;
+use crate::toast::MQTTyToastBuilder;
+use crate::widgets::{MQTTyMessageDetail, MQTTyMessageRow, MQTTyReplayDialog, MQTTyTopicTreeView};
+
+/// Maximum number of messages shown when searching
+const SEARCH_LIMIT: usize = 10_000;
+
+/// Time ranges of the search, in seconds, the first one means any time
+const SEARCH_TIME_RANGES: [i64; 4] = [0, 60 * 60, 24 * 60 * 60, 7 * 24 * 60 * 60];
+
+mod imp {
+
+    use super::*;
+
+    #[derive(gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/subscriptions_view/subscriptions_view.ui")]
+    #[properties(wrapper_type = super::MQTTySubscriptionsView)]
+    pub struct MQTTySubscriptionsView {
+        #[property(get, set, override_interface = MQTTyDisplayModeIface)]
+        display_mode: Cell<MQTTyDisplayMode>,
+
+        #[property(get, set)]
+        url: RefCell<String>,
+
+        #[property(get, set, builder(MQTTyClientVersion::V3X))]
+        mqtt_version: Cell<MQTTyClientVersion>,
+
+        #[property(get, set)]
+        username: RefCell<String>,
+
+        #[property(get, set)]
+        password: RefCell<String>,
+
+        /// If disabled, the password is asked every time the user connects instead of
+        /// using :password
+        #[property(get, set)]
+        remember_password: Cell<bool>,
+
+        #[property(get, set)]
+        tls: RefCell<MQTTyTlsSettings>,
+
+        /// Client ID template, see [`client_id_from_template()`], if empty the broker
+        /// assigns one
+        #[property(get, set)]
+        client_id: RefCell<String>,
+
+        #[property(get, set)]
+        will: RefCell<MQTTyWillSettings>,
+
+        #[property(get, set, default = 60)]
+        keep_alive_interval: Cell<u32>,
+
+        /// Client used by the subscriptions, it's not pooled because the pool disconnects
+        /// idle clients, and subscriptions can be quiet for a long time
+        #[property(get, nullable)]
+        client: RefCell<Option<MQTTyClient>>,
+
+        client_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+
+        #[property(get, builder(MQTTyClientState::Disconnected))]
+        connection_state: Cell<MQTTyClientState>,
+
+        /// Whether there is a client, no matter if it's still connecting or reconnecting
+        #[property(get)]
+        connected: Cell<bool>,
+
+        /// List of [`MQTTySubscription`]
+        pub subscriptions: gio::ListStore,
+
+        /// Messages of the connection to [`Self::url`]
+        pub store: RefCell<Option<MQTTyMessageStore>>,
+
+        messages_selection: OnceCell<gtk::MultiSelection>,
+
+        /// Bumped on every search, so that the results of stale searches are discarded
+        search_generation: Cell<u32>,
+
+        /// Topic hierarchy of the received messages
+        pub topic_tree: MQTTyTopicTree,
+
+        /// Records the received messages into a session file
+        #[property(get)]
+        recorder: MQTTySessionRecorder,
+
+        #[template_child]
+        mqtt_version_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        topic_filter_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        qos_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        subscriptions_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        save_messages_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        messages_count_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        search_bar: TemplateChild<gtk::SearchBar>,
+        #[template_child]
+        search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        direction_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        time_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        messages_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        messages_list: TemplateChild<gtk::ListView>,
+        #[template_child]
+        message_detail: TemplateChild<MQTTyMessageDetail>,
+        #[template_child]
+        topic_tree_view: TemplateChild<MQTTyTopicTreeView>,
+    }
+
+    impl Default for MQTTySubscriptionsView {
+        fn default() -> Self {
+            Self {
+                display_mode: Cell::new(MQTTyDisplayMode::Desktop),
+                url: Default::default(),
+                mqtt_version: Default::default(),
+                username: Default::default(),
+                password: Default::default(),
+                remember_password: Cell::new(true),
+                tls: Default::default(),
+                client_id: Default::default(),
+                will: Default::default(),
+                keep_alive_interval: Cell::new(60),
+                client: Default::default(),
+                client_handlers: Default::default(),
+                connection_state: Default::default(),
+                connected: Default::default(),
+                subscriptions: gio::ListStore::new::<MQTTySubscription>(),
+                store: Default::default(),
+                messages_selection: Default::default(),
+                search_generation: Default::default(),
+                topic_tree: Default::default(),
+                recorder: Default::default(),
+                mqtt_version_row: Default::default(),
+                topic_filter_row: Default::default(),
+                qos_row: Default::default(),
+                subscriptions_list: Default::default(),
+                save_messages_row: Default::default(),
+                messages_count_label: Default::default(),
+                search_bar: Default::default(),
+                search_entry: Default::default(),
+                direction_dropdown: Default::default(),
+                time_dropdown: Default::default(),
+                messages_stack: Default::default(),
+                messages_list: Default::default(),
+                message_detail: Default::default(),
+                topic_tree_view: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTySubscriptionsView {
+        const NAME: &'static str = "MQTTySubscriptionsView";
+
+        type Type = super::MQTTySubscriptionsView;
+
+        type ParentType = adw::Bin;
+
+        type Interfaces = (MQTTyDisplayModeIface,);
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.install_action(
+                "subscriptions-view.toggle-connection",
+                None,
+                |this, _, _| {
+                    let this = this.clone();
+                    glib::spawn_future_local(async move {
+                        this.imp().toggle_connection().await;
+                    });
+                },
+            );
+
+            klass.install_action("subscriptions-view.clear", None, |this, _, _| {
+                if let Some(store) = this.store() {
+                    store.clear();
+                }
+                this.topic_tree().clear();
+                this.imp().on_search_changed();
+            });
+
+            klass.install_action("subscriptions-view.subscribe-all", None, |this, _, _| {
+                this.imp()
+                    .add_subscription("#", &MQTTySubscribeOptions::new(MQTTyClientQos::Qos0));
+            });
+
+            klass.install_action("subscriptions-view.toggle-recording", None, |this, _, _| {
+                let this = this.clone();
+                glib::spawn_future_local(async move {
+                    this.imp().toggle_recording().await;
+                });
+            });
+
+            klass.install_action("subscriptions-view.replay", None, |this, _, _| {
+                MQTTyReplayDialog::default().present(Some(this));
+            });
+
+            klass.install_action(
+                "subscriptions-view.export-selection",
+                Some(glib::VariantTy::STRING),
+                |this, _, param| {
+                    let Some(format) = param
+                        .and_then(|param| param.str())
+                        .and_then(MQTTyExportFormat::from_nick)
+                    else {
+                        return;
+                    };
+                    this.imp().export(format, true);
+                },
+            );
+
+            klass.install_action(
+                "subscriptions-view.export-all",
+                Some(glib::VariantTy::STRING),
+                |this, _, param| {
+                    let Some(format) = param
+                        .and_then(|param| param.str())
+                        .and_then(MQTTyExportFormat::from_nick)
+                    else {
+                        return;
+                    };
+                    this.imp().export(format, false);
+                },
+            );
+
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTySubscriptionsView {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            obj.bind_property("mqtt_version", &*self.mqtt_version_row, "selected")
+                .bidirectional()
+                .sync_create()
+                .transform_to(|_, version: MQTTyClientVersion| {
+                    Some(match version {
+                        MQTTyClientVersion::V3X => 0_u32,
+                        MQTTyClientVersion::V5 => 1_u32,
+                    })
+                })
+                .transform_from(|_, idx: u32| {
+                    Some(if idx == 1 {
+                        MQTTyClientVersion::V5
+                    } else {
+                        MQTTyClientVersion::V3X
+                    })
+                })
+                .build();
+
+            self.subscriptions_list.bind_model(
+                Some(&self.subscriptions),
+                glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    #[upgrade_or_panic]
+                    move |item| {
+                        let subscription = item.downcast_ref::<MQTTySubscription>().unwrap();
+                        this.subscription_row(subscription).upcast()
+                    }
+                ),
+            );
+
+            let selection = gtk::MultiSelection::new(None::<gio::ListModel>);
+
+            // The details are only shown when a single message is selected
+            selection.connect_selection_changed(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |selection, _, _| {
+                    let selected = selection.selection();
+
+                    this.message_detail.set_message(
+                        (selected.size() == 1)
+                            .then(|| selection.item(selected.nth(0)))
+                            .flatten()
+                            .and_downcast::<MQTTyClientMessage>()
+                            .as_ref(),
+                    );
+
+                    this.obj().action_set_enabled(
+                        "subscriptions-view.export-selection",
+                        !selected.is_empty(),
+                    );
+                }
+            ));
+
+            let factory = gtk::SignalListItemFactory::new();
+
+            factory.connect_setup(|_, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                item.set_child(Some(&MQTTyMessageRow::default()));
+            });
+
+            factory.connect_bind(|_, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                let row = item.child().and_downcast::<MQTTyMessageRow>().unwrap();
+                row.set_message(item.item().and_downcast::<MQTTyClientMessage>().as_ref());
+            });
+
+            factory.connect_unbind(|_, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                let row = item.child().and_downcast::<MQTTyMessageRow>().unwrap();
+                row.set_message(None::<&MQTTyClientMessage>);
+            });
+
+            self.messages_list.set_model(Some(&selection));
+            self.messages_list.set_factory(Some(&factory));
+
+            selection.connect_items_changed(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_, _, _, _| this.update_messages_count()
+            ));
+
+            self.messages_selection.set(selection).unwrap();
+
+            // Messages of previous sessions show up as soon as the URL is known
+            obj.connect_url_notify(|obj| {
+                let url = obj.url();
+                obj.imp().set_store(if url.is_empty() {
+                    None
+                } else {
+                    Some(MQTTyApplication::get_singleton().message_store(&url))
+                });
+            });
+
+            MQTTyApplication::get_singleton()
+                .settings()
+                .bind("persist-messages", &*self.save_messages_row, "active")
+                .build();
+
+            self.update_messages_count();
+
+            self.topic_tree_view.set_tree(Some(&self.topic_tree));
+        }
+    }
+    impl WidgetImpl for MQTTySubscriptionsView {}
+    impl BinImpl for MQTTySubscriptionsView {}
+    impl MQTTyDisplayModeIfaceImpl for MQTTySubscriptionsView {}
+
+    #[gtk::template_callbacks]
+    impl MQTTySubscriptionsView {
+        #[template_callback]
+        fn display_mode_to_collapsed(&self, display_mode: MQTTyDisplayMode) -> bool {
+            matches!(display_mode, MQTTyDisplayMode::Mobile)
+        }
+
+        #[template_callback]
+        fn display_mode_to_orientation(&self, display_mode: MQTTyDisplayMode) -> gtk::Orientation {
+            match display_mode {
+                MQTTyDisplayMode::Desktop => gtk::Orientation::Horizontal,
+                MQTTyDisplayMode::Mobile => gtk::Orientation::Vertical,
+            }
+        }
+
+        #[template_callback]
+        fn connection_button_label(&self, connected: bool) -> String {
+            if connected {
+                gettext("Disconnect")
+            } else {
+                gettext("Connect")
+            }
+        }
+
+        #[template_callback]
+        fn recording_icon(&self, recording: bool) -> String {
+            if recording {
+                "media-playback-stop-symbolic".to_string()
+            } else {
+                "media-record-symbolic".to_string()
+            }
+        }
+
+        #[template_callback]
+        fn recording_tooltip(&self, recording: bool) -> String {
+            if recording {
+                gettext("Stop recording")
+            } else {
+                gettext("Record session")
+            }
+        }
+
+        #[template_callback]
+        fn connection_state_to_string(&self, state: MQTTyClientState) -> String {
+            match state {
+                MQTTyClientState::Connected => gettext("Connected"),
+                MQTTyClientState::Connecting => gettext("Connecting…"),
+                MQTTyClientState::Reconnecting => gettext("Reconnecting…"),
+                MQTTyClientState::Disconnected => gettext("Disconnected"),
+            }
+        }
+
+        /// Query of the search bar filters, None if it's not searching, the query has no
+        /// limit
+        fn search_query(&self) -> Option<MQTTyMessageQuery> {
+            let text = self.search_entry.text().trim().to_string();
+            let direction = self.direction_dropdown.selected() as usize;
+            let time_range = SEARCH_TIME_RANGES
+                .get(self.time_dropdown.selected() as usize)
+                .copied()
+                .unwrap_or_default();
+
+            if !self.search_bar.is_search_mode()
+                || (text.is_empty() && direction == 0 && time_range == 0)
+            {
+                return None;
+            }
+
+            Some(MQTTyMessageQuery {
+                topic: (!text.is_empty()).then_some(text),
+                // The first option means any direction
+                direction: direction
+                    .checked_sub(1)
+                    .and_then(|i| MQTTyMessageDirection::listed().get(i))
+                    .copied(),
+                since: (time_range != 0).then(|| glib::real_time() - time_range * 1_000_000),
+                until: None,
+                limit: 0,
+            })
+        }
+
+        /// Searching shows a snapshot of the matching messages, otherwise the messages
+        /// in memory are shown live
+        #[template_callback]
+        pub fn on_search_changed(&self) {
+            let Some(selection) = self.messages_selection.get() else {
+                return;
+            };
+
+            let generation = self.search_generation.get().wrapping_add(1);
+            self.search_generation.set(generation);
+
+            let store = self.store.borrow().clone();
+
+            let Some(store) = store else {
+                selection.set_model(None::<&gio::ListModel>);
+                self.update_messages_count();
+                return;
+            };
+
+            let Some(query) = self.search_query() else {
+                selection.set_model(Some(&store));
+                self.update_messages_count();
+                return;
+            };
+
+            let query = MQTTyMessageQuery {
+                limit: SEARCH_LIMIT,
+                ..query
+            };
+
+            let obj = self.obj();
+
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                obj,
+                #[weak]
+                selection,
+                async move {
+                    let messages = store.query(&query).await;
+
+                    let this = obj.imp();
+
+                    if this.search_generation.get() != generation {
+                        return;
+                    }
+
+                    let results = gio::ListStore::new::<MQTTyClientMessage>();
+                    results.extend_from_slice(&messages);
+
+                    selection.set_model(Some(&results));
+                    this.update_messages_count();
+                }
+            ));
+        }
+
+        #[template_callback]
+        fn on_add_subscription(&self) {
+            let topic_filter = self.topic_filter_row.text().trim().to_string();
+
+            if topic_filter.is_empty() {
+                return;
+            }
+
+            let qos = MQTTyClientQos::listed()
+                .get(self.qos_row.selected() as usize)
+                .copied()
+                .unwrap_or_default();
+
+            self.add_subscription(&topic_filter, &MQTTySubscribeOptions::new(qos));
+
+            self.topic_filter_row.set_text("");
+        }
+    }
+
+    impl MQTTySubscriptionsView {
+        pub fn add_subscription(&self, topic_filter: &str, options: &MQTTySubscribeOptions) {
+            // Subscribing twice to the same topic filter replaces the subscription in the
+            // broker, so we do the same with our list
+            if let Some(pos) = self.subscriptions.find_with_equal_func(|item| {
+                item.downcast_ref::<MQTTySubscription>()
+                    .is_some_and(|s| s.topic_filter() == topic_filter)
+            }) {
+                self.subscriptions.remove(pos);
+            }
+
+            let subscription = MQTTySubscription::new(topic_filter, options);
+
+            self.subscriptions.append(&subscription);
+
+            let client = self.client.borrow().clone();
+            if let Some(client) = client {
+                if client.is_connected() {
+                    glib::spawn_future_local(async move {
+                        subscribe(&client, &subscription).await;
+                    });
+                }
+            }
+        }
+
+        async fn toggle_connection(&self) {
+            let obj = self.obj();
+
+            let client = self.client.borrow().clone();
+            if let Some(client) = client {
+                self.set_client(None);
+
+                if let Err(e) = client.disconnect_client().await {
+                    toast_error(&e);
+                }
+
+                return;
+            }
+
+            let url = obj.url();
+
+            if MQTTyUrlScheme::parse(&url).is_none() {
+                toast_error(&MQTTyClientError::InvalidUrl(url));
+                return;
+            }
+
+            let password = if obj.remember_password() {
+                obj.password()
+            } else {
+                match ask_password(&*obj, &url).await {
+                    Some(password) => password,
+                    None => return,
+                }
+            };
+
+            let client = MQTTyClient::new(
+                &url,
+                obj.mqtt_version(),
+                &obj.username(),
+                &password,
+                &client_id_from_template(&obj.client_id()),
+                &obj.tls().duplicate(),
+                &obj.will().duplicate(),
+            );
+
+            client.set_keep_alive_interval(obj.keep_alive_interval());
+            client.set_auto_reconnect(true);
+            client.set_reconnect_max_retries(0);
+
+            MQTTyApplication::get_singleton().register_client(&client);
+
+            self.set_client(Some(client.clone()));
+
+            if let Err(e) = client.connect_client().await {
+                if self.client.borrow().as_ref() == Some(&client) {
+                    self.set_client(None);
+                }
+
+                toast_error(&e);
+            }
+        }
+
+        fn set_client(&self, client: Option<MQTTyClient>) {
+            let obj = self.obj();
+
+            if let Some(old_client) = self.client.replace(client.clone()) {
+                for handler in self.client_handlers.take() {
+                    old_client.disconnect(handler);
+                }
+            }
+
+            for subscription in self.subscriptions.iter::<MQTTySubscription>().flatten() {
+                subscription.set_active(false);
+                subscription.set_status("");
+            }
+
+            if let Some(client) = &client {
+                let handlers = vec![
+                    client.connect_state_notify(glib::clone!(
+                        #[weak(rename_to = this)]
+                        self,
+                        move |client| this.set_connection_state(client.state())
+                    )),
+                    // Called on the first connection and on every reconnection, the
+                    // broker may have discarded the session, so we subscribe again
+                    client.connect_connected(glib::clone!(
+                        #[weak(rename_to = this)]
+                        self,
+                        move |client| {
+                            let client = client.clone();
+                            let subscriptions = this.subscriptions.clone();
+                            glib::spawn_future_local(async move {
+                                for subscription in
+                                    subscriptions.iter::<MQTTySubscription>().flatten()
+                                {
+                                    subscribe(&client, &subscription).await;
+                                }
+                            });
+                        }
+                    )),
+                    client.connect_message(glib::clone!(
+                        #[weak(rename_to = this)]
+                        self,
+                        move |_, msg| this.push_message(msg)
+                    )),
+                    client.connect_disconnected(glib::clone!(
+                        #[weak(rename_to = this)]
+                        self,
+                        move |client| {
+                            if this.client.borrow().as_ref() == Some(client) {
+                                this.set_client(None);
+                            }
+                        }
+                    )),
+                ];
+
+                self.client_handlers.replace(handlers);
+            }
+
+            self.set_connection_state(
+                client
+                    .as_ref()
+                    .map_or(MQTTyClientState::Disconnected, |c| c.state()),
+            );
+
+            if self.connected.replace(client.is_some()) != client.is_some() {
+                obj.notify_connected();
+            }
+
+            obj.notify_client();
+        }
+
+        fn set_connection_state(&self, state: MQTTyClientState) {
+            if self.connection_state.replace(state) != state {
+                self.obj().notify_connection_state();
+            }
+        }
+
+        fn push_message(&self, msg: &MQTTyClientMessage) {
+            if let Some(store) = self.store.borrow().as_ref() {
+                store.push(msg);
+            }
+            self.topic_tree.insert(msg);
+            self.recorder.push(msg);
+        }
+
+        fn set_store(&self, store: Option<MQTTyMessageStore>) {
+            if self.store.borrow().as_ref() == store.as_ref() {
+                return;
+            }
+
+            self.store.replace(store);
+            self.on_search_changed();
+        }
+
+        fn update_messages_count(&self) {
+            let n_items = self.messages_selection.get().map_or(0, |s| s.n_items());
+
+            let obj = self.obj();
+            obj.action_set_enabled("subscriptions-view.export-all", n_items != 0);
+            obj.action_set_enabled(
+                "subscriptions-view.export-selection",
+                self.messages_selection
+                    .get()
+                    .is_some_and(|s| !s.selection().is_empty()),
+            );
+
+            self.messages_count_label.set_label(
+                &formatx!(ngettext("{} message", "{} messages", n_items), n_items)
+                    .unwrap_or_default(),
+            );
+
+            self.messages_stack.set_visible_child_name(if n_items == 0 {
+                "empty"
+            } else {
+                "messages"
+            });
+        }
+
+        async fn toggle_recording(&self) {
+            if self.recorder.recording() {
+                self.recorder.stop();
+
+                let n = self.recorder.message_count();
+                toast(
+                    formatx!(
+                        ngettext("Recorded {} message", "Recorded {} messages", n as u32),
+                        n
+                    )
+                    .unwrap_or_default(),
+                    "media-record-symbolic",
+                );
+                return;
+            }
+
+            let filters = gio::ListStore::new::<gtk::FileFilter>();
+            filters.append(&MQTTyExportFormat::JsonLines.file_filter());
+
+            let dialog = gtk::FileDialog::builder()
+                .title(gettext("Record Session"))
+                .initial_name("session.jsonl")
+                .filters(&filters)
+                .modal(true)
+                .build();
+
+            let window = self.obj().root().and_downcast::<gtk::Window>();
+
+            let Ok(file) = dialog.save_future(window.as_ref()).await else {
+                return;
+            };
+
+            if let Err(e) = self.recorder.start(&file).await {
+                toast(
+                    formatx!(gettext("Couldn't record the session: {}"), e.message())
+                        .unwrap_or_default(),
+                    "dialog-error-symbolic",
+                );
+            }
+        }
+
+        /// Exports the selected messages, or every message of the store that matches the
+        /// search filters, to a file picked by the user, the messages are written oldest
+        /// first
+        fn export(&self, format: MQTTyExportFormat, selected_only: bool) {
+            let pages = if selected_only {
+                let Some(selection) = self.messages_selection.get() else {
+                    return;
+                };
+
+                let selected = selection.selection();
+
+                let mut messages = (0..selected.size())
+                    .filter_map(|i| {
+                        selection
+                            .item(selected.nth(i as u32))
+                            .and_downcast::<MQTTyClientMessage>()
+                    })
+                    .collect::<Vec<_>>();
+
+                // Messages are listed newest first
+                messages.reverse();
+
+                if messages.is_empty() {
+                    return;
+                }
+
+                stream::iter([Ok(messages)]).boxed_local()
+            } else {
+                let Some(store) = self.store.borrow().clone() else {
+                    return;
+                };
+
+                // Every message is read in pages when the file is written, not only
+                // the ones shown, which are limited by the capacity or the search limit
+                let query = self.search_query().unwrap_or_default();
+
+                store.query_pages(&query)
+            };
+
+            let filters = gio::ListStore::new::<gtk::FileFilter>();
+            filters.append(&format.file_filter());
+
+            let dialog = gtk::FileDialog::builder()
+                .title(gettext("Export Messages"))
+                .initial_name(format.file_name())
+                .filters(&filters)
+                .modal(true)
+                .build();
+
+            let window = self.obj().root().and_downcast::<gtk::Window>();
+
+            glib::spawn_future_local(async move {
+                let Ok(file) = dialog.save_future(window.as_ref()).await else {
+                    return;
+                };
+
+                match export_messages(pages, format, &file).await {
+                    Ok(written) => toast(
+                        formatx!(
+                            ngettext(
+                                "Exported {} message",
+                                "Exported {} messages",
+                                written as u32
+                            ),
+                            written
+                        )
+                        .unwrap_or_default(),
+                        "document-save-symbolic",
+                    ),
+                    Err(e) => toast(
+                        formatx!(gettext("Couldn't export messages: {}"), e.message())
+                            .unwrap_or_default(),
+                        "dialog-error-symbolic",
+                    ),
+                }
+            });
+        }
+
+        fn subscription_row(&self, subscription: &MQTTySubscription) -> adw::ActionRow {
+            let row = adw::ActionRow::builder()
+                .title(subscription.topic_filter())
+                .title_lines(1)
+                .use_markup(false)
+                .build();
+
+            subscription
+                .bind_property("status", &row, "subtitle")
+                .transform_to(|binding, status: String| {
+                    let subscription = binding.source()?.downcast::<MQTTySubscription>().ok()?;
+
+                    let qos = formatx!(gettext("QoS {}"), subscription.options().qos() as i32)
+                        .unwrap_or_default();
+
+                    Some(if status.is_empty() {
+                        qos
+                    } else {
+                        [qos, status].join(" · ")
+                    })
+                })
+                .sync_create()
+                .build();
+
+            let remove_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(gettext("Remove subscription"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+
+            remove_button.connect_clicked(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                #[weak]
+                subscription,
+                move |_| this.remove_subscription(&subscription)
+            ));
+
+            row.add_suffix(&remove_button);
+
+            row
+        }
+
+        pub fn remove_subscription(&self, subscription: &MQTTySubscription) {
+            let Some(pos) = self.subscriptions.find(subscription) else {
+                return;
+            };
+
+            self.subscriptions.remove(pos);
+
+            let client = self.client.borrow().clone();
+            if let Some(client) = client {
+                if subscription.active() && client.is_connected() {
+                    let topic_filter = subscription.topic_filter();
+                    glib::spawn_future_local(async move {
+                        if let Err(e) = client.unsubscribe(&topic_filter).await {
+                            toast_error(&e);
+                        }
+                    });
+                }
+            }
+        }
+    }
+}
+
+async fn subscribe(client: &MQTTyClient, subscription: &MQTTySubscription) {
+    match client
+        .subscribe(&subscription.topic_filter(), &subscription.options())
+        .await
+    {
+        Ok(code) => {
+            subscription.set_active(code.is_success());
+            subscription.set_status(code.to_string());
+        }
+        Err(e) => {
+            subscription.set_active(false);
+            subscription.set_status(e.to_string());
+        }
+    }
+}
+
+fn toast_error(error: &MQTTyClientError) {
+    toast(
+        formatx!(gettext("Connection error: {}"), error).unwrap(),
+        "network-error-symbolic",
+    );
+}
+
+fn toast(title: String, icon_name: &str) {
+    let app = MQTTyApplication::get_singleton();
+
+    let Some(window) = app.active_window().and_downcast::<MQTTyWindow>() else {
+        return;
+    };
+
+    window.toast(
+        &MQTTyToastBuilder::new()
+            .title(title)
+            .icon(gtk::Image::builder().icon_name(icon_name).build().as_ref())
+            .timeout(5)
+            .build(),
+    );
+}
+
+glib::wrapper! {
+    /// Subscribes to several topic filters of a broker and shows the received messages,
+    /// along with the details of the selected one
+    pub struct MQTTySubscriptionsView(ObjectSubclass<imp::MQTTySubscriptionsView>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl MQTTySubscriptionsView {
+    /// List of [`MQTTySubscription`]
+    pub fn subscriptions(&self) -> gio::ListStore {
+        self.imp().subscriptions.clone()
+    }
+
+    /// Adds a subscription, replacing the one with the same topic filter, it's subscribed
+    /// right away if the view is connected
+    pub fn add_subscription(&self, topic_filter: &str, options: &MQTTySubscribeOptions) {
+        self.imp().add_subscription(topic_filter, options);
+    }
+
+    /// Removes every subscription, unsubscribing from the active ones
+    pub fn remove_all_subscriptions(&self) {
+        let subscriptions = self
+            .subscriptions()
+            .iter::<MQTTySubscription>()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        for subscription in subscriptions {
+            self.imp().remove_subscription(&subscription);
+        }
+    }
+
+    /// Messages of the connection to the current URL
+    pub fn store(&self) -> Option<MQTTyMessageStore> {
+        self.imp().store.borrow().clone()
+    }
+
+    /// Topic hierarchy of the received messages
+    pub fn topic_tree(&self) -> MQTTyTopicTree {
+        self.imp().topic_tree.clone()
+    }
+}
+
+/// Asks the user for the password of the broker, returns None if the dialog was
+/// cancelled
+pub async fn ask_password(parent: &impl IsA<gtk::Widget>, url: &str) -> Option<String> {
+    const RESPONSE_CONNECT: &str = "connect";
+
+    let entry = gtk::PasswordEntry::builder()
+        .show_peek_icon(true)
+        .activates_default(true)
+        .build();
+
+    let dialog = adw::AlertDialog::builder()
+        .heading(gettext("Password Required"))
+        .body(formatx!(gettext("Enter the password for {}"), url).unwrap())
+        .extra_child(&entry)
+        .close_response("cancel")
+        .default_response(RESPONSE_CONNECT)
+        .build();
+
+    dialog.add_response("cancel", &gettext("Cancel"));
+    dialog.add_response(RESPONSE_CONNECT, &gettext("Connect"));
+    dialog.set_response_appearance(RESPONSE_CONNECT, adw::ResponseAppearance::Suggested);
+
+    if dialog.choose_future(parent).await == RESPONSE_CONNECT {
+        Some(entry.text().to_string())
+    } else {
+        None
+    }
+}
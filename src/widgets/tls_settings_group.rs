@@ -0,0 +1,232 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::glib;
+
+use crate::objects::{MQTTyTlsSettings, MQTTyTlsVersion};
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/tls_settings_group.ui")]
+    #[properties(wrapper_type = super::MQTTyTlsSettingsGroup)]
+    pub struct MQTTyTlsSettingsGroup {
+        #[property(get, set = Self::set_settings)]
+        settings: RefCell<MQTTyTlsSettings>,
+
+        /// Bindings between the current settings object and the rows, they are removed
+        /// when the settings object changes
+        bindings: RefCell<Vec<glib::Binding>>,
+
+        #[template_child]
+        ca_file_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        client_cert_file_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        client_key_file_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        client_key_passphrase_row: TemplateChild<adw::PasswordEntryRow>,
+        #[template_child]
+        verify_server_cert_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        verify_hostname_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        min_tls_version_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        cipher_list_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        alpn_protocols_row: TemplateChild<adw::EntryRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyTlsSettingsGroup {
+        const NAME: &'static str = "MQTTyTlsSettingsGroup";
+
+        type Type = super::MQTTyTlsSettingsGroup;
+
+        type ParentType = adw::PreferencesGroup;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyTlsSettingsGroup {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let list = gtk::StringList::new(&[]);
+
+            for i in MQTTyTlsVersion::listed() {
+                list.append(&i.translated());
+            }
+
+            self.min_tls_version_row.set_model(Some(&list));
+
+            self.bind_settings();
+        }
+    }
+    impl WidgetImpl for MQTTyTlsSettingsGroup {}
+    impl PreferencesGroupImpl for MQTTyTlsSettingsGroup {}
+
+    #[gtk::template_callbacks]
+    impl MQTTyTlsSettingsGroup {
+        #[template_callback]
+        fn on_select_ca_file(&self) {
+            self.select_file(gettext("Select CA certificates file"), "ca_file");
+        }
+
+        #[template_callback]
+        fn on_select_client_cert_file(&self) {
+            self.select_file(
+                gettext("Select client certificate file"),
+                "client_cert_file",
+            );
+        }
+
+        #[template_callback]
+        fn on_select_client_key_file(&self) {
+            self.select_file(gettext("Select client private key file"), "client_key_file");
+        }
+    }
+
+    impl MQTTyTlsSettingsGroup {
+        fn set_settings(&self, settings: MQTTyTlsSettings) {
+            self.settings.replace(settings);
+            self.bind_settings();
+        }
+
+        fn bind_settings(&self) {
+            for binding in self.bindings.take() {
+                binding.unbind();
+            }
+
+            let settings = self.settings.borrow();
+
+            let bindings = vec![
+                settings
+                    .bind_property("ca_file", &*self.ca_file_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                settings
+                    .bind_property("client_cert_file", &*self.client_cert_file_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                settings
+                    .bind_property("client_key_file", &*self.client_key_file_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                settings
+                    .bind_property(
+                        "client_key_passphrase",
+                        &*self.client_key_passphrase_row,
+                        "text",
+                    )
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                settings
+                    .bind_property(
+                        "verify_server_cert",
+                        &*self.verify_server_cert_row,
+                        "active",
+                    )
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                settings
+                    .bind_property("verify_hostname", &*self.verify_hostname_row, "active")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                settings
+                    .bind_property("cipher_list", &*self.cipher_list_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                settings
+                    .bind_property("alpn_protocols", &*self.alpn_protocols_row, "text")
+                    .bidirectional()
+                    .sync_create()
+                    .build(),
+                settings
+                    .bind_property("min_tls_version", &*self.min_tls_version_row, "selected")
+                    .bidirectional()
+                    .sync_create()
+                    .transform_to(|_, version: MQTTyTlsVersion| {
+                        MQTTyTlsVersion::listed()
+                            .iter()
+                            .position(|v| *v == version)
+                            .map(|i| i as u32)
+                    })
+                    .transform_from(|_, idx: u32| {
+                        MQTTyTlsVersion::listed().get(idx as usize).copied()
+                    })
+                    .build(),
+            ];
+
+            self.bindings.replace(bindings);
+        }
+
+        fn select_file(&self, title: String, property: &'static str) {
+            let obj = self.obj();
+
+            let dialog = gtk::FileDialog::builder().title(title).modal(true).build();
+
+            let window = obj.root().and_downcast::<gtk::Window>();
+
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    let Ok(file) = dialog.open_future(window.as_ref()).await else {
+                        return;
+                    };
+
+                    let Some(path) = file.path() else {
+                        return;
+                    };
+
+                    obj.settings()
+                        .set_property(property, path.to_string_lossy().to_string());
+                }
+            ));
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Editor of a MQTTyTlsSettings object, changes made by the user are written directly
+    /// to the :settings object
+    pub struct MQTTyTlsSettingsGroup(ObjectSubclass<imp::MQTTyTlsSettingsGroup>)
+        @extends gtk::Widget, adw::PreferencesGroup,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
@@ -0,0 +1,180 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use formatx::formatx;
+use gettextrs::{gettext, ngettext};
+use gtk::glib;
+
+use crate::client::MQTTyTopicNode;
+use crate::payload_decoder::MQTTyPayloadDecoder;
+
+/// Maximum number of characters of the last value shown in a row
+const VALUE_PREVIEW_LEN: usize = 100;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/topic_node_row.ui")]
+    #[properties(wrapper_type = super::MQTTyTopicNodeRow)]
+    pub struct MQTTyTopicNodeRow {
+        /// Row of the tree list model, its item must be a [`MQTTyTopicNode`]
+        #[property(get, set = Self::set_list_row, nullable)]
+        list_row: RefCell<Option<gtk::TreeListRow>>,
+
+        handlers: RefCell<Vec<glib::SignalHandlerId>>,
+
+        node: RefCell<Option<MQTTyTopicNode>>,
+
+        #[template_child]
+        expander: TemplateChild<gtk::TreeExpander>,
+        #[template_child]
+        name_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        retained_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        value_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        stats_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyTopicNodeRow {
+        const NAME: &'static str = "MQTTyTopicNodeRow";
+
+        type Type = super::MQTTyTopicNodeRow;
+
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyTopicNodeRow {}
+    impl WidgetImpl for MQTTyTopicNodeRow {}
+    impl BinImpl for MQTTyTopicNodeRow {}
+
+    impl MQTTyTopicNodeRow {
+        fn set_list_row(&self, list_row: Option<gtk::TreeListRow>) {
+            if let Some(node) = self.node.take() {
+                for handler in self.handlers.take() {
+                    node.disconnect(handler);
+                }
+            }
+
+            self.expander.set_list_row(list_row.as_ref());
+
+            let node = list_row
+                .as_ref()
+                .and_then(|r| r.item())
+                .and_downcast::<MQTTyTopicNode>();
+
+            if let Some(node) = &node {
+                // Statistics change very often, so we don't use a binding per label
+                let handlers = ["last-message", "message-rate", "n-children"]
+                    .into_iter()
+                    .map(|name| {
+                        node.connect_notify_local(
+                            Some(name),
+                            glib::clone!(
+                                #[weak(rename_to = this)]
+                                self,
+                                move |node, _| this.update(node)
+                            ),
+                        )
+                    })
+                    .collect();
+
+                self.handlers.replace(handlers);
+
+                self.update(node);
+            }
+
+            self.node.replace(node);
+            self.list_row.replace(list_row);
+        }
+
+        fn update(&self, node: &MQTTyTopicNode) {
+            let name = node.name();
+
+            self.name_label.set_label(&if name.is_empty() {
+                gettext("(empty)")
+            } else {
+                name
+            });
+
+            self.expander.set_hide_expander(node.n_children() == 0);
+
+            self.retained_image.set_visible(node.retained());
+
+            self.value_label.set_label(
+                &node
+                    .last_message()
+                    .map(|msg| {
+                        MQTTyPayloadDecoder::get_singleton()
+                            .message_text(&msg)
+                            .chars()
+                            .take(VALUE_PREVIEW_LEN)
+                            .map(|c| if c.is_control() { ' ' } else { c })
+                            .collect::<String>()
+                    })
+                    .unwrap_or_default(),
+            );
+            self.value_label.set_visible(node.last_message().is_some());
+
+            let count = node.subtree_message_count();
+
+            self.stats_label.set_label(&format!(
+                "{}\n{}",
+                formatx!(
+                    ngettext(
+                        "{} message",
+                        "{} messages",
+                        count.try_into().unwrap_or(u32::MAX),
+                    ),
+                    count
+                )
+                .unwrap_or_default(),
+                formatx!(gettext("{} msg/s"), format!("{:.1}", node.message_rate()))
+                    .unwrap_or_default(),
+            ));
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Row of the topic tree, shows the last value of a topic and the statistics of its
+    /// subtree
+    pub struct MQTTyTopicNodeRow(ObjectSubclass<imp::MQTTyTopicNodeRow>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl Default for MQTTyTopicNodeRow {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{Cell, OnceCell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::client::{MQTTyClientMessage, MQTTyTopicNode, MQTTyTopicTree};
+use crate::display_mode::{MQTTyDisplayMode, MQTTyDisplayModeIface};
+use crate::subclass::prelude::*;
+use crate::widgets::{MQTTyMessageDetail, MQTTyMessageRow, MQTTyTopicNodeRow};
+
+mod imp {
+
+    use super::*;
+
+    #[derive(gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/io/github/otaxhu/MQTTy/ui/topic_tree_view.ui")]
+    #[properties(wrapper_type = super::MQTTyTopicTreeView)]
+    pub struct MQTTyTopicTreeView {
+        #[property(get, set, override_interface = MQTTyDisplayModeIface)]
+        display_mode: Cell<MQTTyDisplayMode>,
+
+        #[property(get, set = Self::set_tree, nullable)]
+        tree: RefCell<Option<MQTTyTopicTree>>,
+
+        tree_handler: RefCell<Option<glib::SignalHandlerId>>,
+
+        tree_model: RefCell<Option<gtk::TreeListModel>>,
+
+        topics_selection: OnceCell<gtk::SingleSelection>,
+
+        history_selection: OnceCell<gtk::SingleSelection>,
+
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        topics_list: TemplateChild<gtk::ListView>,
+        #[template_child]
+        node_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        topic_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        history_list: TemplateChild<gtk::ListView>,
+        #[template_child]
+        history_detail: TemplateChild<MQTTyMessageDetail>,
+    }
+
+    impl Default for MQTTyTopicTreeView {
+        fn default() -> Self {
+            Self {
+                display_mode: Cell::new(MQTTyDisplayMode::Desktop),
+                tree: Default::default(),
+                tree_handler: Default::default(),
+                tree_model: Default::default(),
+                topics_selection: Default::default(),
+                history_selection: Default::default(),
+                stack: Default::default(),
+                topics_list: Default::default(),
+                node_stack: Default::default(),
+                topic_label: Default::default(),
+                history_list: Default::default(),
+                history_detail: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MQTTyTopicTreeView {
+        const NAME: &'static str = "MQTTyTopicTreeView";
+
+        type Type = super::MQTTyTopicTreeView;
+
+        type ParentType = adw::Bin;
+
+        type Interfaces = (MQTTyDisplayModeIface,);
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::types::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MQTTyTopicTreeView {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let topics_selection = gtk::SingleSelection::builder()
+                .autoselect(false)
+                .can_unselect(true)
+                .build();
+
+            topics_selection.connect_selected_item_notify(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |selection| {
+                    this.set_node(
+                        selection
+                            .selected_item()
+                            .and_downcast::<gtk::TreeListRow>()
+                            .and_then(|row| row.item())
+                            .and_downcast::<MQTTyTopicNode>()
+                            .as_ref(),
+                    );
+                }
+            ));
+
+            let factory = gtk::SignalListItemFactory::new();
+
+            factory.connect_setup(|_, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                item.set_child(Some(&MQTTyTopicNodeRow::default()));
+            });
+
+            factory.connect_bind(|_, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                let row = item.child().and_downcast::<MQTTyTopicNodeRow>().unwrap();
+                row.set_list_row(item.item().and_downcast::<gtk::TreeListRow>().as_ref());
+            });
+
+            factory.connect_unbind(|_, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                let row = item.child().and_downcast::<MQTTyTopicNodeRow>().unwrap();
+                row.set_list_row(None::<&gtk::TreeListRow>);
+            });
+
+            self.topics_list.set_model(Some(&topics_selection));
+            self.topics_list.set_factory(Some(&factory));
+
+            self.topics_selection.set(topics_selection).unwrap();
+
+            let history_selection = gtk::SingleSelection::builder()
+                .autoselect(false)
+                .can_unselect(true)
+                .build();
+
+            let history_detail = &self.history_detail;
+
+            history_selection.connect_selected_item_notify(glib::clone!(
+                #[weak]
+                history_detail,
+                move |selection| {
+                    history_detail.set_message(
+                        selection
+                            .selected_item()
+                            .and_downcast::<MQTTyClientMessage>()
+                            .as_ref(),
+                    );
+                }
+            ));
+
+            let factory = gtk::SignalListItemFactory::new();
+
+            factory.connect_setup(|_, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                item.set_child(Some(&MQTTyMessageRow::default()));
+            });
+
+            factory.connect_bind(|_, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                let row = item.child().and_downcast::<MQTTyMessageRow>().unwrap();
+                row.set_message(item.item().and_downcast::<MQTTyClientMessage>().as_ref());
+            });
+
+            factory.connect_unbind(|_, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                let row = item.child().and_downcast::<MQTTyMessageRow>().unwrap();
+                row.set_message(None::<&MQTTyClientMessage>);
+            });
+
+            self.history_list.set_model(Some(&history_selection));
+            self.history_list.set_factory(Some(&factory));
+
+            self.history_selection.set(history_selection).unwrap();
+
+            self.set_node(None);
+        }
+    }
+    impl WidgetImpl for MQTTyTopicTreeView {}
+    impl BinImpl for MQTTyTopicTreeView {}
+    impl MQTTyDisplayModeIfaceImpl for MQTTyTopicTreeView {}
+
+    #[gtk::template_callbacks]
+    impl MQTTyTopicTreeView {
+        #[template_callback]
+        fn display_mode_to_orientation(&self, display_mode: MQTTyDisplayMode) -> gtk::Orientation {
+            match display_mode {
+                MQTTyDisplayMode::Desktop => gtk::Orientation::Horizontal,
+                MQTTyDisplayMode::Mobile => gtk::Orientation::Vertical,
+            }
+        }
+
+        /// Busy trees are easier to explore from the first level
+        #[template_callback]
+        fn on_collapse_all(&self) {
+            let Some(model) = self.tree_model.borrow().clone() else {
+                return;
+            };
+
+            // Collapsing a row only removes the rows after it, so we go backwards
+            for i in (0..model.n_items()).rev() {
+                if let Some(row) = model.row(i) {
+                    row.set_expanded(false);
+                }
+            }
+        }
+    }
+
+    impl MQTTyTopicTreeView {
+        fn set_tree(&self, tree: Option<MQTTyTopicTree>) {
+            if let Some(old_tree) = self.tree.replace(tree.clone()) {
+                if let Some(handler) = self.tree_handler.take() {
+                    old_tree.root().disconnect(handler);
+                }
+            }
+
+            let model = tree.as_ref().map(|tree| {
+                gtk::TreeListModel::new(tree.root().children(), false, false, |item| {
+                    let node = item.downcast_ref::<MQTTyTopicNode>().unwrap();
+                    Some(node.children().upcast())
+                })
+            });
+
+            self.topics_selection
+                .get()
+                .unwrap()
+                .set_model(model.as_ref());
+            self.tree_model.replace(model);
+
+            if let Some(tree) = &tree {
+                let handler = tree.root().connect_n_children_notify(glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |root| this.update_stack(root.n_children())
+                ));
+
+                self.tree_handler.replace(Some(handler));
+            }
+
+            self.update_stack(tree.map_or(0, |tree| tree.root().n_children()));
+        }
+
+        fn update_stack(&self, n_children: u32) {
+            self.stack
+                .set_visible_child_name(if n_children == 0 { "empty" } else { "tree" });
+        }
+
+        fn set_node(&self, node: Option<&MQTTyTopicNode>) {
+            let history_selection = self.history_selection.get().unwrap();
+
+            let Some(node) = node else {
+                history_selection.set_model(None::<&gtk::gio::ListModel>);
+                self.node_stack.set_visible_child_name("empty");
+                return;
+            };
+
+            self.topic_label.set_label(&node.topic());
+            history_selection.set_model(Some(&node.history()));
+            self.node_stack.set_visible_child_name("node");
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Explores the topic hierarchy built from the received messages, along with the
+    /// message history of the selected topic
+    pub struct MQTTyTopicTreeView(ObjectSubclass<imp::MQTTyTopicTreeView>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
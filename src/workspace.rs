@@ -0,0 +1,455 @@
+// Copyright (c) 2025 Oscar Pernia
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use adw::prelude::*;
+use base64::prelude::*;
+use formatx::formatx;
+use gettextrs::gettext;
+use gtk::{gio, glib};
+use serde::{Deserialize, Serialize};
+
+use crate::application::MQTTyApplication;
+use crate::client::{
+    MQTTyClientQos, MQTTyClientVersion, MQTTyRetainHandling, MQTTySubscribeOptions,
+};
+use crate::content_type::MQTTyContentType;
+use crate::credentials;
+use crate::objects::{MQTTyKeyValue, MQTTySubscription};
+use crate::widgets::{MQTTyPublishView, MQTTyPublishViewNotebook, MQTTySubscriptionsView};
+
+/// Version of the workspace format, it's increased on incompatible changes, files with
+/// a newer version are not opened
+const WORKSPACE_VERSION: u32 = 1;
+
+/// Number of workspaces kept in the "recent-workspaces" setting
+const RECENT_WORKSPACES_LIMIT: usize = 10;
+
+/// Publish tabs and subscriptions of the main window, saved to a JSON file that looks
+/// like this:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "publish_tabs": [
+///     {
+///       "url": "mqtt://localhost:1883",
+///       "ws_path": "",
+///       "ws_subprotocol": "",
+///       "client_id": "",
+///       "topic": "sensors/kitchen",
+///       "qos": 1,
+///       "mqtt_version": 5,
+///       "content_type": "json",
+///       "body": "{\"temperature\": 21.5}",
+///       "body_file": "file:///home/user/payload.bin",
+///       "user_properties": [{ "key": "unit", "value": "celsius", "active": true }],
+///       "auth": { "username": "admin", "credentials_id": "8c6976e5b541..." }
+///     }
+///   ],
+///   "subscriptions": {
+///     "url": "mqtt://localhost:1883",
+///     "mqtt_version": 5,
+///     "auth": { "username": "" },
+///     "subscriptions": [
+///       {
+///         "topic_filter": "sensors/#",
+///         "qos": 0,
+///         "no_local": false,
+///         "retain_as_published": false,
+///         "retain_handling": "send-on-subscribe",
+///         "subscription_identifier": 0
+///       }
+///     ]
+///   }
+/// }
+/// ```
+///
+/// Bodies that are not valid UTF-8 are saved in `body_base64` instead of `body`, and
+/// `body_file` is only present if the tab sends a file.
+///
+/// Passwords are never saved, `auth` only references the credentials of the connection,
+/// which are stored in the keyring, see [`credentials::broker_credentials_id()`]
+#[derive(Serialize, Deserialize)]
+pub struct MQTTyWorkspace {
+    version: u32,
+    #[serde(default)]
+    publish_tabs: Vec<WorkspaceTab>,
+    #[serde(default)]
+    subscriptions: WorkspaceSubscriptions,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct WorkspaceAuth {
+    #[serde(default)]
+    username: String,
+    /// Empty if there is no password
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    credentials_id: String,
+    /// Stored in the keyring when saving the workspace, and retrieved when loading it
+    #[serde(skip)]
+    password: String,
+}
+
+impl WorkspaceAuth {
+    fn new(url: &str, username: String, password: String) -> Self {
+        let credentials_id = if password.is_empty() {
+            String::new()
+        } else {
+            credentials::broker_credentials_id(url, &username)
+        };
+
+        Self {
+            username,
+            credentials_id,
+            password,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorkspaceTab {
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    ws_path: String,
+    #[serde(default)]
+    ws_subprotocol: String,
+    #[serde(default)]
+    client_id: String,
+    #[serde(default)]
+    topic: String,
+    #[serde(default)]
+    qos: MQTTyClientQos,
+    #[serde(default)]
+    mqtt_version: MQTTyClientVersion,
+    #[serde(default)]
+    content_type: MQTTyContentType,
+    /// Only used by protobuf bodies
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    message_type: String,
+    /// Body as text, empty if it's not valid UTF-8
+    #[serde(default)]
+    body: String,
+    /// Used instead of `body` when it's not valid UTF-8
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    body_base64: String,
+    /// URI of the file sent instead of the body
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    body_file: String,
+    /// Inline JSON Schema of the body
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    json_schema: String,
+    #[serde(default)]
+    user_properties: Vec<MQTTyKeyValue>,
+    #[serde(default)]
+    auth: WorkspaceAuth,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct WorkspaceSubscriptions {
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    mqtt_version: MQTTyClientVersion,
+    #[serde(default)]
+    auth: WorkspaceAuth,
+    #[serde(default)]
+    subscriptions: Vec<WorkspaceSubscription>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorkspaceSubscription {
+    topic_filter: String,
+    #[serde(default)]
+    qos: MQTTyClientQos,
+    #[serde(default)]
+    no_local: bool,
+    #[serde(default)]
+    retain_as_published: bool,
+    #[serde(default)]
+    retain_handling: MQTTyRetainHandling,
+    #[serde(default)]
+    subscription_identifier: u32,
+}
+
+impl From<&MQTTyPublishViewNotebook> for WorkspaceTab {
+    fn from(notebook: &MQTTyPublishViewNotebook) -> Self {
+        let body = notebook.body();
+
+        let (body, body_base64) = match std::str::from_utf8(&body) {
+            Ok(text) => (text.to_string(), String::new()),
+            Err(_) => (String::new(), BASE64_STANDARD.encode(&body)),
+        };
+
+        Self {
+            url: notebook.url(),
+            ws_path: notebook.ws_path(),
+            ws_subprotocol: notebook.ws_subprotocol(),
+            client_id: notebook.client_id(),
+            topic: notebook.topic(),
+            qos: notebook.qos(),
+            mqtt_version: notebook.mqtt_version(),
+            content_type: notebook.content_type(),
+            message_type: notebook.message_type(),
+            body,
+            body_base64,
+            body_file: notebook
+                .body_file()
+                .map(|file| file.uri().into())
+                .unwrap_or_default(),
+            json_schema: notebook.json_schema(),
+            user_properties: notebook.user_properties(),
+            auth: WorkspaceAuth::new(&notebook.url(), notebook.username(), notebook.password()),
+        }
+    }
+}
+
+impl WorkspaceTab {
+    fn apply(&self, notebook: &MQTTyPublishViewNotebook) {
+        notebook.set_url(self.url.as_str());
+        notebook.set_ws_path(self.ws_path.as_str());
+        notebook.set_ws_subprotocol(self.ws_subprotocol.as_str());
+        notebook.set_client_id(self.client_id.as_str());
+        notebook.set_topic(self.topic.as_str());
+        notebook.set_qos(self.qos);
+        notebook.set_mqtt_version(self.mqtt_version);
+        notebook.set_content_type(self.content_type);
+        notebook.set_message_type(self.message_type.as_str());
+
+        // Invalid base64 leaves the body empty rather than failing the whole workspace
+        let body = if self.body_base64.is_empty() {
+            self.body.as_bytes().to_vec()
+        } else {
+            BASE64_STANDARD
+                .decode(&self.body_base64)
+                .inspect_err(|e| tracing::warn!("Invalid base64 body in workspace: {}", e))
+                .unwrap_or_default()
+        };
+        notebook.set_body(glib::Bytes::from_owned(body));
+        notebook.set_body_file(
+            (!self.body_file.is_empty()).then(|| gio::File::for_uri(&self.body_file)),
+        );
+        notebook.set_json_schema(self.json_schema.as_str());
+        notebook.set_user_properties(&self.user_properties);
+        notebook.set_username(self.auth.username.as_str());
+        notebook.set_password(self.auth.password.as_str());
+    }
+}
+
+impl From<&MQTTySubscription> for WorkspaceSubscription {
+    fn from(subscription: &MQTTySubscription) -> Self {
+        let options = subscription.options();
+
+        Self {
+            topic_filter: subscription.topic_filter(),
+            qos: options.qos(),
+            no_local: options.no_local(),
+            retain_as_published: options.retain_as_published(),
+            retain_handling: options.retain_handling(),
+            subscription_identifier: options.subscription_identifier(),
+        }
+    }
+}
+
+impl From<&WorkspaceSubscription> for MQTTySubscribeOptions {
+    fn from(subscription: &WorkspaceSubscription) -> Self {
+        let options = MQTTySubscribeOptions::new(subscription.qos);
+        options.set_no_local(subscription.no_local);
+        options.set_retain_as_published(subscription.retain_as_published);
+        options.set_retain_handling(subscription.retain_handling);
+        options.set_subscription_identifier(subscription.subscription_identifier);
+        options
+    }
+}
+
+impl MQTTyWorkspace {
+    /// Captures the current state of the views
+    pub fn new(
+        publish_view: &MQTTyPublishView,
+        subscriptions_view: &MQTTySubscriptionsView,
+    ) -> Self {
+        Self {
+            version: WORKSPACE_VERSION,
+            publish_tabs: publish_view.tabs().iter().map(WorkspaceTab::from).collect(),
+            subscriptions: WorkspaceSubscriptions {
+                url: subscriptions_view.url(),
+                mqtt_version: subscriptions_view.mqtt_version(),
+                auth: WorkspaceAuth::new(
+                    &subscriptions_view.url(),
+                    subscriptions_view.username(),
+                    subscriptions_view.password(),
+                ),
+                subscriptions: subscriptions_view
+                    .subscriptions()
+                    .iter::<MQTTySubscription>()
+                    .flatten()
+                    .map(|subscription| WorkspaceSubscription::from(&subscription))
+                    .collect(),
+            },
+        }
+    }
+
+    /// Replaces the tabs and subscriptions of the views with the ones of the workspace
+    pub fn apply(
+        &self,
+        publish_view: &MQTTyPublishView,
+        subscriptions_view: &MQTTySubscriptionsView,
+    ) {
+        publish_view.close_all_tabs();
+
+        for tab in &self.publish_tabs {
+            tab.apply(&publish_view.new_tab());
+        }
+
+        let subscriptions = &self.subscriptions;
+
+        subscriptions_view.set_url(subscriptions.url.as_str());
+        subscriptions_view.set_mqtt_version(subscriptions.mqtt_version);
+        subscriptions_view.set_username(subscriptions.auth.username.as_str());
+        subscriptions_view.set_password(subscriptions.auth.password.as_str());
+
+        subscriptions_view.remove_all_subscriptions();
+
+        for subscription in &subscriptions.subscriptions {
+            subscriptions_view.add_subscription(
+                &subscription.topic_filter,
+                &MQTTySubscribeOptions::from(subscription),
+            );
+        }
+    }
+
+    pub async fn load(file: &gio::File) -> Result<Self, glib::Error> {
+        let (contents, _) = file.load_contents_future().await?;
+
+        let mut workspace = serde_json::from_slice::<Self>(&contents)
+            .map_err(|e| glib::Error::new(gio::IOErrorEnum::InvalidData, &e.to_string()))?;
+
+        if workspace.version > WORKSPACE_VERSION {
+            return Err(glib::Error::new(
+                gio::IOErrorEnum::NotSupported,
+                &gettext("The workspace was saved by a newer version of MQTTy"),
+            ));
+        }
+
+        for auth in workspace.auths_mut() {
+            if auth.credentials_id.is_empty() {
+                continue;
+            }
+
+            match credentials::lookup_password(&auth.credentials_id).await {
+                Ok(password) => auth.password = password.unwrap_or_default(),
+                Err(e) => tracing::warn!("Could not retrieve a workspace password: {}", e),
+            }
+        }
+
+        Ok(workspace)
+    }
+
+    pub async fn save(&self, file: &gio::File) -> Result<(), glib::Error> {
+        for (url, auth) in self.auths() {
+            if auth.credentials_id.is_empty() {
+                continue;
+            }
+
+            let label = formatx!(gettext("MQTTy password for {}"), url).unwrap_or_default();
+
+            credentials::store_password(&auth.credentials_id, &label, &auth.password)
+                .await
+                .map_err(|e| glib::Error::new(gio::IOErrorEnum::Failed, &e.to_string()))?;
+        }
+
+        let contents = serde_json::to_vec_pretty(self)
+            .map_err(|e| glib::Error::new(gio::IOErrorEnum::InvalidData, &e.to_string()))?;
+
+        file.replace_contents_future(
+            contents,
+            None,
+            false,
+            gio::FileCreateFlags::REPLACE_DESTINATION,
+        )
+        .await
+        .map_err(|(_, e)| e)?;
+
+        Ok(())
+    }
+
+    fn auths(&self) -> impl Iterator<Item = (&String, &WorkspaceAuth)> {
+        self.publish_tabs
+            .iter()
+            .map(|tab| (&tab.url, &tab.auth))
+            .chain(std::iter::once((
+                &self.subscriptions.url,
+                &self.subscriptions.auth,
+            )))
+    }
+
+    fn auths_mut(&mut self) -> impl Iterator<Item = &mut WorkspaceAuth> {
+        self.publish_tabs
+            .iter_mut()
+            .map(|tab| &mut tab.auth)
+            .chain(std::iter::once(&mut self.subscriptions.auth))
+    }
+}
+
+/// Workspaces opened or saved lately, most recent first
+pub fn recent_workspaces() -> Vec<gio::File> {
+    MQTTyApplication::get_singleton()
+        .settings()
+        .strv("recent-workspaces")
+        .iter()
+        .map(|uri| gio::File::for_uri(uri))
+        .collect()
+}
+
+/// Moves `file` to the top of the recent workspaces
+pub fn add_recent_workspace(file: &gio::File) {
+    let uri = file.uri();
+
+    let mut uris = vec![uri.clone()];
+    uris.extend(
+        MQTTyApplication::get_singleton()
+            .settings()
+            .strv("recent-workspaces")
+            .into_iter()
+            .filter(|u| *u != uri)
+            .take(RECENT_WORKSPACES_LIMIT - 1),
+    );
+
+    set_recent_workspaces(&uris);
+}
+
+pub fn remove_recent_workspace(file: &gio::File) {
+    let uri = file.uri();
+
+    let uris = MQTTyApplication::get_singleton()
+        .settings()
+        .strv("recent-workspaces")
+        .into_iter()
+        .filter(|u| *u != uri)
+        .collect::<Vec<_>>();
+
+    set_recent_workspaces(&uris);
+}
+
+fn set_recent_workspaces(uris: &[glib::GString]) {
+    let uris = uris.iter().map(|uri| uri.as_str()).collect::<Vec<_>>();
+
+    if let Err(e) = MQTTyApplication::get_singleton()
+        .settings()
+        .set_strv("recent-workspaces", uris)
+    {
+        tracing::error!("Could not save the recent workspaces: {}", e);
+    }
+}